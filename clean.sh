@@ -1,4 +1,4 @@
 #!/bin/bash
 cargo clean
-rm -rf lotka_volterra.png
+rm -rf lotka_volterra.png lotka_volterra.svg sensitivity.png sensitivity.csv spatial.gif spatial_frames metapopulation_patches.png metapopulation_global.png agents.png sde.png cobweb.png map_phase.png bifurcation.png continuation.png continuation.svg continuation.csv orbit_diagram.csv phase_portrait.png report.png report.html trajectory.gif
 echo "             Images removed"
@@ -0,0 +1,279 @@
+use crate::error::SimulationError;
+use crate::stochastic::LotkaVolterraParams;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+type AgentCounts = Result<(Vec<f64>, Vec<f64>, Vec<f64>), SimulationError>;
+
+/// Lattice settings of the agent-based simulation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AgentParameters {
+    pub width: usize,
+    pub height: usize,
+    pub seed: Option<u64>,
+    pub max_agents: usize, // The run stops once the population exceeds this
+}
+
+impl Default for AgentParameters {
+    fn default() -> Self {
+        Self {
+            width: 64,
+            height: 64,
+            seed: None,
+            max_agents: 1_000_000,
+        }
+    }
+}
+
+/// Individual-based predator-prey model on a periodic lattice.
+///
+/// Each time step every agent moves to a random neighbouring cell, then predators hunt
+/// the prey sharing their cell, prey reproduce and predators die. Rates come from
+/// [`LotkaVolterraParams`]: prey reproduce at rate `alpha`, predators die at rate
+/// `delta`, and each predator kills a prey in its cell at rate `beta * cells`, which
+/// matches the mass-action term `beta * x * y` when the agents are well mixed. Every
+/// kill produces a predator offspring with probability `gamma / beta`, so `gamma` may not
+/// exceed `beta`.
+pub struct AgentSimulation {
+    pub params: LotkaVolterraParams,
+    pub lattice: AgentParameters,
+    pub prey: Vec<usize>,      // Cell index of every prey
+    pub predators: Vec<usize>, // Cell index of every predator
+    pub time: f64,
+    pub times: Vec<f64>,
+    pub prey_counts: Vec<f64>,
+    pub predator_counts: Vec<f64>,
+    rng: StdRng,
+}
+
+impl AgentSimulation {
+    /// Places the initial populations uniformly at random on the lattice.
+    pub fn new(params: LotkaVolterraParams, lattice: AgentParameters) -> Self {
+        let mut rng = match lattice.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_rng(&mut rand::rng()),
+        };
+        let cells = lattice.width * lattice.height;
+        let mut place = |n: f64| -> Vec<usize> {
+            (0..n.max(0.0).round() as usize)
+                .map(|_| rng.random_range(0..cells))
+                .collect()
+        };
+        let prey = place(params.initial_prey);
+        let predators = place(params.initial_predator);
+        let mut simulation = Self {
+            params,
+            lattice,
+            prey,
+            predators,
+            time: 0.0,
+            times: Vec::new(),
+            prey_counts: Vec::new(),
+            predator_counts: Vec::new(),
+            rng,
+        };
+        simulation.record();
+        simulation
+    }
+
+    fn cells(&self) -> usize {
+        self.lattice.width * self.lattice.height
+    }
+
+    fn record(&mut self) {
+        self.times.push(self.time);
+        self.prey_counts.push(self.prey.len() as f64);
+        self.predator_counts.push(self.predators.len() as f64);
+    }
+
+    /// Moves an agent to one of the four neighbouring cells.
+    fn neighbour(&mut self, cell: usize) -> usize {
+        let (w, h) = (self.lattice.width, self.lattice.height);
+        let (i, j) = (cell % w, cell / w);
+        let (i, j) = match self.rng.random_range(0..4) {
+            0 => ((i + 1) % w, j),
+            1 => ((i + w - 1) % w, j),
+            2 => (i, (j + 1) % h),
+            _ => (i, (j + h - 1) % h),
+        };
+        j * w + i
+    }
+
+    /// Advances the simulation by one time step `dt` and records the counts.
+    pub fn step(&mut self) -> Result<(), SimulationError> {
+        let dt = self.params.dt;
+        let cells = self.cells();
+
+        for k in 0..self.prey.len() {
+            self.prey[k] = self.neighbour(self.prey[k]);
+        }
+        for k in 0..self.predators.len() {
+            self.predators[k] = self.neighbour(self.predators[k]);
+        }
+
+        // Hunting: a prey with k predators in its cell is caught with rate k * beta * cells
+        let mut hunters = vec![0u32; cells];
+        for &cell in &self.predators {
+            hunters[cell] += 1;
+        }
+        let hunting_rate = self.params.beta * cells as f64;
+        let conversion = if self.params.beta > 0.0 {
+            self.params.gamma / self.params.beta
+        } else {
+            0.0
+        };
+        let mut survivors = Vec::with_capacity(self.prey.len());
+        for k in 0..self.prey.len() {
+            let cell = self.prey[k];
+            let caught = hunters[cell] > 0
+                && self.rng.random::<f64>()
+                    < 1.0 - (-hunting_rate * hunters[cell] as f64 * dt).exp();
+            if !caught {
+                survivors.push(cell);
+            } else if self.rng.random::<f64>() < conversion {
+                self.predators.push(cell);
+            }
+        }
+        self.prey = survivors;
+
+        // Prey births and predator deaths
+        let birth = 1.0 - (-self.params.alpha * dt).exp();
+        for k in 0..self.prey.len() {
+            if self.rng.random::<f64>() < birth {
+                let cell = self.prey[k];
+                self.prey.push(cell);
+            }
+        }
+        let death = 1.0 - (-self.params.delta * dt).exp();
+        let rng = &mut self.rng;
+        self.predators.retain(|_| rng.random::<f64>() >= death);
+
+        self.time += dt;
+        self.record();
+
+        if self.prey.len() + self.predators.len() > self.lattice.max_agents {
+            return Err(SimulationError::SolverError(format!(
+                "The population exceeded {} agents at t = {:.1}.",
+                self.lattice.max_agents, self.time
+            )));
+        }
+        Ok(())
+    }
+
+    /// Number of prey and predators in every cell, row by row.
+    pub fn occupancy(&self) -> (Vec<u32>, Vec<u32>) {
+        let mut prey = vec![0; self.cells()];
+        let mut predators = vec![0; self.cells()];
+        for &cell in &self.prey {
+            prey[cell] += 1;
+        }
+        for &cell in &self.predators {
+            predators[cell] += 1;
+        }
+        (prey, predators)
+    }
+}
+
+/// Checks that the lattice, time step and rates allow a simulation.
+pub fn validate_agents(
+    params: &LotkaVolterraParams,
+    lattice: &AgentParameters,
+) -> Result<(), SimulationError> {
+    if lattice.width == 0 || lattice.height == 0 {
+        return Err(SimulationError::InvalidParameter(
+            "The lattice must have at least one cell.".to_string(),
+        ));
+    }
+    if params.dt <= 0.0 {
+        return Err(SimulationError::InvalidParameter(
+            "The time step must be positive.".to_string(),
+        ));
+    }
+    if params.gamma > params.beta {
+        return Err(SimulationError::InvalidParameter(format!(
+            "Each caught prey yields at most one predator, so the predator birth rate ({}) \
+             cannot exceed the predation rate beta ({}).",
+            params.gamma, params.beta
+        )));
+    }
+    Ok(())
+}
+
+/// Runs the agent-based model for `params.n` steps and returns the times and the prey
+/// and predator counts.
+pub fn simulate_agents(params: &LotkaVolterraParams, lattice: AgentParameters) -> AgentCounts {
+    validate_agents(params, &lattice)?;
+    let mut simulation = AgentSimulation::new(params.clone(), lattice);
+    for _ in 0..params.n {
+        simulation.step()?;
+    }
+    Ok((
+        simulation.times,
+        simulation.prey_counts,
+        simulation.predator_counts,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_params() -> LotkaVolterraParams {
+        LotkaVolterraParams {
+            alpha: 0.01,
+            beta: 0.00001,
+            gamma: 0.00001,
+            delta: 0.01,
+            dt: 1.0,
+            n: 200,
+            initial_prey: 500.0,
+            initial_predator: 300.0,
+        }
+    }
+
+    #[test]
+    fn test_seeded_runs_are_reproducible() {
+        let lattice = AgentParameters {
+            width: 32,
+            height: 32,
+            seed: Some(7),
+            ..AgentParameters::default()
+        };
+        let first = simulate_agents(&test_params(), lattice).unwrap();
+        let second = simulate_agents(&test_params(), lattice).unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(first.0.len(), 201);
+        assert_eq!(first.1[0], 500.0);
+    }
+
+    #[test]
+    fn test_prey_grow_without_predators() {
+        let params = LotkaVolterraParams {
+            initial_predator: 0.0,
+            alpha: 0.05,
+            n: 20,
+            ..test_params()
+        };
+        let lattice = AgentParameters {
+            seed: Some(1),
+            ..AgentParameters::default()
+        };
+        let (_, prey, predators) = simulate_agents(&params, lattice).unwrap();
+
+        // Expected growth is exp(0.05 * 20) = 2.7
+        let ratio = prey.last().unwrap() / prey[0];
+        assert!(ratio > 2.3 && ratio < 3.1, "ratio = {}", ratio);
+        assert!(predators.iter().all(|&n| n == 0.0));
+    }
+
+    #[test]
+    fn test_birth_rate_above_predation_rate_is_rejected() {
+        let params = LotkaVolterraParams {
+            gamma: 2.0 * test_params().beta,
+            ..test_params()
+        };
+        let error = simulate_agents(&params, AgentParameters::default()).unwrap_err();
+        assert!(error.to_string().contains("predation rate"), "{}", error);
+    }
+}
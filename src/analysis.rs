@@ -0,0 +1,247 @@
+use crate::models::LotkaVolterraParameters;
+
+/// Returns the indices of the strict local maxima of a series.
+pub fn local_maxima(values: &[f64]) -> Vec<usize> {
+    (1..values.len().saturating_sub(1))
+        .filter(|&i| values[i] > values[i - 1] && values[i] >= values[i + 1])
+        .collect()
+}
+
+/// Largest value of a series, or zero for an empty series.
+pub fn peak(values: &[f64]) -> f64 {
+    values.iter().copied().fold(0.0, f64::max)
+}
+
+/// Estimates the oscillation period as the mean spacing between successive peaks.
+/// Returns `None` when fewer than two peaks are present.
+pub fn estimate_period(times: &[f64], values: &[f64]) -> Option<f64> {
+    let peaks = local_maxima(values);
+    if peaks.len() < 2 {
+        return None;
+    }
+    let first = times[peaks[0]];
+    let last = times[peaks[peaks.len() - 1]];
+    Some((last - first) / (peaks.len() - 1) as f64)
+}
+
+/// Checks whether either population reached zero along a trajectory.
+pub fn went_extinct(prey: &[f64], predators: &[f64]) -> bool {
+    prey.iter().chain(predators.iter()).any(|&n| n <= 0.0)
+}
+
+/// The Lotka-Volterra first integral `V = delta x - gamma ln x + beta y - alpha ln y`,
+/// which is constant along every orbit with positive populations.
+pub fn conserved_quantity(params: &LotkaVolterraParameters, prey: f64, predators: f64) -> f64 {
+    params.delta * prey - params.gamma * prey.ln() + params.beta * predators
+        - params.alpha * predators.ln()
+}
+
+/// Drift of the first integral relative to its initial value, `(V(t) - V(0)) / |V(0)|`.
+/// Along an exact solution the drift is zero, so it measures the integration error.
+pub fn conserved_drift(
+    params: &LotkaVolterraParameters,
+    prey: &[f64],
+    predators: &[f64],
+) -> Vec<f64> {
+    let values: Vec<f64> = prey
+        .iter()
+        .zip(predators)
+        .map(|(&x, &y)| conserved_quantity(params, x, y))
+        .collect();
+    let initial = values.first().copied().unwrap_or_default();
+    let scale = if initial != 0.0 { initial.abs() } else { 1.0 };
+    values.iter().map(|v| (v - initial) / scale).collect()
+}
+
+/// Time average of a series sampled at `times`, using the trapezoidal rule.
+pub fn time_average(times: &[f64], values: &[f64]) -> f64 {
+    let span = match (times.first(), times.last()) {
+        (Some(first), Some(last)) if last > first => last - first,
+        _ => return values.first().copied().unwrap_or_default(),
+    };
+    let integral: f64 = times
+        .windows(2)
+        .zip(values.windows(2))
+        .map(|(t, v)| (t[1] - t[0]) * (v[0] + v[1]) / 2.0)
+        .sum();
+    integral / span
+}
+
+/// Derived metrics of a trajectory, as printed in reports.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrajectorySummary {
+    pub prey_period: Option<f64>,
+    pub predator_period: Option<f64>,
+    pub mean_prey: f64,
+    pub mean_predator: f64,
+    pub peak_prey: f64,
+    pub peak_predator: f64,
+    pub extinct: bool,
+    /// Largest absolute relative drift of the first integral
+    pub max_drift: f64,
+}
+
+/// Computes the derived metrics of a trajectory.
+pub fn summarize(
+    params: &LotkaVolterraParameters,
+    times: &[f64],
+    prey: &[f64],
+    predators: &[f64],
+) -> TrajectorySummary {
+    TrajectorySummary {
+        prey_period: estimate_period(times, prey),
+        predator_period: estimate_period(times, predators),
+        mean_prey: time_average(times, prey),
+        mean_predator: time_average(times, predators),
+        peak_prey: peak(prey),
+        peak_predator: peak(predators),
+        extinct: went_extinct(prey, predators),
+        max_drift: conserved_drift(params, prey, predators)
+            .into_iter()
+            .map(f64::abs)
+            .filter(|d| d.is_finite())
+            .fold(0.0, f64::max),
+    }
+}
+
+/// Value of a series sampled at `times` by linear interpolation, or `None` outside the
+/// samples.
+pub fn interpolate(times: &[f64], values: &[f64], t: f64) -> Option<f64> {
+    let i = times.partition_point(|&s| s < t);
+    if i == times.len() {
+        return None;
+    }
+    if times[i] == t {
+        return Some(values[i]);
+    }
+    if i == 0 {
+        return None;
+    }
+    let w = (t - times[i - 1]) / (times[i] - times[i - 1]);
+    Some(values[i - 1] + w * (values[i] - values[i - 1]))
+}
+
+/// Index of the point whose first coordinate is closest to `x`, for points sorted by
+/// their first coordinate, such as `[t, value]` samples.
+pub fn nearest_sample(points: &[[f64; 2]], x: f64) -> Option<usize> {
+    let i = points.partition_point(|p| p[0] < x);
+    match (i.checked_sub(1), points.get(i)) {
+        (Some(before), Some(after)) if x - points[before][0] <= after[0] - x => Some(before),
+        (_, Some(_)) => Some(i),
+        (before, None) => before,
+    }
+}
+
+/// Index of the point closest to `target`, with each coordinate measured in units of
+/// `scale` so that axes of different magnitude count alike.
+pub fn nearest_point(points: &[[f64; 2]], target: [f64; 2], scale: [f64; 2]) -> Option<usize> {
+    let unit = |s: f64| if s > 0.0 { s } else { 1.0 };
+    let distance = |p: &[f64; 2]| {
+        ((p[0] - target[0]) / unit(scale[0])).powi(2)
+            + ((p[1] - target[1]) / unit(scale[1])).powi(2)
+    };
+    (0..points.len()).min_by(|&a, &b| distance(&points[a]).total_cmp(&distance(&points[b])))
+}
+
+/// Differences `other - reference` of two trajectories, given as times, prey and
+/// predators, at the reference times covered by both. Returns `[t, prey, predators]`.
+pub fn trajectory_difference(
+    reference: (&[f64], &[f64], &[f64]),
+    other: (&[f64], &[f64], &[f64]),
+) -> Vec<[f64; 3]> {
+    let (times, prey, predators) = reference;
+    times
+        .iter()
+        .zip(prey.iter().zip(predators))
+        .filter_map(|(&t, (&x, &y))| {
+            let other_x = interpolate(other.0, other.1, t)?;
+            let other_y = interpolate(other.0, other.2, t)?;
+            Some([t, other_x - x, other_y - y])
+        })
+        .collect()
+}
+
+/// First time at which the prey or predator difference exceeds its threshold.
+pub fn divergence_time(difference: &[[f64; 3]], threshold: [f64; 2]) -> Option<f64> {
+    difference
+        .iter()
+        .find(|[_, dx, dy]| dx.abs() > threshold[0] || dy.abs() > threshold[1])
+        .map(|d| d[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_period_of_sine() {
+        let times: Vec<f64> = (0..2000).map(|i| i as f64 * 0.01).collect();
+        let values: Vec<f64> = times
+            .iter()
+            .map(|t| (2.0 * std::f64::consts::PI * t / 4.0).sin())
+            .collect();
+        let period = estimate_period(&times, &values).unwrap();
+        assert!((period - 4.0).abs() < 0.02, "Period was {}", period);
+    }
+
+    #[test]
+    fn test_estimate_period_requires_two_peaks() {
+        assert!(estimate_period(&[0.0, 1.0, 2.0], &[0.0, 1.0, 0.0]).is_none());
+    }
+
+    #[test]
+    fn test_summary_of_solution() {
+        let params = LotkaVolterraParameters::default();
+        let y0 = [params.initial_prey, params.initial_predator];
+        let (times, prey, predators) =
+            crate::solver::solve_lotka_volterra(params, y0, 0.0, params.t_end, 0.1).unwrap();
+        let summary = summarize(&params, &times, &prey, &predators);
+
+        // The time averages over whole cycles equal the coexistence equilibrium
+        assert!((summary.mean_prey - 1000.0).abs() < 50.0, "{:?}", summary);
+        assert!(
+            (summary.mean_predator - 1000.0).abs() < 50.0,
+            "{:?}",
+            summary
+        );
+        assert!(summary.max_drift < 1e-3);
+        assert!(!summary.extinct);
+        assert!(summary.prey_period.is_some());
+    }
+
+    #[test]
+    fn test_nearest_sample_and_point() {
+        let points = [[0.0, 5.0], [1.0, 100.0], [2.0, 5.0]];
+        assert_eq!(nearest_sample(&points, -1.0), Some(0));
+        assert_eq!(nearest_sample(&points, 1.4), Some(1));
+        assert_eq!(nearest_sample(&points, 1.6), Some(2));
+        assert_eq!(nearest_sample(&points, 9.0), Some(2));
+        assert_eq!(nearest_sample(&[], 1.0), None);
+
+        // Unscaled, the second coordinate dominates; scaled, both count alike
+        assert_eq!(nearest_point(&points, [0.1, 60.0], [1.0, 1.0]), Some(1));
+        assert_eq!(nearest_point(&points, [0.1, 60.0], [1.0, 100.0]), Some(0));
+    }
+
+    #[test]
+    fn test_trajectory_difference_and_divergence() {
+        let times = [0.0, 1.0, 2.0, 3.0];
+        let reference = (
+            &times[..],
+            &[1.0, 1.0, 1.0, 1.0][..],
+            &[2.0, 2.0, 2.0, 2.0][..],
+        );
+        // The other run is sampled more coarsely and stops earlier
+        let other = (&[0.0, 2.0][..], &[1.0, 5.0][..], &[2.0, 2.0][..]);
+        assert_eq!(interpolate(other.0, other.1, 1.0), Some(3.0));
+        assert_eq!(interpolate(other.0, other.1, 2.5), None);
+
+        let difference = trajectory_difference(reference, other);
+        assert_eq!(
+            difference,
+            vec![[0.0, 0.0, 0.0], [1.0, 2.0, 0.0], [2.0, 4.0, 0.0]]
+        );
+        assert_eq!(divergence_time(&difference, [3.0, 1.0]), Some(2.0));
+        assert_eq!(divergence_time(&difference, [5.0, 1.0]), None);
+    }
+}
@@ -0,0 +1,219 @@
+use crate::error::SimulationError;
+use plotters::prelude::*;
+use std::error::Error;
+
+/// Settings of an animated trajectory.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnimationSettings {
+    /// Frames per second of the GIF
+    pub frame_rate: u32,
+    /// Number of frames
+    pub frames: usize,
+    /// Time window to animate; the whole run when `None`
+    pub window: Option<(f64, f64)>,
+    /// Length of time of the trailing tail in the phase plane; the whole path when `None`
+    pub tail: Option<f64>,
+    pub size: (u32, u32),
+}
+
+impl Default for AnimationSettings {
+    fn default() -> Self {
+        Self {
+            frame_rate: 20,
+            frames: 200,
+            window: None,
+            tail: None,
+            size: (1200, 550),
+        }
+    }
+}
+
+impl AnimationSettings {
+    /// Checks the frame rate, frame count, window and tail.
+    pub fn validate(&self) -> Result<(), SimulationError> {
+        let bad_window = matches!(
+            self.window,
+            Some((start, end)) if start >= end || start.is_nan() || end.is_nan()
+        );
+        let bad_tail = matches!(self.tail, Some(tail) if tail <= 0.0 || tail.is_nan());
+        if self.frame_rate == 0 || self.frame_rate > 100 || self.frames == 0 {
+            return Err(SimulationError::InvalidParameter(
+                "The frame rate must be between 1 and 100 and there must be at least one frame."
+                    .to_string(),
+            ));
+        }
+        if bad_window || bad_tail {
+            return Err(SimulationError::InvalidParameter(
+                "The time window must satisfy start < end and the tail must be positive."
+                    .to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Delay between frames in milliseconds.
+    pub fn frame_delay(&self) -> u32 {
+        1000 / self.frame_rate
+    }
+}
+
+/// Index ranges of the samples shown in each frame. Frame `k` ends at the last sample
+/// no later than its time, with the frame times evenly spread over the window; the range
+/// starts at the beginning of the window or, with a tail, `tail` time units earlier.
+pub fn frame_ranges(times: &[f64], settings: &AnimationSettings) -> Vec<(usize, usize)> {
+    let (Some(&first), Some(&last)) = (times.first(), times.last()) else {
+        return Vec::new();
+    };
+    let (start, end) = settings.window.unwrap_or((first, last));
+    let (start, end) = (start.max(first), end.min(last));
+    if start > end {
+        return Vec::new();
+    }
+    let begin = times.partition_point(|&t| t < start);
+
+    (1..=settings.frames)
+        .map(|k| {
+            let t = start + (end - start) * k as f64 / settings.frames as f64;
+            let head = times.partition_point(|&u| u <= t + 1e-9).max(begin + 1);
+            let tail_start = match settings.tail {
+                Some(tail) => times.partition_point(|&u| u < t - tail).max(begin),
+                None => begin,
+            };
+            (tail_start, head)
+        })
+        .collect()
+}
+
+/// Animates a trajectory as a GIF: the populations are traced over the time window on
+/// the left while the phase-plane path, with a fading trailing tail, is traced on the
+/// right. Works for any sampled path, deterministic or stochastic.
+pub fn animate_trajectory(
+    times: &[f64],
+    prey: &[f64],
+    predators: &[f64],
+    settings: &AnimationSettings,
+    title: &str,
+    output_file: &str,
+) -> Result<(), Box<dyn Error>> {
+    settings.validate()?;
+    let ranges = frame_ranges(times, settings);
+    let Some(&(begin, _)) = ranges.first() else {
+        return Err(SimulationError::InvalidParameter(
+            "The time window contains no samples.".to_string(),
+        )
+        .into());
+    };
+    let end = ranges.last().map_or(begin, |r| r.1);
+    let window = begin..end;
+
+    // Axes are fixed over the whole animation
+    let max = |values: &[f64]| values[window.clone()].iter().copied().fold(1.0, f64::max);
+    let (prey_max, predator_max) = (max(prey) * 1.05, max(predators) * 1.05);
+    let t_range = times[begin]..times[end - 1].max(times[begin] + 1e-9);
+    let population_max = prey_max.max(predator_max);
+
+    let root =
+        BitMapBackend::gif(output_file, settings.size, settings.frame_delay())?.into_drawing_area();
+    for &(tail_start, head) in &ranges {
+        root.fill(&WHITE)?;
+        let now = times[head - 1];
+        let area = root.titled(&format!("{}   t = {:.1}", title, now), ("sans-serif", 26))?;
+        let (left, right) = area.split_horizontally(settings.size.0 * 11 / 20);
+
+        let mut chart = ChartBuilder::on(&left)
+            .margin(15)
+            .x_label_area_size(40)
+            .y_label_area_size(60)
+            .build_cartesian_2d(t_range.clone(), 0.0..population_max)?;
+        chart
+            .configure_mesh()
+            .x_desc("Time")
+            .y_desc("Population")
+            .draw()?;
+        let traced = begin..head;
+        chart
+            .draw_series(LineSeries::new(
+                traced.clone().map(|i| (times[i], prey[i])),
+                BLUE.stroke_width(2),
+            ))?
+            .label("Prey")
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLUE.stroke_width(2)));
+        chart
+            .draw_series(LineSeries::new(
+                traced.map(|i| (times[i], predators[i])),
+                RED.stroke_width(2),
+            ))?
+            .label("Predators")
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED.stroke_width(2)));
+        chart
+            .configure_series_labels()
+            .position(SeriesLabelPosition::UpperRight)
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()?;
+
+        let mut chart = ChartBuilder::on(&right)
+            .margin(15)
+            .x_label_area_size(40)
+            .y_label_area_size(60)
+            .build_cartesian_2d(0.0..prey_max, 0.0..predator_max)?;
+        chart
+            .configure_mesh()
+            .x_desc("Prey")
+            .y_desc("Predators")
+            .draw()?;
+        // The tail fades from light to dark purple towards the current state
+        let segments = 20.min(head - tail_start).max(1);
+        for s in 0..segments {
+            let from = tail_start + (head - tail_start) * s / segments;
+            let to = (tail_start + (head - tail_start) * (s + 1) / segments + 1).min(head);
+            let shade = 1.0 - (s + 1) as f64 / segments as f64;
+            let color = RGBColor(
+                (120.0 + 120.0 * shade) as u8,
+                (60.0 + 180.0 * shade) as u8,
+                (160.0 + 85.0 * shade) as u8,
+            );
+            chart.draw_series(LineSeries::new(
+                (from..to).map(|i| (prey[i], predators[i])),
+                color.stroke_width(2),
+            ))?;
+        }
+        chart.draw_series([Circle::new(
+            (prey[head - 1], predators[head - 1]),
+            6,
+            RGBColor(120, 60, 160).filled(),
+        )])?;
+        root.present()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_ranges_follow_window_and_tail() {
+        let times: Vec<f64> = (0..=100).map(|i| i as f64).collect();
+        let settings = AnimationSettings {
+            frames: 4,
+            window: Some((20.0, 60.0)),
+            tail: Some(15.0),
+            ..AnimationSettings::default()
+        };
+        let ranges = frame_ranges(&times, &settings);
+
+        assert_eq!(ranges.len(), 4);
+        // Frame ends at t = 30, 40, 50, 60; tails start 15 time units earlier
+        assert_eq!(ranges[0], (20, 31));
+        assert_eq!(ranges[3], (45, 61));
+        assert!(
+            AnimationSettings {
+                frame_rate: 0,
+                ..settings
+            }
+            .validate()
+            .is_err()
+        );
+    }
+}
@@ -1,44 +1,170 @@
 use clap::Parser;
 use lotka_volterra::{
-    cli::Cli, error::SimulationError, gui::launch_gui, interactive::interactive_mode, models::*,
-    plot::*, solver::*,
+    agents::*,
+    analysis::summarize,
+    animation::animate_trajectory,
+    cli::{
+        BoundaryKind, Cli, MapChoice, NoiseColor, SchemeKind, SensitivityMethod, SensitivityOutput,
+    },
+    continuation::*,
+    dde::{DelayLotkaVolterraSystem, solve_delay_lotka_volterra, solve_run, validate_delay},
+    discrete::*,
+    error::SimulationError,
+    gui::{launch_gui, launch_gui_with},
+    interactive::{MenuChoice, interactive_mode},
+    interventions::Schedule,
+    metapopulation::*,
+    models::*,
+    phase::{autonomous_params, phase_orbits},
+    plot::*,
+    plot_config::PlotConfig,
+    report::RunReport,
+    scenario::Scenario,
+    sde::*,
+    sensitivity::*,
+    session::View,
+    solver::*,
+    spatial::*,
+    stochastic::{LotkaVolterraParams, simulate_gillespie_with_interventions},
+    worker::Progress,
 };
 use std::error::Error;
 
 /// Parses the command-line for the arguments needed to solve the Lotka-Volterra
 /// differential equation. By default, the program launches an interactive menu
 /// when no command-line arguments are supplied. The program will save the plot
-/// as a graph as a png in the directory of the project.
+/// as a graph as a png (or svg) in the directory of the project.
 fn main() -> Result<(), Box<dyn Error>> {
     let cli = Cli::parse();
 
+    // Parameter file with harvesting and forcing terms, if given
+    let scenario = cli.scenario.as_deref().map(Scenario::load).transpose()?;
+    let Scenario {
+        params,
+        terms,
+        schedule,
+        delay,
+        plot,
+    } = scenario.clone().unwrap_or_default();
+    // Parameters given on the command line take precedence over the file's
+    let params = cli.parameters_over(params);
+    let delay = cli.delay.or(delay);
+    validate_delay(delay, &schedule)?;
+    let plot = cli.plot_config(plot)?;
+
+    // Runs the spatial model instead of the well-mixed one
+    if cli.spatial {
+        let scenario = Scenario {
+            params,
+            terms: terms.clone(),
+            schedule: schedule.clone(),
+            delay,
+            plot: plot.clone(),
+        };
+        return run_spatial(&cli, scenario);
+    }
+
+    // Runs the agent-based model on a lattice
+    if cli.agents {
+        let scenario = Scenario {
+            params,
+            terms: terms.clone(),
+            schedule: schedule.clone(),
+            delay,
+            plot: plot.clone(),
+        };
+        return run_agents(&cli, scenario);
+    }
+
+    // Continues equilibria and detects bifurcations
+    if let Some(sweep) = &cli.continuation {
+        return run_continuation(&cli, sweep);
+    }
+
+    // Iterates a discrete-time host-parasitoid map
+    if let Some(choice) = cli.map {
+        return run_map(&cli, choice);
+    }
+
+    // Runs an ensemble of the environmental noise model
+    if let Some(color) = cli.sde {
+        return run_sde(&cli, color, params, &terms, &schedule, delay);
+    }
+
+    // Runs the metapopulation model over a patch network
+    if let Some(network) = &cli.network {
+        return run_metapopulation(&cli, network, params);
+    }
+
+    // Exports a phase portrait
+    if cli.phase_portrait {
+        return run_phase_portrait(&cli, params, &terms, &schedule, delay);
+    }
+
+    // Exports the composite report figure
+    if let Some(file) = &cli.report_figure {
+        return run_report_figure(params, &terms, &schedule, delay, file);
+    }
+
+    // Exports an animation of the deterministic or stochastic trajectory
+    if let Some(file) = &cli.animate {
+        return run_animation(&cli, params, &terms, &schedule, delay, file);
+    }
+
+    // Writes the HTML report of a run
+    if let Some(file) = &cli.report {
+        return run_html_report(params, &terms, &schedule, delay, &plot, file);
+    }
+
     // Skips interactive menu if program is run with the GUI option
     if cli.gui {
         println!("\nLaunching interactive GUI...");
 
-        // GUI begins with default parameters
-        let params = LotkaVolterraParameters::default();
-        launch_gui(params).map_err(|e| SimulationError::GuiError(e.to_string()))?;
+        launch_gui(
+            gui_scenario(Scenario {
+                params,
+                terms,
+                schedule,
+                delay,
+                plot,
+            }),
+            None,
+        )
+        .map_err(|e| SimulationError::GuiError(e.to_string()))?;
 
         return Ok(());
     }
 
-    // Determine parameters for CLI arguments if provided; use interactive mode otherwise
-    let params = if cli.interactive
-        || cli.alpha.is_none()
-        || cli.beta.is_none()
-        || cli.delta.is_none()
-        || cli.gamma.is_none()
-        || cli.initial_prey.is_none()
-        || cli.initial_predator.is_none()
-        || cli.t_start.is_none()
-        || cli.t_end.is_none()
+    // Runs a global sensitivity analysis instead of a single simulation
+    if let Some(method) = cli.sensitivity {
+        let scenario = Scenario {
+            params,
+            terms,
+            schedule,
+            delay,
+            plot,
+        };
+        return run_sensitivity(&cli, method, &scenario);
+    }
+
+    // Use the parameters from the parameter file or CLI arguments if provided;
+    // use interactive mode otherwise
+    let params = if scenario.is_none()
+        && (cli.interactive
+            || cli.alpha.is_none()
+            || cli.beta.is_none()
+            || cli.delta.is_none()
+            || cli.gamma.is_none()
+            || cli.initial_prey.is_none()
+            || cli.initial_predator.is_none()
+            || cli.t_start.is_none()
+            || cli.t_end.is_none())
     {
         match interactive_mode()? {
             // User selected "Use default parameters" or "Enter custom parameters"
-            Some(params) => params,
-            None => {
-                // User selected "Interactive Plot"
+            MenuChoice::Run(params) => params,
+            MenuChoice::Gui(view) => {
+                // User selected "Interactive Plot" or "Interactive Stochastic Plot"
                 println!("\nLaunching interactive GUI...");
                 let params = LotkaVolterraParameters {
                     alpha: 0.01,
@@ -50,47 +176,612 @@ fn main() -> Result<(), Box<dyn Error>> {
                     t_start: 0.0,
                     t_end: 8000.0,
                 };
-                launch_gui(params).map_err(|e| SimulationError::GuiError(e.to_string()))?;
+                launch_gui(
+                    gui_scenario(Scenario {
+                        params,
+                        terms,
+                        schedule,
+                        delay,
+                        plot,
+                    }),
+                    Some(view),
+                )
+                .map_err(|e| SimulationError::GuiError(e.to_string()))?;
                 return Ok(());
             }
         }
     } else {
-        // Use CLI arguments
-        LotkaVolterraParameters {
-            alpha: cli.alpha.unwrap(),
-            beta: cli.beta.unwrap(),
-            delta: cli.delta.unwrap(),
-            gamma: cli.gamma.unwrap(),
-            initial_prey: cli.initial_prey.unwrap(),
-            initial_predator: cli.initial_predator.unwrap(),
-            t_start: cli.t_start.unwrap(),
-            t_end: cli.t_end.unwrap(),
-        }
+        params
     };
 
     // Run simulation with selected mode
     if cli.interactive_plot {
         println!("\nLaunching interactive GUI...");
-        launch_gui(params).map_err(|e| SimulationError::GuiError(e.to_string()))?;
+        launch_gui(
+            gui_scenario(Scenario {
+                params,
+                terms,
+                schedule,
+                delay,
+                plot,
+            }),
+            None,
+        )
+        .map_err(|e| SimulationError::GuiError(e.to_string()))?;
     } else {
         println!("\nRunning simulation...");
 
         // Initial conditions
-        let y0 = [2000.0, 2000.0];
-        let t0 = 0.0;
-        let t_end = 8000.0;
+        let y0 = [params.initial_prey, params.initial_predator];
+        let t0 = params.t_start;
+        let t_end = params.t_end;
         let step = 0.1;
 
-        // Run the simulation
-        let (times, prey, predators) = solve_lotka_volterra(params, y0, t0, t_end, step)
+        // Run the simulation, using the delay model if a maturation delay is given
+        let (times, prey, predators) = if let Some(tau) = delay {
+            let mut system = DelayLotkaVolterraSystem::new(params, tau);
+            system.terms = terms.clone();
+            solve_delay_lotka_volterra(&system, step)?
+        } else {
+            solve_with_interventions(params, &terms, &schedule, y0, t0, t_end, step)
+                .map_err(|e| SimulationError::PlotError(e.to_string()))?
+        };
+
+        // Plot results, marking harvest intervals
+        let file = cli.plot_file(&plot);
+        plot_time_series(&times, &prey, &predators, &terms.harvests, &plot, &file)
             .map_err(|e| SimulationError::PlotError(e.to_string()))?;
 
-        // Plot results
-        plot_lotka_volterra(&times, &prey, &predators, "lotka_volterra.png")
+        println!("Plot saved as {}", file);
+    }
+
+    Ok(())
+}
+
+/// The scenario to open the GUI with, or `None` to resume the last session when nothing
+/// was given on the command line.
+fn gui_scenario(scenario: Scenario) -> Option<Scenario> {
+    (scenario != Scenario::default()).then_some(scenario)
+}
+
+/// Computes Sobol or Morris indices for the requested output, then writes the index
+/// table to sensitivity.csv and a bar chart to sensitivity.png. The model runs with the
+/// harvesting, forcing, interventions and delay of the scenario.
+fn run_sensitivity(
+    cli: &Cli,
+    method: SensitivityMethod,
+    scenario: &Scenario,
+) -> Result<(), Box<dyn Error>> {
+    let ranges = if cli.ranges.is_empty() {
+        ParameterRange::around_rates(&scenario.params, 0.1)
+    } else {
+        cli.ranges
+            .iter()
+            .map(|r| r.parse())
+            .collect::<Result<Vec<ParameterRange>, _>>()?
+    };
+    let output = match cli.sensitivity_output {
+        SensitivityOutput::PeakPrey => ScalarOutput::PeakPrey,
+        SensitivityOutput::Period => ScalarOutput::Period,
+        SensitivityOutput::Extinction => ScalarOutput::ExtinctionProbability {
+            replicates: cli.replicates,
+            dt: cli.stochastic_dt,
+        },
+    };
+    // Every extinction estimate runs a batch of stochastic simulations, so fewer
+    // samples are taken by default
+    let samples = cli.samples.unwrap_or(match output {
+        ScalarOutput::ExtinctionProbability { .. } => 8,
+        _ => 128,
+    });
+    if cli.sensitivity_step <= 0.0 || cli.stochastic_dt <= 0.0 {
+        return Err(SimulationError::InvalidParameter(
+            "The sensitivity step and stochastic dt must be positive.".to_string(),
+        )
+        .into());
+    }
+
+    println!("\nRunning sensitivity analysis...");
+    let step = cli.sensitivity_step;
+    let table = match method {
+        SensitivityMethod::Sobol => {
+            sobol_indices(scenario, &ranges, output, samples, step, cli.seed)?
+        }
+        SensitivityMethod::Morris => {
+            morris_effects(scenario, &ranges, output, samples, 4, step, cli.seed)?
+        }
+    };
+    table.print();
+
+    std::fs::write("sensitivity.csv", table.to_csv())?;
+    plot_sensitivity_indices(&table, "sensitivity.png")
+        .map_err(|e| SimulationError::PlotError(e.to_string()))?;
+
+    println!("Indices saved as sensitivity.csv and sensitivity.png");
+    Ok(())
+}
+
+/// Runs the spatial model, either in the Spatial view of the GUI or by exporting PNG
+/// frames to spatial_frames/ and an animation to spatial.gif.
+fn run_spatial(cli: &Cli, scenario: Scenario) -> Result<(), Box<dyn Error>> {
+    let params = scenario.params;
+    let (nx, ny) = cli.grid_size()?;
+    let spatial = SpatialParameters {
+        nx,
+        ny,
+        prey_diffusion: cli.prey_diffusion,
+        predator_diffusion: cli.predator_diffusion,
+        boundary: match cli.boundary {
+            BoundaryKind::Periodic => Boundary::Periodic,
+            BoundaryKind::Neumann => Boundary::Neumann,
+            BoundaryKind::Dirichlet => Boundary::Dirichlet {
+                prey: cli.boundary_prey,
+                predator: cli.boundary_predator,
+            },
+        },
+        ..SpatialParameters::default()
+    };
+    validate_spatial(&spatial)?;
+
+    if cli.gui || cli.interactive_plot {
+        println!("\nLaunching interactive GUI...");
+        return launch_gui_with(Some(scenario), Some(View::Spatial), move |app| {
+            app.with_spatial(spatial)
+        })
+        .map_err(|e| SimulationError::GuiError(e.to_string()).into());
+    }
+
+    println!("\nRunning spatial simulation...");
+    let snapshots = solve_spatial_lotka_volterra(params, spatial, cli.frames)?;
+
+    std::fs::create_dir_all("spatial_frames")?;
+    plot_spatial_frames(&snapshots, "spatial_frames/frame")
+        .map_err(|e| SimulationError::PlotError(e.to_string()))?;
+    animate_spatial(&snapshots, "spatial.gif", 100)
+        .map_err(|e| SimulationError::PlotError(e.to_string()))?;
+
+    println!("Frames saved in spatial_frames/ and animation saved as spatial.gif");
+    Ok(())
+}
+
+/// Runs the metapopulation model deterministically or stochastically, prints the patch
+/// synchrony and saves per-patch and global population plots.
+fn run_metapopulation(
+    cli: &Cli,
+    network: &str,
+    params: LotkaVolterraParameters,
+) -> Result<(), Box<dyn Error>> {
+    let network = PatchNetwork::load(network)?;
+    let patches = network.patches;
+    let model = Metapopulation::new(params, network, cli.prey_migration, cli.predator_migration);
+
+    println!(
+        "\nRunning metapopulation simulation with {} patches...",
+        patches
+    );
+    let step = 1.0;
+    let (times, states) = if cli.stochastic {
+        use rand::SeedableRng;
+        let mut rng = match cli.seed {
+            Some(seed) => rand::rngs::StdRng::seed_from_u64(seed),
+            None => rand::rngs::StdRng::from_rng(&mut rand::rng()),
+        };
+        lotka_volterra::stochastic::simulate_gillespie(
+            &model,
+            params.t_start,
+            params.t_end,
+            step,
+            &mut rng,
+        )
+    } else {
+        solve_model(&model, params.t_start, params.t_end, step)?
+    };
+
+    let (prey, predators) = patch_series(&states, patches);
+    println!("Prey synchrony:     {:.3}", synchrony(&prey));
+    println!("Predator synchrony: {:.3}", synchrony(&predators));
+
+    let labels: Vec<String> = (0..patches).map(|i| format!("Patch {}", i)).collect();
+    plot_patch_populations(
+        &times,
+        &prey,
+        &predators,
+        &labels,
+        "Metapopulation: Populations per Patch",
+        "metapopulation_patches.png",
+    )
+    .map_err(|e| SimulationError::PlotError(e.to_string()))?;
+    plot_patch_populations(
+        &times,
+        &[global_series(&prey)],
+        &[global_series(&predators)],
+        &["Global".to_string()],
+        "Metapopulation: Global Populations",
+        "metapopulation_global.png",
+    )
+    .map_err(|e| SimulationError::PlotError(e.to_string()))?;
+
+    println!("Plots saved as metapopulation_patches.png and metapopulation_global.png");
+    Ok(())
+}
+
+/// Runs the agent-based model, either in the Agents view of the GUI or to completion, and
+/// plots its counts against the ODE solution in agents.png.
+fn run_agents(cli: &Cli, scenario: Scenario) -> Result<(), Box<dyn Error>> {
+    let params = scenario.params;
+    let (width, height) = cli.grid_size()?;
+    let lattice = AgentParameters {
+        width,
+        height,
+        seed: cli.seed,
+        ..AgentParameters::default()
+    };
+    let step = 1.0;
+    let stochastic = LotkaVolterraParams::from_deterministic(&params, step);
+    validate_agents(&stochastic, &lattice)?;
+
+    if cli.gui || cli.interactive_plot {
+        println!("\nLaunching interactive GUI...");
+        return launch_gui_with(Some(scenario), Some(View::Agents), move |app| {
+            app.with_lattice(lattice)
+        })
+        .map_err(|e| SimulationError::GuiError(e.to_string()).into());
+    }
+
+    println!("\nRunning agent-based simulation...");
+    let (times, prey, predators) = simulate_agents(&stochastic, lattice)?;
+    let times: Vec<f64> = times.iter().map(|t| t + params.t_start).collect();
+
+    let y0 = [params.initial_prey, params.initial_predator];
+    let (_, ode_prey, ode_predators) =
+        solve_lotka_volterra(params, y0, params.t_start, params.t_end, step)
+            .map_err(|e| SimulationError::SolverError(e.to_string()))?;
+    let n = times.len().min(ode_prey.len());
+
+    plot_patch_populations(
+        &times[..n],
+        &[prey[..n].to_vec(), ode_prey[..n].to_vec()],
+        &[predators[..n].to_vec(), ode_predators[..n].to_vec()],
+        &["Agents".to_string(), "ODE".to_string()],
+        "Agent-Based Model vs. ODE",
+        "agents.png",
+    )
+    .map_err(|e| SimulationError::PlotError(e.to_string()))?;
+
+    println!("Plot saved as agents.png");
+    Ok(())
+}
+
+/// Simulates an ensemble of the environmental noise model, with the harvesting, forcing
+/// and interventions, and plots it against the deterministic solution in sde.png.
+fn run_sde(
+    cli: &Cli,
+    color: NoiseColor,
+    params: LotkaVolterraParameters,
+    terms: &ExternalTerms,
+    schedule: &Schedule,
+    delay: Option<f64>,
+) -> Result<(), Box<dyn Error>> {
+    let noise = EnvironmentalNoise {
+        alpha_intensity: cli.noise_alpha,
+        gamma_intensity: cli.noise_gamma,
+        kind: match color {
+            NoiseColor::White => NoiseKind::White,
+            NoiseColor::Ou => NoiseKind::OrnsteinUhlenbeck {
+                correlation_time: cli.correlation_time,
+            },
+        },
+        scheme: match cli.scheme {
+            SchemeKind::EulerMaruyama => SdeScheme::EulerMaruyama,
+            SchemeKind::Milstein => SdeScheme::Milstein,
+        },
+        ..EnvironmentalNoise::default()
+    };
+
+    println!("\nRunning {} realisations of the SDE model...", cli.runs);
+    if delay.is_some() {
+        println!("The SDE model has no maturation delay, so it is left out.");
+    }
+    let runs = sde_ensemble(&params, terms, schedule, &noise, cli.runs, cli.seed)?;
+    let (det_times, det_prey, det_predators) = solve_run(&params, terms, schedule, None, 1.0)?;
+
+    let times = runs.first().map(|run| run.0.clone()).unwrap_or_default();
+    let prey: Vec<Vec<f64>> = runs.iter().map(|run| run.1.clone()).collect();
+    let predators: Vec<Vec<f64>> = runs.iter().map(|run| run.2.clone()).collect();
+    plot_ensemble(
+        &times,
+        &prey,
+        &predators,
+        (&det_times, &det_prey, &det_predators),
+        "Environmental Noise vs. Deterministic Model",
+        "sde.png",
+    )
+    .map_err(|e| SimulationError::PlotError(e.to_string()))?;
+
+    println!("Plot saved as sde.png");
+    Ok(())
+}
+
+/// Iterates a host-parasitoid map and saves cobweb and phase plots, plus a bifurcation
+/// diagram when a sweep is requested.
+fn run_map(cli: &Cli, choice: MapChoice) -> Result<(), Box<dyn Error>> {
+    let mut model = HostParasitoidMap::new(match choice {
+        MapChoice::NicholsonBailey => MapKind::NicholsonBailey,
+        MapChoice::NegativeBinomial => MapKind::NegativeBinomial,
+        MapChoice::Ricker => MapKind::Ricker,
+    });
+    for entry in &cli.map_params {
+        let (name, value) = parse_assignment(entry)?;
+        model.set(&name, value)?;
+    }
+    model.validate()?;
+
+    println!("\nIterating the map for {} generations...", cli.generations);
+    let states = iterate_map(&model, cli.generations);
+    let hosts: Vec<f64> = states.iter().map(|s| s[0]).collect();
+    let parasitoids: Vec<f64> = states.iter().map(|s| s[1]).collect();
+    plot_cobweb(&hosts, "cobweb.png").map_err(|e| SimulationError::PlotError(e.to_string()))?;
+    plot_map_phase(&hosts, &parasitoids, "map_phase.png")
+        .map_err(|e| SimulationError::PlotError(e.to_string()))?;
+    println!("Plots saved as cobweb.png and map_phase.png");
+
+    if let Some(sweep) = &cli.bifurcation {
+        let sweep: Sweep = sweep.parse()?;
+        let diagram = bifurcation_diagram(&model, &sweep, cli.bifurcation_points, 500, 100)?;
+        plot_bifurcation(&diagram, &sweep.name, "bifurcation.png")
             .map_err(|e| SimulationError::PlotError(e.to_string()))?;
+        println!("Bifurcation diagram saved as bifurcation.png");
+    }
+    Ok(())
+}
+
+/// Parses a `name=value` assignment, converting `-` in the name to `_`.
+fn parse_assignment(entry: &str) -> Result<(String, f64), SimulationError> {
+    let invalid = || {
+        SimulationError::InvalidParameter(format!(
+            "Parameter '{}' must have the form name=value",
+            entry
+        ))
+    };
+    let (name, value) = entry.split_once('=').ok_or_else(invalid)?;
+    let value: f64 = value.trim().parse().map_err(|_| invalid())?;
+    Ok((name.trim().replace('-', "_"), value))
+}
+
+/// Continues the equilibria of the Rosenzweig-MacArthur model over a parameter range,
+/// prints the detected bifurcations and writes the plot and CSV tables.
+fn run_continuation(cli: &Cli, sweep: &str) -> Result<(), Box<dyn Error>> {
+    let sweep: Sweep = sweep.parse()?;
+    let mut model = RosenzweigMacArthur::default();
+    for entry in &cli.rm_params {
+        let (name, value) = parse_assignment(entry)?;
+        model.set_parameter(&name, value)?;
+    }
+    model.set_parameter(&sweep.name, sweep.min)?;
 
-        println!("Plot saved as lotka_volterra.png");
+    println!("\nContinuing equilibria over {}...", sweep.name);
+    let (branch, bifurcations) = continue_equilibria(
+        &model,
+        &sweep.name,
+        model.coexistence_equilibrium(),
+        (sweep.min, sweep.max),
+        &ContinuationSettings::default(),
+    )?;
+    for bifurcation in &bifurcations {
+        println!(
+            "{:?} bifurcation at {} = {:.6} (prey = {:.6}, predators = {:.6})",
+            bifurcation.kind,
+            sweep.name,
+            bifurcation.parameter,
+            bifurcation.state[0],
+            bifurcation.state[1]
+        );
     }
+    if bifurcations.is_empty() {
+        println!("No bifurcations detected.");
+    }
+    std::fs::write("continuation.csv", branch_to_csv(&sweep.name, &branch))?;
+
+    let orbit = if cli.orbit_diagram {
+        println!("Computing orbit diagram...");
+        let values: Vec<f64> = (0..200)
+            .map(|i| sweep.min + (sweep.max - sweep.min) * i as f64 / 199.0)
+            .collect();
+        let orbit = orbit_diagram(&model, &sweep.name, &values, 500.0, 200.0, 0.1)?;
+        std::fs::write(
+            "orbit_diagram.csv",
+            orbit_diagram_to_csv(&sweep.name, &orbit),
+        )?;
+        orbit
+    } else {
+        Vec::new()
+    };
+
+    plot_continuation(
+        &branch,
+        &bifurcations,
+        &orbit,
+        &sweep.name,
+        &cli.continuation_output,
+    )
+    .map_err(|e| SimulationError::PlotError(e.to_string()))?;
+
+    println!(
+        "Plot saved as {} and branch saved as continuation.csv",
+        cli.continuation_output
+    );
+    if cli.orbit_diagram {
+        println!("Orbit diagram saved as orbit_diagram.csv");
+    }
+    Ok(())
+}
+
+/// Saves the phase portrait with orbits from the initial populations and every
+/// `--phase-start` to phase_portrait.png. The orbits follow the harvesting, forcing,
+/// interventions and delay of the run; the direction field, nullclines and equilibria
+/// are only drawn when the flow does not change over time.
+fn run_phase_portrait(
+    cli: &Cli,
+    params: LotkaVolterraParameters,
+    terms: &ExternalTerms,
+    schedule: &Schedule,
+    delay: Option<f64>,
+) -> Result<(), Box<dyn Error>> {
+    let mut initials = vec![[params.initial_prey, params.initial_predator]];
+    initials.extend(cli.phase_starts()?);
+
+    println!("\nComputing phase portrait...");
+    let orbits = phase_orbits(&params, terms, schedule, delay, &initials, 1.0)?;
+    let field = match delay {
+        Some(_) => None,
+        None => autonomous_params(&params, terms, schedule),
+    };
+    if field.is_none() {
+        println!(
+            "The direction field, nullclines and equilibria are left out, as harvesting, \
+             forcing, interventions or the delay change the flow over time."
+        );
+    }
+    plot_phase_portrait(field.as_ref(), &orbits, "phase_portrait.png")
+        .map_err(|e| SimulationError::PlotError(e.to_string()))?;
+
+    println!("Phase portrait saved as phase_portrait.png");
+    Ok(())
+}
+
+/// Solves the model with its harvesting, forcing, interventions and delay and saves the
+/// composite report figure, printing the derived metrics. The equilibria are only drawn
+/// when the flow does not change over time.
+fn run_report_figure(
+    params: LotkaVolterraParameters,
+    terms: &ExternalTerms,
+    schedule: &Schedule,
+    delay: Option<f64>,
+    file: &str,
+) -> Result<(), Box<dyn Error>> {
+    println!("\nRunning simulation...");
+    let (times, prey, predators) = solve_run(&params, terms, schedule, delay, 0.1)?;
+    let field = match delay {
+        Some(_) => None,
+        None => autonomous_params(&params, terms, schedule),
+    };
+    if field.is_none() {
+        println!(
+            "The equilibria are left out, as harvesting, forcing, interventions or the \
+             delay change the flow over time."
+        );
+    }
+
+    let summary = summarize(&params, &times, &prey, &predators);
+    println!("Mean prey: {:.1}", summary.mean_prey);
+    println!("Mean predators: {:.1}", summary.mean_predator);
+    if let Some(period) = summary.prey_period {
+        println!("Period: {:.1}", period);
+    }
+    println!("Extinction: {}", if summary.extinct { "yes" } else { "no" });
+
+    plot_report(&params, field.as_ref(), &times, &prey, &predators, file)
+        .map_err(|e| SimulationError::PlotError(e.to_string()))?;
+    println!("Report figure saved as {}", file);
+    Ok(())
+}
+
+/// Solves the model with its harvesting, forcing, interventions and delay and writes the
+/// self-contained HTML report. The equilibria are left out of the report of a delayed run.
+fn run_html_report(
+    params: LotkaVolterraParameters,
+    terms: &ExternalTerms,
+    schedule: &Schedule,
+    delay: Option<f64>,
+    plot: &PlotConfig,
+    file: &str,
+) -> Result<(), Box<dyn Error>> {
+    println!("\nRunning simulation...");
+    let step = 0.1;
+    let (times, prey, predators, stats) = match delay {
+        Some(_) => {
+            let start = std::time::Instant::now();
+            let (times, prey, predators) = solve_run(&params, terms, schedule, delay, step)?;
+            let stats = SolverStats {
+                elapsed: start.elapsed(),
+                ..SolverStats::default()
+            };
+            (times, prey, predators, stats)
+        }
+        None => solve_with_statistics(
+            params,
+            terms,
+            schedule,
+            [params.initial_prey, params.initial_predator],
+            params.t_start,
+            params.t_end,
+            step,
+        )
+        .map_err(|e| SimulationError::SolverError(e.to_string()))?,
+    };
+
+    let report = RunReport {
+        params,
+        terms,
+        schedule,
+        delay,
+        times: &times,
+        prey: &prey,
+        predators: &predators,
+        step,
+        stats,
+        plot,
+    };
+    std::fs::write(file, report.to_html()?)?;
+    println!("Report saved as {}", file);
+    Ok(())
+}
+
+/// Solves the model with the harvesting, forcing, interventions and delay, or simulates
+/// it with the Gillespie algorithm and the scheduled interventions under `--stochastic`,
+/// and saves the animated trajectory.
+fn run_animation(
+    cli: &Cli,
+    params: LotkaVolterraParameters,
+    terms: &ExternalTerms,
+    schedule: &Schedule,
+    delay: Option<f64>,
+    file: &str,
+) -> Result<(), Box<dyn Error>> {
+    let settings = cli.animation_settings()?;
+    let (times, prey, predators, title) = if cli.stochastic {
+        if !terms.harvests.is_empty() || !terms.forcings.is_empty() || delay.is_some() {
+            return Err(SimulationError::InvalidParameter(
+                "The stochastic model has no harvesting, forcing or delay; animate it \
+                 without them or drop --stochastic."
+                    .to_string(),
+            )
+            .into());
+        }
+        use rand::SeedableRng;
+        println!("\nRunning stochastic simulation...");
+        let mut rng = match cli.seed {
+            Some(seed) => rand::rngs::StdRng::seed_from_u64(seed),
+            None => rand::rngs::StdRng::from_rng(&mut rand::rng()),
+        };
+        let (times, states) = simulate_gillespie_with_interventions(
+            &params,
+            schedule,
+            1.0,
+            &mut rng,
+            &Progress::default(),
+        )?;
+        let (prey, predators) = states.iter().map(|s| (s[0], s[1])).unzip();
+        (times, prey, predators, "Stochastic Lotka-Volterra")
+    } else {
+        println!("\nRunning simulation...");
+        let (times, prey, predators) = solve_run(&params, terms, schedule, delay, 0.1)?;
+        (times, prey, predators, "Lotka-Volterra")
+    };
 
+    println!("Rendering {} frames...", settings.frames);
+    animate_trajectory(&times, &prey, &predators, &settings, title, file)
+        .map_err(|e| SimulationError::PlotError(e.to_string()))?;
+    println!("Animation saved as {}", file);
     Ok(())
 }
@@ -1,4 +1,53 @@
-use clap::Parser;
+use crate::animation::AnimationSettings;
+use crate::error::SimulationError;
+use crate::models::LotkaVolterraParameters;
+use crate::plot_config::{PlotConfig, PlotFormat, parse_range};
+use clap::{Parser, ValueEnum};
+
+/// Global sensitivity analysis methods.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum SensitivityMethod {
+    Sobol,
+    Morris,
+}
+
+/// Boundary conditions of the spatial model.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum BoundaryKind {
+    Periodic,
+    Neumann,
+    Dirichlet,
+}
+
+/// Scalar outputs available to the sensitivity analysis.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum SensitivityOutput {
+    PeakPrey,
+    Period,
+    Extinction,
+}
+
+/// Environmental noise processes of the SDE model.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum NoiseColor {
+    White,
+    Ou,
+}
+
+/// Integration schemes of the SDE model.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum SchemeKind {
+    EulerMaruyama,
+    Milstein,
+}
+
+/// Discrete-time host-parasitoid maps.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum MapChoice {
+    NicholsonBailey,
+    NegativeBinomial,
+    Ricker,
+}
 
 /// Command-line arguments for the parameters of the Lotka-Volterra differential
 /// equation. The command-line arguments are used as values in the simulation.
@@ -49,6 +98,361 @@ pub struct Cli {
     /// Enable interactive plot mode
     #[arg(short = 'p', long = "interactive-plot")]
     pub interactive_plot: bool,
+
+    /// Parameter file with model parameters, harvesting and forcing
+    #[arg(short = 's', long = "scenario")]
+    pub scenario: Option<String>,
+
+    /// Maturation delay tau; selects the delay differential equation model
+    #[arg(long = "delay")]
+    pub delay: Option<f64>,
+
+    /// Run the spatial reaction-diffusion model
+    #[arg(long = "spatial")]
+    pub spatial: bool,
+
+    /// Spatial grid size as NX (1D) or NXxNY (2D)
+    #[arg(long = "grid", default_value = "64x64")]
+    pub grid: String,
+
+    /// Prey diffusion coefficient
+    #[arg(long = "prey-diffusion", default_value_t = 1.0)]
+    pub prey_diffusion: f64,
+
+    /// Predator diffusion coefficient
+    #[arg(long = "predator-diffusion", default_value_t = 1.0)]
+    pub predator_diffusion: f64,
+
+    /// Boundary condition of the spatial grid
+    #[arg(long = "boundary", value_enum, default_value = "periodic")]
+    pub boundary: BoundaryKind,
+
+    /// Prey density held outside the grid with `--boundary dirichlet`
+    #[arg(long = "boundary-prey", default_value_t = 0.0)]
+    pub boundary_prey: f64,
+
+    /// Predator density held outside the grid with `--boundary dirichlet`
+    #[arg(long = "boundary-predator", default_value_t = 0.0)]
+    pub boundary_predator: f64,
+
+    /// Number of spatial snapshots to export
+    #[arg(long = "frames", default_value_t = 50)]
+    pub frames: usize,
+
+    /// Run the agent-based lattice model on the grid given by `--grid`
+    #[arg(long = "agents")]
+    pub agents: bool,
+
+    /// Run the environmental noise SDE model with white or Ornstein-Uhlenbeck noise
+    #[arg(long = "sde", value_enum)]
+    pub sde: Option<NoiseColor>,
+
+    /// Noise intensity on the prey birth rate alpha
+    #[arg(long = "noise-alpha", default_value_t = 0.2)]
+    pub noise_alpha: f64,
+
+    /// Noise intensity on the predator death rate gamma
+    #[arg(long = "noise-gamma", default_value_t = 0.2)]
+    pub noise_gamma: f64,
+
+    /// Correlation time of Ornstein-Uhlenbeck noise
+    #[arg(long = "correlation-time", default_value_t = 50.0)]
+    pub correlation_time: f64,
+
+    /// SDE integration scheme
+    #[arg(long = "scheme", value_enum, default_value = "milstein")]
+    pub scheme: SchemeKind,
+
+    /// Number of realisations in a stochastic ensemble
+    #[arg(long = "runs", default_value_t = 20)]
+    pub runs: usize,
+
+    /// Iterate a discrete-time host-parasitoid map
+    #[arg(long = "map", value_enum)]
+    pub map: Option<MapChoice>,
+
+    /// Number of generations to iterate the map
+    #[arg(long = "generations", default_value_t = 100)]
+    pub generations: usize,
+
+    /// Map parameter as name=value (lambda, search-efficiency, conversion, clumping, capacity, initial-host, initial-parasitoid); repeatable
+    #[arg(long = "map-param")]
+    pub map_params: Vec<String>,
+
+    /// Bifurcation diagram of the map over a parameter, as name=min:max
+    #[arg(long = "bifurcation")]
+    pub bifurcation: Option<String>,
+
+    /// Number of parameter values in the bifurcation diagram
+    #[arg(long = "bifurcation-points", default_value_t = 400)]
+    pub bifurcation_points: usize,
+
+    /// Continue equilibria of the Rosenzweig-MacArthur model over a parameter, as name=min:max
+    #[arg(long = "continuation")]
+    pub continuation: Option<String>,
+
+    /// Also compute a brute-force orbit diagram over the continuation range
+    #[arg(long = "orbit-diagram")]
+    pub orbit_diagram: bool,
+
+    /// Rosenzweig-MacArthur parameter as name=value (growth, capacity, attack, handling, efficiency, mortality); repeatable
+    #[arg(long = "rm-param")]
+    pub rm_params: Vec<String>,
+
+    /// Output image of the continuation; a .svg name writes SVG, anything else PNG
+    #[arg(long = "continuation-output", default_value = "continuation.png")]
+    pub continuation_output: String,
+
+    /// Export a phase portrait with direction field, nullclines and equilibria
+    #[arg(long = "phase-portrait")]
+    pub phase_portrait: bool,
+
+    /// Export a composite report figure (time series, phase plane, conserved quantity
+    /// drift and summary) to FILE, report.png by default
+    #[arg(long = "report-figure", num_args = 0..=1, default_missing_value = "report.png")]
+    pub report_figure: Option<String>,
+
+    /// Export an animated GIF of the trajectory to FILE, trajectory.gif by default;
+    /// combine with --stochastic to animate a Gillespie realisation
+    #[arg(long = "animate", num_args = 0..=1, default_missing_value = "trajectory.gif")]
+    pub animate: Option<String>,
+
+    /// Animation frame rate in frames per second
+    #[arg(long = "fps", default_value_t = 20)]
+    pub fps: u32,
+
+    /// Number of animation frames
+    #[arg(long = "animation-frames", default_value_t = 200)]
+    pub animation_frames: usize,
+
+    /// Animated time window as start:end, or auto for the whole run
+    #[arg(long = "window", default_value = "auto")]
+    pub window: String,
+
+    /// Length of time of the trailing tail in the animated phase plane
+    #[arg(long = "tail")]
+    pub tail: Option<f64>,
+
+    /// Write a self-contained HTML report of the run to FILE
+    #[arg(long = "report")]
+    pub report: Option<String>,
+
+    /// Additional phase portrait initial condition as PREY,PREDATOR; repeatable
+    #[arg(long = "phase-start")]
+    pub phase_starts: Vec<String>,
+
+    /// Edge-list file of a patch network; runs the metapopulation model
+    #[arg(long = "network")]
+    pub network: Option<String>,
+
+    /// Per-capita prey migration rate per unit edge weight
+    #[arg(long = "prey-migration", default_value_t = 0.001)]
+    pub prey_migration: f64,
+
+    /// Per-capita predator migration rate per unit edge weight
+    #[arg(long = "predator-migration", default_value_t = 0.001)]
+    pub predator_migration: f64,
+
+    /// Use demographic (Gillespie) simulation instead of the ODEs
+    #[arg(long = "stochastic")]
+    pub stochastic: bool,
+
+    /// Run a global sensitivity analysis
+    #[arg(long = "sensitivity", value_enum)]
+    pub sensitivity: Option<SensitivityMethod>,
+
+    /// Scalar output analysed by the sensitivity analysis
+    #[arg(long = "sensitivity-output", value_enum, default_value = "peak-prey")]
+    pub sensitivity_output: SensitivityOutput,
+
+    /// Parameter range as name=min:max (repeatable); defaults to +/-10% of each rate
+    #[arg(long = "range")]
+    pub ranges: Vec<String>,
+
+    /// Sobol base samples or Morris trajectories; 128 by default, 8 for the extinction output
+    #[arg(long = "samples")]
+    pub samples: Option<usize>,
+
+    /// Output step of the solver used by the sensitivity analysis
+    #[arg(long = "sensitivity-step", default_value_t = 1.0)]
+    pub sensitivity_step: f64,
+
+    /// Stochastic replicates per extinction probability estimate
+    #[arg(long = "replicates", default_value_t = 10)]
+    pub replicates: usize,
+
+    /// Time step of the stochastic simulations of the extinction output
+    #[arg(long = "stochastic-dt", default_value_t = 0.005)]
+    pub stochastic_dt: f64,
+
+    /// Seed for reproducible sampling
+    #[arg(long = "seed")]
+    pub seed: Option<u64>,
+
+    /// Output file of the static plot; a .svg extension selects SVG output
+    #[arg(short = 'o', long = "output")]
+    pub output: Option<String>,
+
+    /// Static plot format: png or svg
+    #[arg(long = "format")]
+    pub format: Option<String>,
+
+    /// Static plot size as WIDTHxHEIGHT in pixels
+    #[arg(long = "size")]
+    pub size: Option<String>,
+
+    /// Time axis range as min:max, or auto
+    #[arg(long = "x-range")]
+    pub x_range: Option<String>,
+
+    /// Population axis range as min:max, or auto
+    #[arg(long = "y-range")]
+    pub y_range: Option<String>,
+
+    /// Use a logarithmic population axis
+    #[arg(long = "log-y")]
+    pub log_y: bool,
+
+    /// Static plot title
+    #[arg(long = "title")]
+    pub title: Option<String>,
+
+    /// Time axis label
+    #[arg(long = "x-label")]
+    pub x_label: Option<String>,
+
+    /// Population axis label
+    #[arg(long = "y-label")]
+    pub y_label: Option<String>,
+
+    /// Prey line color as a name or #rrggbb
+    #[arg(long = "prey-color")]
+    pub prey_color: Option<String>,
+
+    /// Predator line color as a name or #rrggbb
+    #[arg(long = "predator-color")]
+    pub predator_color: Option<String>,
+
+    /// Line width in pixels
+    #[arg(long = "line-width")]
+    pub line_width: Option<u32>,
+
+    /// Legend position: upper-left, upper-right, lower-left, lower-right or none
+    #[arg(long = "legend")]
+    pub legend: Option<String>,
+
+    /// Plot theme: light or dark
+    #[arg(long = "theme")]
+    pub theme: Option<String>,
+}
+
+impl Cli {
+    /// Static plot appearance: `base` (from a parameter file, say) overridden by any
+    /// plot flags given on the command line.
+    pub fn plot_config(&self, base: PlotConfig) -> Result<PlotConfig, SimulationError> {
+        let mut config = base;
+        if let Some(format) = self.output.as_deref().and_then(PlotFormat::from_file_name) {
+            config.format = format;
+        }
+        let line_width = self.line_width.map(|w| w.to_string());
+        let options = [
+            ("format", &self.format),
+            ("size", &self.size),
+            ("x_range", &self.x_range),
+            ("y_range", &self.y_range),
+            ("title", &self.title),
+            ("x_label", &self.x_label),
+            ("y_label", &self.y_label),
+            ("prey_color", &self.prey_color),
+            ("predator_color", &self.predator_color),
+            ("line_width", &line_width),
+            ("legend", &self.legend),
+            ("theme", &self.theme),
+        ];
+        for (name, value) in options {
+            if let Some(value) = value {
+                config.set(name, value)?;
+            }
+        }
+        config.log_y |= self.log_y;
+        Ok(config)
+    }
+
+    /// Animation settings from `--fps`, `--animation-frames`, `--window` and `--tail`.
+    pub fn animation_settings(&self) -> Result<AnimationSettings, SimulationError> {
+        let settings = AnimationSettings {
+            frame_rate: self.fps,
+            frames: self.animation_frames,
+            window: parse_range(&self.window)?,
+            tail: self.tail,
+            ..AnimationSettings::default()
+        };
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    /// File the static plot is written to: `--output`, or `lotka_volterra` with the
+    /// extension of the configured format.
+    pub fn plot_file(&self, config: &PlotConfig) -> String {
+        self.output
+            .clone()
+            .unwrap_or_else(|| config.file_name("lotka_volterra"))
+    }
+
+    /// Initial conditions parsed from `--phase-start`.
+    pub fn phase_starts(&self) -> Result<Vec<[f64; 2]>, SimulationError> {
+        self.phase_starts
+            .iter()
+            .map(|entry| {
+                let invalid = || {
+                    SimulationError::InvalidParameter(format!(
+                        "Initial condition '{}' must have the form PREY,PREDATOR",
+                        entry
+                    ))
+                };
+                let (prey, predators) = entry.split_once(',').ok_or_else(invalid)?;
+                Ok([
+                    prey.trim().parse().map_err(|_| invalid())?,
+                    predators.trim().parse().map_err(|_| invalid())?,
+                ])
+            })
+            .collect()
+    }
+
+    /// Spatial grid dimensions parsed from `--grid`; a single number gives a 1D grid.
+    pub fn grid_size(&self) -> Result<(usize, usize), SimulationError> {
+        let invalid = || {
+            SimulationError::InvalidParameter(format!(
+                "Grid '{}' must have the form NX or NXxNY",
+                self.grid
+            ))
+        };
+        let parse = |s: &str| s.trim().parse::<usize>().map_err(|_| invalid());
+        match self.grid.split_once(['x', 'X']) {
+            Some((nx, ny)) => Ok((parse(nx)?, parse(ny)?)),
+            None => Ok((parse(&self.grid)?, 1)),
+        }
+    }
+
+    /// Parameters given on the command line, with defaults for any that are missing.
+    pub fn parameters(&self) -> LotkaVolterraParameters {
+        self.parameters_over(LotkaVolterraParameters::default())
+    }
+
+    /// Parameters given on the command line, taking the values of `defaults`, such as
+    /// those of a parameter file, for any that are missing.
+    pub fn parameters_over(&self, defaults: LotkaVolterraParameters) -> LotkaVolterraParameters {
+        LotkaVolterraParameters {
+            alpha: self.alpha.unwrap_or(defaults.alpha),
+            beta: self.beta.unwrap_or(defaults.beta),
+            delta: self.delta.unwrap_or(defaults.delta),
+            gamma: self.gamma.unwrap_or(defaults.gamma),
+            initial_prey: self.initial_prey.unwrap_or(defaults.initial_prey),
+            initial_predator: self.initial_predator.unwrap_or(defaults.initial_predator),
+            t_start: self.t_start.unwrap_or(defaults.t_start),
+            t_end: self.t_end.unwrap_or(defaults.t_end),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -61,7 +465,7 @@ mod tests {
         let args = vec!["lotka_volterra"];
         let cli = Cli::try_parse_from(args).unwrap();
 
-        assert_eq!(cli.gui, false, "GUI mode should be false by default.");
+        assert!(!cli.gui, "GUI mode should be false by default.");
         assert!(cli.alpha.is_none(), "Alpha should be None by default.");
     }
 
@@ -73,4 +477,103 @@ mod tests {
         assert_eq!(cli.alpha.unwrap(), 0.1, "Alpha should be parsed correctly.");
         assert_eq!(cli.beta.unwrap(), 0.02, "Beta should be parsed correctly.");
     }
+
+    #[test]
+    fn test_cli_flags_override_parameter_file() {
+        let args = vec!["lotka_volterra", "--alpha", "0.1", "--beta", "0.02"];
+        let cli = Cli::try_parse_from(args).unwrap();
+
+        // Flags given take precedence over the values of a parameter file
+        let file = LotkaVolterraParameters {
+            alpha: 0.5,
+            gamma: 0.3,
+            ..LotkaVolterraParameters::default()
+        };
+        let params = cli.parameters_over(file);
+        assert_eq!((params.alpha, params.beta, params.gamma), (0.1, 0.02, 0.3));
+    }
+
+    #[test]
+    fn test_cli_parsing_sensitivity() {
+        let args = vec![
+            "lotka_volterra",
+            "--sensitivity",
+            "morris",
+            "--sensitivity-output",
+            "period",
+            "--range",
+            "alpha=0.005:0.015",
+            "--gamma",
+            "0.02",
+        ];
+        let cli = Cli::try_parse_from(args).unwrap();
+
+        assert_eq!(cli.sensitivity, Some(SensitivityMethod::Morris));
+        assert_eq!(cli.sensitivity_output, SensitivityOutput::Period);
+        assert_eq!(cli.ranges, vec!["alpha=0.005:0.015"]);
+        assert_eq!(cli.parameters().gamma, 0.02);
+        assert_eq!(cli.parameters().alpha, 0.01);
+    }
+
+    #[test]
+    fn test_cli_phase_starts() {
+        let cli = Cli::try_parse_from([
+            "lotka_volterra",
+            "--phase-portrait",
+            "--phase-start",
+            "500,800",
+        ])
+        .unwrap();
+        assert_eq!(cli.phase_starts().unwrap(), vec![[500.0, 800.0]]);
+
+        let cli = Cli::try_parse_from(["lotka_volterra", "--phase-start", "500"]).unwrap();
+        assert!(cli.phase_starts().is_err());
+    }
+
+    #[test]
+    fn test_cli_grid_size() {
+        let cli = Cli::try_parse_from(["lotka_volterra", "--spatial", "--grid", "32x16"]).unwrap();
+        assert_eq!(cli.grid_size().unwrap(), (32, 16));
+
+        let cli = Cli::try_parse_from(["lotka_volterra", "--grid", "200"]).unwrap();
+        assert_eq!(cli.grid_size().unwrap(), (200, 1));
+
+        let cli = Cli::try_parse_from([
+            "lotka_volterra",
+            "--boundary",
+            "dirichlet",
+            "--boundary-prey",
+            "2.5",
+        ])
+        .unwrap();
+        assert_eq!(cli.boundary, BoundaryKind::Dirichlet);
+        assert_eq!((cli.boundary_prey, cli.boundary_predator), (2.5, 0.0));
+    }
+
+    #[test]
+    fn test_cli_plot_config() {
+        let args = vec![
+            "lotka_volterra",
+            "--output",
+            "run.svg",
+            "--y-range",
+            "0:8000",
+            "--log-y",
+        ];
+        let cli = Cli::try_parse_from(args).unwrap();
+        let base = PlotConfig {
+            title: "From file".to_string(),
+            ..PlotConfig::default()
+        };
+        let config = cli.plot_config(base).unwrap();
+
+        assert_eq!(config.format, PlotFormat::Svg);
+        assert_eq!(config.y_range, Some((0.0, 8000.0)));
+        assert!(config.log_y);
+        assert_eq!(
+            config.title, "From file",
+            "Unset flags keep the file's options."
+        );
+        assert_eq!(cli.plot_file(&config), "run.svg");
+    }
 }
@@ -0,0 +1,483 @@
+use crate::analysis::local_maxima;
+use crate::error::SimulationError;
+use crate::models::{ContinuousModel, PopulationModel};
+use crate::parallel::par_map;
+use crate::solver::solve_model;
+
+/// Rosenzweig-MacArthur model: logistic prey growth and a Holling type II functional
+/// response,
+///
+/// ```text
+/// dx/dt = r x (1 - x / K) - a x y / (1 + a h x)
+/// dy/dt = e a x y / (1 + a h x) - m y
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RosenzweigMacArthur {
+    pub growth: f64,     // Prey intrinsic growth rate r
+    pub capacity: f64,   // Prey carrying capacity K
+    pub attack: f64,     // Attack rate a
+    pub handling: f64,   // Handling time h
+    pub efficiency: f64, // Conversion efficiency e
+    pub mortality: f64,  // Predator mortality m
+    pub initial_prey: f64,
+    pub initial_predator: f64,
+}
+
+impl Default for RosenzweigMacArthur {
+    fn default() -> Self {
+        Self {
+            growth: 1.0,
+            capacity: 3.0,
+            attack: 1.0,
+            handling: 1.0,
+            efficiency: 0.5,
+            mortality: 0.2,
+            initial_prey: 1.0,
+            initial_predator: 0.5,
+        }
+    }
+}
+
+impl RosenzweigMacArthur {
+    /// Names accepted by [`ParametricModel::set_parameter`].
+    pub const NAMES: [&'static str; 8] = [
+        "growth",
+        "capacity",
+        "attack",
+        "handling",
+        "efficiency",
+        "mortality",
+        "initial_prey",
+        "initial_predator",
+    ];
+
+    /// The coexistence equilibrium, which exists for `K` above the prey equilibrium.
+    pub fn coexistence_equilibrium(&self) -> [f64; 2] {
+        let x = self.mortality / (self.attack * (self.efficiency - self.mortality * self.handling));
+        let y = self.growth / self.attack
+            * (1.0 - x / self.capacity)
+            * (1.0 + self.attack * self.handling * x);
+        [x, y]
+    }
+}
+
+impl PopulationModel for RosenzweigMacArthur {
+    fn dimension(&self) -> usize {
+        2
+    }
+
+    fn initial_state(&self) -> Vec<f64> {
+        vec![self.initial_prey, self.initial_predator]
+    }
+}
+
+impl ContinuousModel for RosenzweigMacArthur {
+    fn derivative(&self, _t: f64, state: &[f64], dydt: &mut [f64]) {
+        let (x, y) = (state[0], state[1]);
+        let response = self.attack * x / (1.0 + self.attack * self.handling * x);
+        dydt[0] = self.growth * x * (1.0 - x / self.capacity) - response * y;
+        dydt[1] = self.efficiency * response * y - self.mortality * y;
+    }
+}
+
+/// A continuous model whose parameters can be changed by name.
+pub trait ParametricModel: ContinuousModel + Clone + Sync {
+    fn set_parameter(&mut self, name: &str, value: f64) -> Result<(), SimulationError>;
+}
+
+impl ParametricModel for RosenzweigMacArthur {
+    fn set_parameter(&mut self, name: &str, value: f64) -> Result<(), SimulationError> {
+        let field = match name {
+            "growth" => &mut self.growth,
+            "capacity" => &mut self.capacity,
+            "attack" => &mut self.attack,
+            "handling" => &mut self.handling,
+            "efficiency" => &mut self.efficiency,
+            "mortality" => &mut self.mortality,
+            "initial_prey" => &mut self.initial_prey,
+            "initial_predator" => &mut self.initial_predator,
+            _ => {
+                return Err(SimulationError::InvalidParameter(format!(
+                    "Unknown model parameter '{}'",
+                    name
+                )));
+            }
+        };
+        *field = value;
+        Ok(())
+    }
+}
+
+/// An equilibrium on a continued branch with the eigenvalues of its Jacobian, given as
+/// `(real, imaginary)` pairs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BranchPoint {
+    pub parameter: f64,
+    pub state: [f64; 2],
+    pub eigenvalues: [(f64, f64); 2],
+}
+
+impl BranchPoint {
+    /// Whether both eigenvalues have negative real parts.
+    pub fn stable(&self) -> bool {
+        self.eigenvalues.iter().all(|e| e.0 < 0.0)
+    }
+}
+
+/// Type of a detected bifurcation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BifurcationKind {
+    /// A complex pair of eigenvalues crosses the imaginary axis; limit cycles appear.
+    Hopf,
+    /// The branch turns back in the parameter (saddle-node).
+    Fold,
+}
+
+/// A bifurcation located on a branch by interpolation between two branch points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bifurcation {
+    pub kind: BifurcationKind,
+    pub parameter: f64,
+    pub state: [f64; 2],
+}
+
+/// Settings of the pseudo-arclength continuation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContinuationSettings {
+    pub step: f64,     // Arclength step
+    pub min_step: f64, // The step is halved on Newton failures down to this size
+    pub max_points: usize,
+    pub tolerance: f64,
+}
+
+impl Default for ContinuationSettings {
+    fn default() -> Self {
+        Self {
+            step: 0.02,
+            min_step: 1e-5,
+            max_points: 5000,
+            tolerance: 1e-10,
+        }
+    }
+}
+
+/// Vector field at state `u` with the swept parameter set to `p`.
+fn field<M: ParametricModel>(model: &M, name: &str, u: [f64; 2], p: f64) -> [f64; 2] {
+    let mut model = model.clone();
+    // The name is checked before continuation starts
+    model.set_parameter(name, p).ok();
+    let mut out = [0.0; 2];
+    model.derivative(0.0, &u, &mut out);
+    out
+}
+
+/// Jacobian of the field with respect to `(x, y, p)` by central differences.
+fn jacobian<M: ParametricModel>(model: &M, name: &str, z: [f64; 3]) -> [[f64; 3]; 2] {
+    let mut jac = [[0.0; 3]; 2];
+    for k in 0..3 {
+        let h = 1e-7 * (1.0 + z[k].abs());
+        let (mut plus, mut minus) = (z, z);
+        plus[k] += h;
+        minus[k] -= h;
+        let f_plus = field(model, name, [plus[0], plus[1]], plus[2]);
+        let f_minus = field(model, name, [minus[0], minus[1]], minus[2]);
+        for i in 0..2 {
+            jac[i][k] = (f_plus[i] - f_minus[i]) / (2.0 * h);
+        }
+    }
+    jac
+}
+
+/// Solves a 3x3 linear system by Gaussian elimination with partial pivoting.
+fn solve3(mut a: [[f64; 3]; 3], mut b: [f64; 3]) -> Option<[f64; 3]> {
+    for col in 0..3 {
+        let pivot = (col..3).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col].abs() < 1e-14 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        for row in col + 1..3 {
+            let factor = a[row][col] / a[col][col];
+            let pivot_row = a[col];
+            for (value, pivot) in a[row][col..].iter_mut().zip(&pivot_row[col..]) {
+                *value -= factor * pivot;
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+    let mut x = [0.0; 3];
+    for row in (0..3).rev() {
+        let sum: f64 = (row + 1..3).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+    Some(x)
+}
+
+/// Unit tangent of the branch at `z`, oriented along `previous`.
+fn tangent<M: ParametricModel>(
+    model: &M,
+    name: &str,
+    z: [f64; 3],
+    previous: [f64; 3],
+) -> Option<[f64; 3]> {
+    let jac = jacobian(model, name, z);
+    let t = solve3([jac[0], jac[1], previous], [0.0, 0.0, 1.0])?;
+    let norm = t.iter().map(|v| v * v).sum::<f64>().sqrt();
+    Some(t.map(|v| v / norm))
+}
+
+/// Eigenvalues of the 2x2 state Jacobian.
+fn eigenvalues(jac: &[[f64; 3]; 2]) -> [(f64, f64); 2] {
+    let trace = jac[0][0] + jac[1][1];
+    let det = jac[0][0] * jac[1][1] - jac[0][1] * jac[1][0];
+    let disc = trace * trace / 4.0 - det;
+    if disc >= 0.0 {
+        let root = disc.sqrt();
+        [(trace / 2.0 + root, 0.0), (trace / 2.0 - root, 0.0)]
+    } else {
+        let root = (-disc).sqrt();
+        [(trace / 2.0, root), (trace / 2.0, -root)]
+    }
+}
+
+/// Continues a branch of equilibria of a two-dimensional model in the parameter `name`
+/// with pseudo-arclength continuation, starting near `start` at the lower end of
+/// `range` and stopping when the parameter leaves `range`. Eigenvalues are tracked
+/// along the branch; Hopf points are detected where the real part of a complex pair
+/// changes sign, and folds where the branch turns in the parameter.
+pub fn continue_equilibria<M: ParametricModel>(
+    model: &M,
+    name: &str,
+    start: [f64; 2],
+    range: (f64, f64),
+    settings: &ContinuationSettings,
+) -> Result<(Vec<BranchPoint>, Vec<Bifurcation>), SimulationError> {
+    if model.dimension() != 2 {
+        return Err(SimulationError::InvalidParameter(
+            "Continuation supports two-dimensional models only.".to_string(),
+        ));
+    }
+    model.clone().set_parameter(name, range.0)?;
+    let not_converged = || {
+        SimulationError::SolverError(format!(
+            "No equilibrium found near ({}, {}) for {} = {}",
+            start[0], start[1], name, range.0
+        ))
+    };
+
+    // Newton's method at fixed parameter for the first point
+    let mut z = [start[0], start[1], range.0];
+    for _ in 0..50 {
+        let f = field(model, name, [z[0], z[1]], z[2]);
+        let jac = jacobian(model, name, z);
+        let du = solve3([jac[0], jac[1], [0.0, 0.0, 1.0]], [-f[0], -f[1], 0.0])
+            .ok_or_else(not_converged)?;
+        z[0] += du[0];
+        z[1] += du[1];
+        if du[0].abs() + du[1].abs() < settings.tolerance {
+            break;
+        }
+    }
+    let f = field(model, name, [z[0], z[1]], z[2]);
+    if f[0].abs() + f[1].abs() > 1e-6 || !z.iter().all(|v| v.is_finite()) {
+        return Err(not_converged());
+    }
+
+    let point = |z: [f64; 3]| BranchPoint {
+        parameter: z[2],
+        state: [z[0], z[1]],
+        eigenvalues: eigenvalues(&jacobian(model, name, z)),
+    };
+    let mut branch = vec![point(z)];
+    let mut direction = tangent(model, name, z, [0.0, 0.0, 1.0]).ok_or_else(not_converged)?;
+    let mut step = settings.step;
+
+    while branch.len() < settings.max_points && z[2] >= range.0 && z[2] <= range.1 {
+        let predicted: [f64; 3] = std::array::from_fn(|k| z[k] + step * direction[k]);
+
+        // Newton corrector on the field plus the arclength condition
+        let mut corrected = predicted;
+        let mut converged = false;
+        for _ in 0..20 {
+            let f = field(model, name, [corrected[0], corrected[1]], corrected[2]);
+            let arclength: f64 = (0..3)
+                .map(|k| direction[k] * (corrected[k] - predicted[k]))
+                .sum();
+            let jac = jacobian(model, name, corrected);
+            let Some(dz) = solve3([jac[0], jac[1], direction], [-f[0], -f[1], -arclength]) else {
+                break;
+            };
+            for k in 0..3 {
+                corrected[k] += dz[k];
+            }
+            if dz.iter().map(|v| v.abs()).sum::<f64>() < settings.tolerance {
+                converged = true;
+                break;
+            }
+        }
+
+        if !converged || !corrected.iter().all(|v| v.is_finite()) {
+            step /= 2.0;
+            if step < settings.min_step {
+                break;
+            }
+            continue;
+        }
+
+        let Some(next_direction) = tangent(model, name, corrected, direction) else {
+            break;
+        };
+        z = corrected;
+        direction = next_direction;
+        step = (step * 1.5).min(settings.step);
+        branch.push(point(z));
+    }
+
+    let bifurcations = detect_bifurcations(&branch);
+    Ok((branch, bifurcations))
+}
+
+/// Locates Hopf points and folds between consecutive branch points.
+fn detect_bifurcations(branch: &[BranchPoint]) -> Vec<Bifurcation> {
+    let interpolate = |a: &BranchPoint, b: &BranchPoint, s: f64| {
+        (
+            a.parameter + s * (b.parameter - a.parameter),
+            [
+                a.state[0] + s * (b.state[0] - a.state[0]),
+                a.state[1] + s * (b.state[1] - a.state[1]),
+            ],
+        )
+    };
+    let mut bifurcations = Vec::new();
+    for (i, pair) in branch.windows(2).enumerate() {
+        let (a, b) = (&pair[0], &pair[1]);
+        let complex = a.eigenvalues[0].1 != 0.0 && b.eigenvalues[0].1 != 0.0;
+        let (re_a, re_b) = (a.eigenvalues[0].0, b.eigenvalues[0].0);
+        if complex && re_a * re_b < 0.0 {
+            let (parameter, state) = interpolate(a, b, re_a / (re_a - re_b));
+            bifurcations.push(Bifurcation {
+                kind: BifurcationKind::Hopf,
+                parameter,
+                state,
+            });
+        }
+        if i > 0 {
+            let previous = &branch[i - 1];
+            let (da, db) = (a.parameter - previous.parameter, b.parameter - a.parameter);
+            if da * db < 0.0 {
+                bifurcations.push(Bifurcation {
+                    kind: BifurcationKind::Fold,
+                    parameter: a.parameter,
+                    state: a.state,
+                });
+            }
+        }
+    }
+    bifurcations
+}
+
+/// Brute-force orbit diagram: for each parameter value the model is integrated from its
+/// initial state, and the local maxima of the prey after `transient` time units are
+/// recorded over the next `record` time units. Equilibria show up as their final value.
+pub fn orbit_diagram<M: ParametricModel>(
+    model: &M,
+    name: &str,
+    values: &[f64],
+    transient: f64,
+    record: f64,
+    step: f64,
+) -> Result<Vec<(f64, Vec<f64>)>, SimulationError> {
+    model
+        .clone()
+        .set_parameter(name, values.first().copied().unwrap_or(0.0))?;
+    par_map(values, |&value| {
+        let mut model = model.clone();
+        model.set_parameter(name, value)?;
+        let (times, states) = solve_model(&model, 0.0, transient + record, step)?;
+        let first = times.partition_point(|&t| t < transient);
+        let prey: Vec<f64> = states[first..].iter().map(|s| s[0]).collect();
+        let mut maxima: Vec<f64> = local_maxima(&prey).into_iter().map(|i| prey[i]).collect();
+        if maxima.is_empty() {
+            maxima.extend(prey.last());
+        }
+        Ok((value, maxima))
+    })
+    .into_iter()
+    .collect()
+}
+
+/// Writes the branch as CSV with one row per equilibrium.
+pub fn branch_to_csv(name: &str, branch: &[BranchPoint]) -> String {
+    let mut csv = format!("{},prey,predator,re1,im1,re2,im2,stable\n", name);
+    for point in branch {
+        let [(re1, im1), (re2, im2)] = point.eigenvalues;
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{}\n",
+            point.parameter,
+            point.state[0],
+            point.state[1],
+            re1,
+            im1,
+            re2,
+            im2,
+            point.stable()
+        ));
+    }
+    csv
+}
+
+/// Writes an orbit diagram as CSV with one row per recorded prey maximum.
+pub fn orbit_diagram_to_csv(name: &str, diagram: &[(f64, Vec<f64>)]) -> String {
+    let mut csv = format!("{},prey_maximum\n", name);
+    for (value, maxima) in diagram {
+        for maximum in maxima {
+            csv.push_str(&format!("{},{}\n", value, maximum));
+        }
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hopf_point_matches_paradox_of_enrichment() {
+        let model = RosenzweigMacArthur {
+            capacity: 1.0,
+            ..RosenzweigMacArthur::default()
+        };
+        let (branch, bifurcations) = continue_equilibria(
+            &model,
+            "capacity",
+            model.coexistence_equilibrium(),
+            (1.0, 4.0),
+            &ContinuationSettings::default(),
+        )
+        .unwrap();
+
+        // Hopf at K = 2 x* + 1 / (a h) with x* = m / (a (e - m h))
+        let expected = 2.0 * 0.2 / 0.3 + 1.0;
+        let hopf: Vec<_> = bifurcations
+            .iter()
+            .filter(|b| b.kind == BifurcationKind::Hopf)
+            .collect();
+        assert_eq!(hopf.len(), 1);
+        assert!((hopf[0].parameter - expected).abs() < 1e-3);
+        assert!(branch[0].stable());
+        assert!(!branch.last().unwrap().stable());
+        assert!((branch.last().unwrap().state[0] - 0.2 / 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_orbit_diagram_shows_limit_cycle() {
+        let model = RosenzweigMacArthur::default();
+        let diagram = orbit_diagram(&model, "capacity", &[2.0, 3.0], 300.0, 100.0, 0.1).unwrap();
+
+        // Below the Hopf point the prey settle at the equilibrium
+        assert!(diagram[0].1.iter().all(|x| (x - 0.2 / 0.3).abs() < 1e-2));
+        // Above it the prey cycle with maxima well above the equilibrium
+        assert!(diagram[1].1.iter().all(|&x| x > 1.0));
+    }
+}
@@ -0,0 +1,299 @@
+use crate::error::SimulationError;
+use crate::interventions::Schedule;
+use crate::models::{ExternalTerms, LotkaVolterraParameters, Species};
+use crate::solver::{solve_with_interventions, solve_with_progress};
+use crate::worker::Progress;
+
+type DelaySolution = Result<(Vec<f64>, Vec<f64>, Vec<f64>), SimulationError>;
+
+/// Populations before the start of the simulation, needed for the delayed terms.
+pub enum History {
+    /// Populations held at the given values for all `t < t_start`.
+    Constant([f64; 2]),
+    /// Populations given by a function of time for all `t < t_start`.
+    Function(Box<dyn Fn(f64) -> [f64; 2] + Send + Sync>),
+}
+
+impl History {
+    fn at(&self, t: f64) -> [f64; 2] {
+        match self {
+            History::Constant(y) => *y,
+            History::Function(f) => f(t),
+        }
+    }
+}
+
+/// Lotka-Volterra model with a maturation delay `tau` in predator reproduction:
+///
+/// ```text
+/// dx/dt = alpha x(t) - beta x(t) y(t)
+/// dy/dt = delta x(t - tau) y(t - tau) - gamma y(t)
+/// ```
+pub struct DelayLotkaVolterraSystem {
+    pub params: LotkaVolterraParameters,
+    pub terms: ExternalTerms,
+    pub tau: f64,
+    pub history: History,
+}
+
+impl DelayLotkaVolterraSystem {
+    /// Creates the delayed system with a constant history equal to the initial populations.
+    pub fn new(params: LotkaVolterraParameters, tau: f64) -> Self {
+        Self {
+            params,
+            terms: ExternalTerms::default(),
+            tau,
+            history: History::Constant([params.initial_prey, params.initial_predator]),
+        }
+    }
+
+    fn derivative(&self, t: f64, y: [f64; 2], delayed: [f64; 2]) -> [f64; 2] {
+        let [alpha, beta, delta, gamma] = self.terms.forced_rates(&self.params, t);
+        [
+            alpha * y[0] - beta * y[0] * y[1] - self.terms.harvest_rate(Species::Prey, t, y[0]),
+            delta * delayed[0] * delayed[1]
+                - gamma * y[1]
+                - self.terms.harvest_rate(Species::Predator, t, y[1]),
+        ]
+    }
+}
+
+/// Stored solution used to look up delayed states.
+struct Trajectory {
+    times: Vec<f64>,
+    states: Vec<[f64; 2]>,
+    slopes: Vec<[f64; 2]>,
+}
+
+impl Trajectory {
+    /// Cubic Hermite interpolation of the stored solution, falling back to the history
+    /// function before the first stored point. Past the last stored point the solution
+    /// is extrapolated along its last slope.
+    fn at(&self, t: f64, history: &History) -> [f64; 2] {
+        if t < self.times[0] {
+            return history.at(t);
+        }
+        let last = self.times.len() - 1;
+        if t >= self.times[last] {
+            return add_scaled(self.states[last], self.slopes[last], t - self.times[last]);
+        }
+        let i = self
+            .times
+            .partition_point(|&x| x <= t)
+            .clamp(1, self.times.len() - 1);
+        let (t0, t1) = (self.times[i - 1], self.times[i]);
+        let h = t1 - t0;
+        if h <= 0.0 {
+            return self.states[i];
+        }
+        let s = ((t - t0) / h).clamp(0.0, 1.0);
+        let h00 = 2.0 * s.powi(3) - 3.0 * s.powi(2) + 1.0;
+        let h10 = s.powi(3) - 2.0 * s.powi(2) + s;
+        let h01 = -2.0 * s.powi(3) + 3.0 * s.powi(2);
+        let h11 = s.powi(3) - s.powi(2);
+        let mut y = [0.0; 2];
+        for (k, value) in y.iter_mut().enumerate() {
+            *value = h00 * self.states[i - 1][k]
+                + h10 * h * self.slopes[i - 1][k]
+                + h01 * self.states[i][k]
+                + h11 * h * self.slopes[i][k];
+        }
+        y
+    }
+}
+
+fn add_scaled(y: [f64; 2], k: [f64; 2], h: f64) -> [f64; 2] {
+    [y[0] + h * k[0], y[1] + h * k[1]]
+}
+
+/// Solves the delayed system with classical Runge-Kutta steps of size `step`, looking up
+/// the delayed arguments by interpolating the already computed solution or the history.
+/// The step does not depend on `tau`: a delay shorter than a step reaches into that step,
+/// and its delayed arguments are extrapolated from the last computed point. A zero delay
+/// reduces to the ordinary model.
+pub fn solve_delay_lotka_volterra(system: &DelayLotkaVolterraSystem, step: f64) -> DelaySolution {
+    solve_delay_with_progress(system, step, &Progress::default())
+}
+
+/// Checks that a maturation delay, if given, is non-negative and comes without scheduled
+/// interventions, which the delay model does not apply.
+pub fn validate_delay(delay: Option<f64>, schedule: &Schedule) -> Result<(), SimulationError> {
+    match delay {
+        Some(tau) if tau.is_nan() || tau < 0.0 => Err(SimulationError::InvalidParameter(format!(
+            "The maturation delay must be non-negative, not {}.",
+            tau
+        ))),
+        Some(_) if !schedule.interventions.is_empty() => Err(SimulationError::InvalidParameter(
+            "Interventions cannot be combined with a maturation delay; remove one or the other."
+                .to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Solves the run from the initial populations of `params` with its harvesting, forcing
+/// and interventions, or with the maturation delay `delay` if given. A delay with
+/// interventions is rejected, as the delayed model has none.
+pub fn solve_run(
+    params: &LotkaVolterraParameters,
+    terms: &ExternalTerms,
+    schedule: &Schedule,
+    delay: Option<f64>,
+    step: f64,
+) -> DelaySolution {
+    validate_delay(delay, schedule)?;
+    match delay {
+        Some(tau) => {
+            let mut system = DelayLotkaVolterraSystem::new(*params, tau);
+            system.terms = terms.clone();
+            solve_delay_lotka_volterra(&system, step)
+        }
+        None => solve_with_interventions(
+            *params,
+            terms,
+            schedule,
+            [params.initial_prey, params.initial_predator],
+            params.t_start,
+            params.t_end,
+            step,
+        )
+        .map_err(|e| match e.downcast::<SimulationError>() {
+            Ok(e) => *e,
+            Err(e) => SimulationError::SolverError(e.to_string()),
+        }),
+    }
+}
+
+/// Solves the delayed system like [`solve_delay_lotka_volterra`], reporting the fraction
+/// of the time span covered to `progress`. Once `progress` is cancelled the solve stops
+/// with [`SimulationError::UserCancelled`].
+pub fn solve_delay_with_progress(
+    system: &DelayLotkaVolterraSystem,
+    step: f64,
+    progress: &Progress,
+) -> DelaySolution {
+    let params = system.params;
+    if step <= 0.0 || system.tau < 0.0 {
+        return Err(SimulationError::InvalidParameter(
+            "The step must be positive and the delay non-negative.".to_string(),
+        ));
+    }
+    if system.tau == 0.0 {
+        return solve_with_progress(params, &system.terms, &Schedule::default(), step, progress)
+            .map_err(|e| match e.downcast::<SimulationError>() {
+                Ok(e) => *e,
+                Err(e) => SimulationError::SolverError(e.to_string()),
+            });
+    }
+
+    let tau = system.tau;
+    let history = &system.history;
+    let t_start = params.t_start;
+    let y0 = [params.initial_prey, params.initial_predator];
+
+    let mut trajectory = Trajectory {
+        times: vec![t_start],
+        states: vec![y0],
+        slopes: vec![system.derivative(t_start, y0, history.at(t_start - tau))],
+    };
+
+    let mut t = t_start;
+    while t < params.t_end - 1e-12 {
+        if progress.is_cancelled() {
+            return Err(SimulationError::UserCancelled);
+        }
+        let dt = step.min(params.t_end - t);
+        let y = *trajectory.states.last().unwrap();
+        let lag = |s: f64| trajectory.at(s - tau, history);
+
+        let k1 = *trajectory.slopes.last().unwrap();
+        let k2 = system.derivative(t + dt / 2.0, add_scaled(y, k1, dt / 2.0), lag(t + dt / 2.0));
+        let k3 = system.derivative(t + dt / 2.0, add_scaled(y, k2, dt / 2.0), lag(t + dt / 2.0));
+        let k4 = system.derivative(t + dt, add_scaled(y, k3, dt), lag(t + dt));
+        let next = [
+            y[0] + dt / 6.0 * (k1[0] + 2.0 * k2[0] + 2.0 * k3[0] + k4[0]),
+            y[1] + dt / 6.0 * (k1[1] + 2.0 * k2[1] + 2.0 * k3[1] + k4[1]),
+        ];
+
+        t += dt;
+        let slope = system.derivative(t, next, lag(t));
+        trajectory.times.push(t);
+        trajectory.states.push(next);
+        trajectory.slopes.push(slope);
+        progress.set_fraction((t - t_start) / (params.t_end - t_start));
+    }
+
+    Ok((
+        trajectory.times,
+        trajectory.states.iter().map(|y| y[0]).collect(),
+        trajectory.states.iter().map(|y| y[1]).collect(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::solve_lotka_volterra;
+
+    fn test_params() -> LotkaVolterraParameters {
+        LotkaVolterraParameters {
+            alpha: 0.1,
+            beta: 0.02,
+            gamma: 0.02,
+            delta: 0.1,
+            initial_prey: 40.0,
+            initial_predator: 9.0,
+            t_start: 0.0,
+            t_end: 50.0,
+        }
+    }
+
+    #[test]
+    fn test_small_delay_matches_ode() {
+        let params = test_params();
+        let system = DelayLotkaVolterraSystem::new(params, 1e-3);
+        let (_, prey, predators) = solve_delay_lotka_volterra(&system, 0.01).unwrap();
+        let (_, ode_prey, ode_predators) =
+            solve_lotka_volterra(params, [40.0, 9.0], 0.0, 50.0, 0.1).unwrap();
+
+        assert!((prey.last().unwrap() - ode_prey.last().unwrap()).abs() < 0.1);
+        assert!((predators.last().unwrap() - ode_predators.last().unwrap()).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_tiny_delay_keeps_the_step() {
+        let system = DelayLotkaVolterraSystem::new(test_params(), 1e-12);
+        let (times, prey, _) = solve_delay_lotka_volterra(&system, 0.1).unwrap();
+        assert_eq!(times.len(), 501);
+        let (_, ode_prey, _) =
+            solve_lotka_volterra(test_params(), [40.0, 9.0], 0.0, 50.0, 0.1).unwrap();
+        assert!((prey.last().unwrap() - ode_prey.last().unwrap()).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_history_function_is_used() {
+        let params = test_params();
+        let mut system = DelayLotkaVolterraSystem::new(params, 5.0);
+        system.history = History::Function(Box::new(|_| [0.0, 0.0]));
+
+        // Without any delayed reproduction the predators decay exponentially at first
+        let (times, _, predators) = solve_delay_lotka_volterra(&system, 0.01).unwrap();
+        let i = times.iter().position(|&t| t >= 4.0).unwrap();
+        let expected = 9.0 * (-params.gamma * times[i]).exp();
+        assert!((predators[i] - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_cancelled_solve_stops() {
+        let system = DelayLotkaVolterraSystem::new(test_params(), 5.0);
+        let progress = Progress::default();
+        solve_delay_with_progress(&system, 0.01, &progress).unwrap();
+        assert_eq!(progress.fraction(), 1.0);
+
+        progress.cancel();
+        assert!(matches!(
+            solve_delay_with_progress(&system, 0.01, &progress),
+            Err(SimulationError::UserCancelled)
+        ));
+    }
+}
@@ -0,0 +1,248 @@
+use crate::error::SimulationError;
+use crate::models::{DiscreteModel, PopulationModel};
+use crate::parallel::par_map;
+
+/// Discrete-time host-parasitoid maps for populations with non-overlapping generations.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MapKind {
+    /// `H' = lambda H exp(-a P)`, `P' = c H (1 - exp(-a P))`.
+    NicholsonBailey,
+    /// May's model with negative binomial (clumped) attacks:
+    /// `H' = lambda H f`, `P' = c H (1 - f)` with `f = (1 + a P / k)^-k`.
+    NegativeBinomial,
+    /// Discrete Lotka-Volterra model with Ricker density dependence in the host:
+    /// `H' = H exp(ln(lambda) (1 - H / K) - a P)`, `P' = c H (1 - exp(-a P))`.
+    Ricker,
+}
+
+/// A host-parasitoid map and its parameters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HostParasitoidMap {
+    pub kind: MapKind,
+    pub lambda: f64,             // Host finite rate of increase
+    pub search_efficiency: f64,  // Parasitoid searching efficiency a
+    pub conversion: f64,         // Parasitoids emerging per parasitized host c
+    pub clumping: f64,           // Negative binomial clumping parameter k
+    pub capacity: f64,           // Host carrying capacity K of the Ricker map
+    pub initial_host: f64,       // Initial host population
+    pub initial_parasitoid: f64, // Initial parasitoid population
+}
+
+impl HostParasitoidMap {
+    /// Names accepted by [`HostParasitoidMap::set`].
+    pub const NAMES: [&'static str; 7] = [
+        "lambda",
+        "search_efficiency",
+        "conversion",
+        "clumping",
+        "capacity",
+        "initial_host",
+        "initial_parasitoid",
+    ];
+
+    /// Creates a map with the default parameters.
+    pub fn new(kind: MapKind) -> Self {
+        Self {
+            kind,
+            lambda: 2.0,
+            search_efficiency: 0.05,
+            conversion: 1.0,
+            clumping: 0.5,
+            capacity: 100.0,
+            initial_host: 25.0,
+            initial_parasitoid: 10.0,
+        }
+    }
+
+    /// Sets a parameter by name.
+    pub fn set(&mut self, name: &str, value: f64) -> Result<(), SimulationError> {
+        let field = match name {
+            "lambda" => &mut self.lambda,
+            "search_efficiency" => &mut self.search_efficiency,
+            "conversion" => &mut self.conversion,
+            "clumping" => &mut self.clumping,
+            "capacity" => &mut self.capacity,
+            "initial_host" => &mut self.initial_host,
+            "initial_parasitoid" => &mut self.initial_parasitoid,
+            _ => {
+                return Err(SimulationError::InvalidParameter(format!(
+                    "Unknown map parameter '{}'",
+                    name
+                )));
+            }
+        };
+        *field = value;
+        Ok(())
+    }
+
+    /// Checks that all parameters are positive and the populations non-negative.
+    pub fn validate(&self) -> Result<(), SimulationError> {
+        let rates = [
+            self.lambda,
+            self.search_efficiency,
+            self.conversion,
+            self.clumping,
+            self.capacity,
+        ];
+        if rates.iter().any(|&r| r <= 0.0)
+            || self.initial_host < 0.0
+            || self.initial_parasitoid < 0.0
+        {
+            return Err(SimulationError::InvalidParameter(
+                "Map parameters must be positive and initial populations non-negative.".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Fraction of hosts escaping parasitism at parasitoid density `p`.
+    fn escape(&self, p: f64) -> f64 {
+        match self.kind {
+            MapKind::NegativeBinomial => {
+                (1.0 + self.search_efficiency * p / self.clumping).powf(-self.clumping)
+            }
+            MapKind::NicholsonBailey | MapKind::Ricker => (-self.search_efficiency * p).exp(),
+        }
+    }
+}
+
+impl PopulationModel for HostParasitoidMap {
+    fn dimension(&self) -> usize {
+        2
+    }
+
+    fn initial_state(&self) -> Vec<f64> {
+        vec![self.initial_host, self.initial_parasitoid]
+    }
+}
+
+impl DiscreteModel for HostParasitoidMap {
+    fn next_generation(&self, state: &[f64], next: &mut [f64]) {
+        let (h, p) = (state[0], state[1]);
+        let escape = self.escape(p);
+        let growth = match self.kind {
+            MapKind::Ricker => (self.lambda.ln() * (1.0 - h / self.capacity)).exp(),
+            MapKind::NicholsonBailey | MapKind::NegativeBinomial => self.lambda,
+        };
+        next[0] = h * growth * escape;
+        next[1] = self.conversion * h * (1.0 - escape);
+    }
+}
+
+/// Iterates a discrete model for `generations` generations and returns every state,
+/// starting with the initial one.
+pub fn iterate_map<M: DiscreteModel>(model: &M, generations: usize) -> Vec<Vec<f64>> {
+    let mut states = Vec::with_capacity(generations + 1);
+    let mut state = model.initial_state();
+    let mut next = vec![0.0; model.dimension()];
+    states.push(state.clone());
+    for _ in 0..generations {
+        model.next_generation(&state, &mut next);
+        std::mem::swap(&mut state, &mut next);
+        states.push(state.clone());
+    }
+    states
+}
+
+/// A range of parameter values given as `name=min:max`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sweep {
+    pub name: String,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl std::str::FromStr for Sweep {
+    type Err = SimulationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            SimulationError::InvalidParameter(format!(
+                "Sweep '{}' must have the form name=min:max with min < max",
+                s
+            ))
+        };
+        let (name, bounds) = s.split_once('=').ok_or_else(invalid)?;
+        let (min, max) = bounds.split_once(':').ok_or_else(invalid)?;
+        let min: f64 = min.trim().parse().map_err(|_| invalid())?;
+        let max: f64 = max.trim().parse().map_err(|_| invalid())?;
+        if min >= max || min.is_nan() || max.is_nan() {
+            return Err(invalid());
+        }
+        Ok(Self {
+            name: name.trim().replace('-', "_"),
+            min,
+            max,
+        })
+    }
+}
+
+/// Bifurcation diagram of the host population: for each of `points` values of the
+/// swept parameter, the map is iterated for `transient` generations and the host
+/// densities of the following `keep` generations are returned.
+pub fn bifurcation_diagram(
+    model: &HostParasitoidMap,
+    sweep: &Sweep,
+    points: usize,
+    transient: usize,
+    keep: usize,
+) -> Result<Vec<(f64, Vec<f64>)>, SimulationError> {
+    let mut check = *model;
+    check.set(&sweep.name, sweep.min)?;
+    let values: Vec<f64> = (0..points)
+        .map(|i| sweep.min + (sweep.max - sweep.min) * i as f64 / (points.max(2) - 1) as f64)
+        .collect();
+
+    Ok(par_map(&values, |&value| {
+        let mut model = *model;
+        // The name was checked above
+        model.set(&sweep.name, value).ok();
+        let states = iterate_map(&model, transient + keep);
+        let hosts = states[transient + 1..]
+            .iter()
+            .map(|s| s[0])
+            .filter(|h| h.is_finite())
+            .collect();
+        (value, hosts)
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nicholson_bailey_equilibrium_is_fixed() {
+        let mut model = HostParasitoidMap::new(MapKind::NicholsonBailey);
+        let (lambda, a, c) = (model.lambda, model.search_efficiency, model.conversion);
+        model.initial_host = lambda * lambda.ln() / ((lambda - 1.0) * a * c);
+        model.initial_parasitoid = lambda.ln() / a;
+
+        let states = iterate_map(&model, 10);
+        assert_eq!(states.len(), 11);
+        assert!((states[10][0] - model.initial_host).abs() < 1e-9);
+        assert!((states[10][1] - model.initial_parasitoid).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_clumping_stabilizes_negative_binomial() {
+        let model = HostParasitoidMap::new(MapKind::NegativeBinomial);
+        let states = iterate_map(&model, 500);
+        let last = &states[500];
+        let previous = &states[499];
+        assert!((last[0] - previous[0]).abs() < 1e-6);
+        assert!(last[0] > 0.0 && last[1] > 0.0);
+    }
+
+    #[test]
+    fn test_bifurcation_diagram() {
+        let model = HostParasitoidMap::new(MapKind::Ricker);
+        let sweep: Sweep = "lambda=1.5:3".parse().unwrap();
+        let diagram = bifurcation_diagram(&model, &sweep, 5, 100, 20).unwrap();
+
+        assert_eq!(diagram.len(), 5);
+        assert_eq!(diagram[4].0, 3.0);
+        assert_eq!(diagram[0].1.len(), 20);
+        assert!("x=3:1".parse::<Sweep>().is_err());
+    }
+}
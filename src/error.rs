@@ -6,6 +6,7 @@ pub enum SimulationError {
     GuiError(String),
     InvalidParameter(String),
     PlotError(String),
+    SolverError(String),
     UserCancelled,
 }
 
@@ -15,6 +16,7 @@ impl fmt::Display for SimulationError {
             SimulationError::GuiError(msg) => write!(f, "GUI error: {}", msg),
             SimulationError::InvalidParameter(msg) => write!(f, "Invalid parameter: {}", msg),
             SimulationError::PlotError(msg) => write!(f, "Plot error: {}", msg),
+            SimulationError::SolverError(msg) => write!(f, "Solver error: {}", msg),
             SimulationError::UserCancelled => write!(f, "Simulation cancelled by user."),
         }
     }
@@ -47,6 +49,12 @@ mod tests {
         assert_eq!(format!("{}", error), "Plot error: Failed to render plot");
     }
 
+    #[test]
+    fn test_solver_error() {
+        let error = SimulationError::SolverError("Step size too small".to_string());
+        assert_eq!(format!("{}", error), "Solver error: Step size too small");
+    }
+
     #[test]
     fn test_user_cancelled() {
         let error = SimulationError::UserCancelled;
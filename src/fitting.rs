@@ -0,0 +1,341 @@
+use crate::dde::solve_run;
+use crate::error::SimulationError;
+use crate::models::LotkaVolterraParameters;
+use crate::scenario::Scenario;
+use crate::worker::Progress;
+use std::path::Path;
+
+/// Rates adjusted by the fit.
+pub const FITTED_RATES: [&str; 4] = ["alpha", "beta", "delta", "gamma"];
+
+/// Most iterations of the simplex search.
+const MAX_ITERATIONS: usize = 2000;
+
+/// Solver output steps per observation interval.
+const STEPS_PER_INTERVAL: f64 = 10.0;
+
+/// Observed populations, with times in increasing order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Observations {
+    pub times: Vec<f64>,
+    pub prey: Vec<f64>,
+    pub predators: Vec<f64>,
+}
+
+impl Observations {
+    /// Reads and parses a CSV file of observations.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SimulationError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            SimulationError::InvalidParameter(format!("Cannot read {}: {}", path.display(), e))
+        })?;
+        Self::parse(&text)
+    }
+
+    /// Parses CSV rows of `time,prey,predators`, as written by the CSV export. A header
+    /// line and blank lines are skipped.
+    pub fn parse(text: &str) -> Result<Self, SimulationError> {
+        let mut data = Observations::default();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || (number == 0 && line.starts_with(|c: char| c.is_alphabetic())) {
+                continue;
+            }
+            let invalid = |msg: &str| {
+                SimulationError::InvalidParameter(format!("line {}: {}", number + 1, msg))
+            };
+            let fields = line
+                .split(',')
+                .map(|field| field.trim().parse::<f64>())
+                .collect::<Result<Vec<f64>, _>>()
+                .map_err(|_| invalid("expected numbers"))?;
+            let [t, prey, predators] = fields[..] else {
+                return Err(invalid("expected time, prey and predators"));
+            };
+            if !(t.is_finite() && prey >= 0.0 && predators >= 0.0) {
+                return Err(invalid("populations must be non-negative"));
+            }
+            if data.times.last().is_some_and(|&last| t <= last) {
+                return Err(invalid("times must increase"));
+            }
+            data.times.push(t);
+            data.prey.push(prey);
+            data.predators.push(predators);
+        }
+        if data.times.len() < 2 {
+            return Err(SimulationError::InvalidParameter(
+                "At least two observations are needed for a fit.".to_string(),
+            ));
+        }
+        Ok(data)
+    }
+
+    /// The parameters of `base` over the observed time span, starting from the first
+    /// observed populations.
+    pub fn span_params(&self, base: &LotkaVolterraParameters) -> LotkaVolterraParameters {
+        LotkaVolterraParameters {
+            initial_prey: self.prey[0],
+            initial_predator: self.predators[0],
+            t_start: self.times[0],
+            t_end: self.times[self.times.len() - 1],
+            ..*base
+        }
+    }
+
+    /// Solver output step fine enough to resolve every observation interval.
+    fn step(&self) -> f64 {
+        let span = self.times[self.times.len() - 1] - self.times[0];
+        span / (STEPS_PER_INTERVAL * (self.times.len() - 1) as f64)
+    }
+}
+
+/// Rates that best reproduce a set of observations, and the run they give.
+#[derive(Debug, Clone)]
+pub struct Fit {
+    pub params: LotkaVolterraParameters,
+    /// Root-mean-square residual, with each species scaled by its largest observation.
+    pub error: f64,
+    pub iterations: usize,
+    pub times: Vec<f64>,
+    pub prey: Vec<f64>,
+    pub predators: Vec<f64>,
+}
+
+/// Fits the rates of `scenario` to the observations by least squares, running the model
+/// with the scenario's harvesting, forcing, interventions and delay from the first
+/// observation. The search is a Nelder-Mead simplex over the logarithms of the rates,
+/// starting from the scenario's values. Progress is reported to `progress`, and a
+/// cancelled fit returns [`SimulationError::UserCancelled`].
+pub fn fit_rates(
+    scenario: &Scenario,
+    data: &Observations,
+    progress: &Progress,
+) -> Result<Fit, SimulationError> {
+    scenario.terms.validate()?;
+    scenario.schedule.validate()?;
+    let start = data.span_params(&scenario.params);
+    let mut x0 = Vec::with_capacity(FITTED_RATES.len());
+    for name in FITTED_RATES {
+        let value = start.get(name)?;
+        if value <= 0.0 {
+            return Err(SimulationError::InvalidParameter(format!(
+                "The fit starts from positive rates, but {} is {}.",
+                name, value
+            )));
+        }
+        x0.push(value.ln());
+    }
+
+    let params_at = |x: &[f64]| {
+        let mut params = start;
+        for (name, value) in FITTED_RATES.iter().zip(x) {
+            params.set(name, value.exp()).ok();
+        }
+        params
+    };
+    let cost = |x: &[f64]| residual(scenario, data, &params_at(x)).unwrap_or(f64::INFINITY);
+    let (x, error, iterations) = nelder_mead(cost, &x0, progress)?;
+    if !error.is_finite() {
+        return Err(SimulationError::SolverError(
+            "No rates near the starting values could be solved over the observations.".to_string(),
+        ));
+    }
+
+    let params = params_at(&x);
+    let (times, prey, predators) = solve_run(
+        &params,
+        &scenario.terms,
+        &scenario.schedule,
+        scenario.delay,
+        data.step(),
+    )?;
+    Ok(Fit {
+        params,
+        error: (error / (2 * data.times.len()) as f64).sqrt(),
+        iterations,
+        times,
+        prey,
+        predators,
+    })
+}
+
+/// Sum of squared residuals between the run of `params` and the observations, with each
+/// species scaled by its largest observation.
+fn residual(
+    scenario: &Scenario,
+    data: &Observations,
+    params: &LotkaVolterraParameters,
+) -> Result<f64, SimulationError> {
+    let (times, prey, predators) = solve_run(
+        params,
+        &scenario.terms,
+        &scenario.schedule,
+        scenario.delay,
+        data.step(),
+    )?;
+    let scale = |values: &[f64]| values.iter().copied().fold(0.0, f64::max).max(1e-12);
+    let (prey_scale, predator_scale) = (scale(&data.prey), scale(&data.predators));
+    let mut sum = 0.0;
+    for ((&t, &x), &y) in data.times.iter().zip(&data.prey).zip(&data.predators) {
+        let dx = (interpolate(&times, &prey, t) - x) / prey_scale;
+        let dy = (interpolate(&times, &predators, t) - y) / predator_scale;
+        sum += dx * dx + dy * dy;
+    }
+    if sum.is_finite() {
+        Ok(sum)
+    } else {
+        Err(SimulationError::SolverError(
+            "The run diverged.".to_string(),
+        ))
+    }
+}
+
+/// Linear interpolation of a series sampled at increasing `times`, held constant beyond
+/// its ends.
+fn interpolate(times: &[f64], values: &[f64], t: f64) -> f64 {
+    let i = times.partition_point(|&time| time < t);
+    match i {
+        0 => values.first().copied().unwrap_or(f64::NAN),
+        i if i >= times.len() => values.last().copied().unwrap_or(f64::NAN),
+        i => {
+            let (t0, t1) = (times[i - 1], times[i]);
+            let w = if t1 > t0 { (t - t0) / (t1 - t0) } else { 1.0 };
+            values[i - 1] + w * (values[i] - values[i - 1])
+        }
+    }
+}
+
+/// Minimises `f` with the Nelder-Mead simplex method from `x0`, returning the best point,
+/// its value and the number of iterations.
+fn nelder_mead(
+    f: impl Fn(&[f64]) -> f64,
+    x0: &[f64],
+    progress: &Progress,
+) -> Result<(Vec<f64>, f64, usize), SimulationError> {
+    let n = x0.len();
+    let mut simplex: Vec<(Vec<f64>, f64)> = (0..=n)
+        .map(|i| {
+            let mut x = x0.to_vec();
+            if i > 0 {
+                x[i - 1] += 0.2;
+            }
+            let value = f(&x);
+            (x, value)
+        })
+        .collect();
+    let towards = |from: &[f64], to: &[f64], t: f64| -> Vec<f64> {
+        from.iter().zip(to).map(|(a, b)| a + t * (b - a)).collect()
+    };
+
+    let mut iterations = 0;
+    while iterations < MAX_ITERATIONS {
+        if progress.is_cancelled() {
+            return Err(SimulationError::UserCancelled);
+        }
+        progress.set_fraction(iterations as f64 / MAX_ITERATIONS as f64);
+        simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+        let (best, worst) = (simplex[0].1, simplex[n].1);
+        let size = simplex[1..]
+            .iter()
+            .flat_map(|(x, _)| x.iter().zip(&simplex[0].0).map(|(a, b)| (a - b).abs()))
+            .fold(0.0, f64::max);
+        if worst - best <= 1e-12 * (best.abs() + 1e-12) && size < 1e-6 {
+            break;
+        }
+        iterations += 1;
+
+        let centroid: Vec<f64> = (0..n)
+            .map(|j| simplex[..n].iter().map(|(x, _)| x[j]).sum::<f64>() / n as f64)
+            .collect();
+        let reflected = towards(&centroid, &simplex[n].0, -1.0);
+        let reflected_value = f(&reflected);
+        if reflected_value < best {
+            let expanded = towards(&centroid, &simplex[n].0, -2.0);
+            let expanded_value = f(&expanded);
+            simplex[n] = if expanded_value < reflected_value {
+                (expanded, expanded_value)
+            } else {
+                (reflected, reflected_value)
+            };
+        } else if reflected_value < simplex[n - 1].1 {
+            simplex[n] = (reflected, reflected_value);
+        } else {
+            let contracted = if reflected_value < worst {
+                towards(&centroid, &reflected, 0.5)
+            } else {
+                towards(&centroid, &simplex[n].0, 0.5)
+            };
+            let contracted_value = f(&contracted);
+            if contracted_value < reflected_value.min(worst) {
+                simplex[n] = (contracted, contracted_value);
+            } else {
+                // Shrink everything towards the best point
+                let best_point = simplex[0].0.clone();
+                for (x, value) in &mut simplex[1..] {
+                    *x = towards(&best_point, x, 0.5);
+                    *value = f(x);
+                }
+            }
+        }
+    }
+    simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+    progress.set_fraction(1.0);
+    let (x, value) = simplex.swap_remove(0);
+    Ok((x, value, iterations))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::trajectory_csv;
+
+    #[test]
+    fn test_fit_recovers_rates_of_exported_run() {
+        let truth = LotkaVolterraParameters {
+            alpha: 0.5,
+            beta: 0.02,
+            delta: 0.01,
+            gamma: 0.4,
+            initial_prey: 40.0,
+            initial_predator: 10.0,
+            t_start: 0.0,
+            t_end: 30.0,
+        };
+        let scenario = Scenario {
+            params: truth,
+            ..Scenario::default()
+        };
+        let (times, prey, predators) =
+            solve_run(&truth, &scenario.terms, &scenario.schedule, None, 0.5).unwrap();
+        let data = Observations::parse(&trajectory_csv(&times, &prey, &predators)).unwrap();
+        assert_eq!(data.times.len(), times.len());
+
+        // Start 20% away from the rates that made the data
+        let mut start = scenario.clone();
+        for name in FITTED_RATES {
+            let value = truth.get(name).unwrap();
+            start.params.set(name, value * 1.2).unwrap();
+        }
+        let fit = fit_rates(&start, &data, &Progress::default()).unwrap();
+        for name in FITTED_RATES {
+            let (fitted, exact) = (fit.params.get(name).unwrap(), truth.get(name).unwrap());
+            assert!(
+                (fitted - exact).abs() < 0.01 * exact,
+                "{}: fitted {} but the data came from {}",
+                name,
+                fitted,
+                exact
+            );
+        }
+        assert!(fit.error < 1e-3);
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_rows() {
+        assert!(Observations::parse("time,prey,predators\n0,1,2\n1,2,3\n").is_ok());
+        assert!(Observations::parse("0,1,2\n").is_err());
+        assert!(Observations::parse("0,1,2\n0,2,3\n").is_err());
+        assert!(Observations::parse("0,1\n1,2\n").is_err());
+        assert!(Observations::parse("0,1,-2\n1,2,3\n").is_err());
+    }
+}
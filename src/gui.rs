@@ -1,382 +1,2066 @@
+mod agents;
+mod ensemble;
+mod fitting;
+mod spatial;
+mod stochastic;
+mod sweep;
+
+use crate::agents::AgentParameters;
+use crate::analysis::{
+    divergence_time, nearest_point, nearest_sample, peak, trajectory_difference,
+};
+use crate::dde::{DelayLotkaVolterraSystem, solve_delay_with_progress, validate_delay};
 use crate::error::SimulationError;
-use crate::models::LotkaVolterraParameters;
-use crate::solver::solve_lotka_volterra;
+use crate::history::History;
+use crate::interactive::validate_params;
+use crate::interventions::Schedule;
+use crate::models::{
+    ExternalTerms, Forcing, Harvest, HarvestStrategy, LotkaVolterraParameters, Rate, Species,
+};
+use crate::phase::{autonomous_params, direction_field, equilibria, nullclines, phase_orbits};
+use crate::plot::{plot_phase_portrait_window, plot_time_series};
+use crate::plot_config::{PlotConfig, PlotFormat};
+use crate::report::{parameter_table, trajectory_csv, trajectory_json};
+use crate::scenario::Scenario;
+use crate::session::{SavedSnapshot, Session, SliderRange, View};
+use crate::solver::{solve_window, solve_with_progress};
+use crate::spatial::SpatialParameters;
+use crate::stochastic::{decimate, decimate_min_max, simulate_gillespie_with_interventions};
+use crate::worker::{Debouncer, Job, Progress};
 use eframe::egui;
-use egui_plot::{Corner, Legend, Line, Plot, PlotPoints};
-use indicatif::{ProgressBar, ProgressStyle};
+use egui_plot::{Arrows, Corner, Legend, Line, Plot, PlotPoints, Points, Polygon, VLine};
 use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use agents::AgentView;
+use ensemble::EnsembleView;
+use fitting::FittingView;
+use spatial::SpatialView;
+use stochastic::StochasticView;
+use sweep::SweepView;
+
+/// Times, prey and predators of a stochastic realisation.
+type Realisation = (Vec<f64>, Vec<f64>, Vec<f64>);
+
+/// Quiet period after the last slider change before a new solve starts.
+const SOLVE_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// Most output samples of the solve over the whole time span. Spans longer than
+/// `SOLVE_SAMPLES / 10` are solved at a coarser output step than 0.1.
+const SOLVE_SAMPLES: f64 = 100_000.0;
+
+/// Output samples of the denser solve of a zoomed-in time window.
+const ZOOM_SAMPLES: f64 = 4000.0;
+
+/// Runs of points whose lowest and highest points are drawn in the time plot.
+const PLOT_BUCKETS: usize = 1000;
+
+/// Output step of the solve over the whole time span.
+fn output_step(params: &LotkaVolterraParameters) -> f64 {
+    ((params.t_end - params.t_start) / SOLVE_SAMPLES).max(0.1)
+}
+
+/// Denser solution of a zoomed-in time window, plotted in place of the whole run there.
+struct Zoom {
+    window: (f64, f64),
+    step: f64,
+    prey: Vec<[f64; 2]>,
+    predators: Vec<[f64; 2]>,
+}
+
+impl Zoom {
+    /// Whether solving `window` at `step` covers the visible window `(a, b)` densely
+    /// enough for output steps down to `needed`.
+    fn covers(window: (f64, f64), step: f64, (a, b): (f64, f64), needed: f64) -> bool {
+        window.0 <= a && b <= window.1 && step <= 2.0 * needed
+    }
+}
+
+/// Output of a background solve: the time series, the clicked orbits and, when overlaid,
+/// a stochastic realisation.
+struct Solution {
+    params: LotkaVolterraParameters,
+    times: Vec<f64>,
+    prey: Vec<f64>,
+    predators: Vec<f64>,
+    orbits: Vec<Vec<[f64; 2]>>,
+    stochastic: Option<Realisation>,
+}
+
+/// Most remembered parameter sets.
+const HISTORY_CAPACITY: usize = 100;
+
+/// Undo and redo, as with Ctrl+Z and Ctrl+Shift+Z or Ctrl+Y (Cmd on macOS).
+const UNDO: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Z);
+const REDO: egui::KeyboardShortcut = egui::KeyboardShortcut::new(
+    egui::Modifiers {
+        shift: true,
+        ..egui::Modifiers::COMMAND
+    },
+    egui::Key::Z,
+);
+const REDO_ALT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Y);
+
+/// What clicking the time or phase plot does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ClickTool {
+    Probe,   // Marks the nearest sample on both plots
+    Measure, // Marks two samples and reports the time between them
+    Orbit,   // Adds an orbit from the clicked point of the phase plot
+}
+
+/// Colors of the probe and measurement markers.
+const PROBE_COLOR: egui::Color32 = egui::Color32::from_rgb(0, 150, 0);
+const MEASURE_COLOR: egui::Color32 = egui::Color32::from_rgb(150, 0, 150);
+
+/// What the export buttons save.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Export {
+    PlotPng,
+    PlotSvg,
+    Csv,
+    Json,
+    Scenario,
+}
+
+impl Export {
+    fn extension(self) -> &'static str {
+        match self {
+            Export::PlotPng => "png",
+            Export::PlotSvg => "svg",
+            Export::Csv => "csv",
+            Export::Json => "json",
+            Export::Scenario => "txt",
+        }
+    }
+}
 
-/// Main application struct for the Lotka-Volterra GUI.
+/// A solved parameter set in the history, with a thumbnail of its phase plot.
+#[derive(Debug, Clone)]
+struct HistoryEntry {
+    params: LotkaVolterraParameters,
+    thumbnail: Vec<[f64; 2]>,
+}
+
+/// Entries are the same parameter set whatever their thumbnails, so solving the same
+/// parameters again, as after a change of harvesting, forcing, interventions or delay,
+/// records nothing.
+impl PartialEq for HistoryEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.params == other.params
+    }
+}
+
+/// A pinned run, overlaid on the current one to compare parameter sets.
+struct Snapshot {
+    name: String,
+    params: LotkaVolterraParameters,
+    times: Vec<f64>,
+    prey: Vec<f64>,
+    predators: Vec<f64>,
+    color: egui::Color32,
+    visible: bool,
+}
+
+impl Snapshot {
+    fn series(&self, values: &[f64]) -> PlotPoints<'static> {
+        self.times
+            .iter()
+            .zip(values)
+            .map(|(&t, &v)| [t, v])
+            .collect()
+    }
+
+    fn phase(&self) -> PlotPoints<'static> {
+        self.prey
+            .iter()
+            .zip(&self.predators)
+            .map(|(&x, &y)| [x, y])
+            .collect()
+    }
+}
+
+/// A snapshot of a saved session with its color, solved again on a worker thread.
+type Restoring = (SavedSnapshot, egui::Color32, Job<Result<Solution, String>>);
+
+/// Main application struct for the Lotka-Volterra GUI. The parameters are shared by all
+/// views.
 pub struct LotkaVolterraApp {
-    params: LotkaVolterraParameters, // Previously defined parameters
-    prey_points: Vec<[f64; 2]>,      // Prey population over time
-    predator_points: Vec<[f64; 2]>,  // Predator population over time
-    phase_points: Vec<[f64; 2]>,     // Predator vs. Prey (phase plot)
-    error_message: Option<String>,   // Stores error messages for display
-    t_start: f64,                    // Start time
-    t_end: f64,                      // End time
+    view: View,                                  // View shown in the central panel
+    params: LotkaVolterraParameters,             // Previously defined parameters
+    terms: ExternalTerms,                        // Harvesting and forcing terms
+    schedule: Schedule,                          // Scheduled interventions
+    delay: Option<f64>,                          // Maturation delay of the delay model
+    solved: Option<LotkaVolterraParameters>,     // Parameters of the run shown
+    prey_points: Vec<[f64; 2]>,                  // Prey population over time
+    predator_points: Vec<[f64; 2]>,              // Predator population over time
+    phase_points: Vec<[f64; 2]>,                 // Predator vs. Prey (phase plot)
+    phase_starts: Vec<[f64; 2]>,                 // Initial conditions of clicked orbits
+    phase_orbits: Vec<Vec<[f64; 2]>>,            // Orbits from the clicked initial conditions
+    overlay: bool,                               // Overlay a stochastic realisation
+    stochastic_prey: Vec<[f64; 2]>,              // Prey of the stochastic realisation over time
+    stochastic_predators: Vec<[f64; 2]>, // Predators of the stochastic realisation over time
+    stochastic_phase: Vec<[f64; 2]>,     // Phase path of the stochastic realisation
+    show_field: bool,                    // Draw the direction field in the phase plot
+    show_nullclines: bool,               // Draw nullclines and equilibria in the phase plot
+    error_message: Option<String>,       // Stores error messages for display
+    initial: LotkaVolterraParameters,    // Parameters the GUI was launched with
+    sliders: [SliderRange; 8],           // Slider of each parameter, in `SLIDERS` order
+    job: Option<Job<Result<Solution, String>>>, // Solve running on a worker thread
+    debouncer: Debouncer,                // Delays solves while sliders are dragged
+    history: History<HistoryEntry>,      // Solved parameter sets for undo and redo
+    snapshots: Vec<Snapshot>,            // Pinned runs
+    restoring: Vec<Restoring>,           // Saved snapshots being solved again
+    snapshot_name: String,               // Name for the next pinned run
+    pinned: usize,                       // Number of runs pinned so far, for names and colors
+    compared: usize,                     // Snapshot compared in the difference view
+    divergence_tolerance: f64,           // Divergence threshold relative to the peaks
+    plot: PlotConfig,                    // Appearance of exported plots
+    export_path: String,                 // File exports are saved to, with the extension swapped
+    export_message: Option<String>,      // Outcome of the last export
+    time_bounds: Option<([f64; 2], [f64; 2])>, // Visible window of the time-series plot
+    phase_bounds: Option<([f64; 2], [f64; 2])>, // Visible window of the phase plot
+    click_tool: ClickTool,               // What clicking the plots does
+    probe: Option<f64>,                  // Time of the probed sample
+    measured: Vec<f64>,                  // Times of up to two measured samples
+    window: Option<[f32; 4]>,            // Window position and size
+    session_dir: Option<PathBuf>,        // Directory the session is saved to on exit
+    zoom: Option<Zoom>,                  // Denser solution of the zoomed-in time window
+    zoom_request: Option<((f64, f64), f64)>, // Window and step of the next zoomed solve
+    zoom_job: Option<Job<Result<Zoom, String>>>, // Zoomed solve running on a worker thread
+    zoom_debouncer: Debouncer,           // Delays zoomed solves while the time plot is zoomed
+    stochastic_view: StochasticView,
+    ensemble_view: EnsembleView,
+    sweep_view: SweepView,
+    fitting_view: FittingView,
+    spatial_view: SpatialView,
+    agent_view: AgentView,
 }
 
 impl LotkaVolterraApp {
     /// Create a new app instance with the given parameters.
     pub fn new(params: LotkaVolterraParameters) -> Self {
-        let mut app = Self {
+        Self::from_scenario(Scenario {
             params,
+            ..Scenario::default()
+        })
+    }
+
+    /// Create a new app instance including harvesting, forcing and interventions.
+    pub fn from_scenario(scenario: Scenario) -> Self {
+        let mut app = Self {
+            view: View::Deterministic,
+            params: scenario.params,
+            terms: scenario.terms,
+            schedule: scenario.schedule,
+            delay: scenario.delay,
+            solved: None,
             prey_points: Vec::new(),
             predator_points: Vec::new(),
             phase_points: Vec::new(),
+            phase_starts: Vec::new(),
+            phase_orbits: Vec::new(),
+            overlay: true,
+            stochastic_prey: Vec::new(),
+            stochastic_predators: Vec::new(),
+            stochastic_phase: Vec::new(),
+            show_field: true,
+            show_nullclines: true,
             error_message: None,
-            t_start: 0.0,
-            t_end: 8000.0,
+            initial: scenario.params,
+            sliders: SLIDERS.map(|(name, _, range)| {
+                range.including(scenario.params.get(name).unwrap_or_default())
+            }),
+            job: None,
+            debouncer: Debouncer::new(SOLVE_DEBOUNCE),
+            history: History::new(HISTORY_CAPACITY),
+            snapshots: Vec::new(),
+            restoring: Vec::new(),
+            snapshot_name: String::new(),
+            pinned: 0,
+            compared: 0,
+            divergence_tolerance: 0.05,
+            plot: scenario.plot,
+            export_path: "lotka_volterra.png".to_string(),
+            export_message: None,
+            time_bounds: None,
+            phase_bounds: None,
+            click_tool: ClickTool::Probe,
+            probe: None,
+            measured: Vec::new(),
+            window: None,
+            session_dir: None,
+            zoom: None,
+            zoom_request: None,
+            zoom_job: None,
+            zoom_debouncer: Debouncer::new(SOLVE_DEBOUNCE),
+            stochastic_view: StochasticView::default(),
+            ensemble_view: EnsembleView::default(),
+            sweep_view: SweepView::default(),
+            fitting_view: FittingView::default(),
+            spatial_view: SpatialView::default(),
+            agent_view: AgentView::default(),
         };
 
         // Print the table **once** when the app starts
         app.print_parameter_table();
 
-        // Solve the system and initialize the plot data
-        app.solve_system();
+        // Start solving the system; the plots fill in when the solution is ready
+        app.start_solve();
         app
     }
 
+    /// Opens the app on the given view.
+    pub fn with_view(mut self, view: View) -> Self {
+        self.view = view;
+        self
+    }
+
+    /// Runs the spatial view on the given grid, diffusion and boundary settings.
+    pub fn with_spatial(mut self, spatial: SpatialParameters) -> Self {
+        self.spatial_view = SpatialView::new(spatial);
+        self
+    }
+
+    /// Runs the agents view on the given lattice.
+    pub fn with_lattice(mut self, lattice: AgentParameters) -> Self {
+        self.agent_view = AgentView::new(lattice);
+        self
+    }
+
+    /// Takes the window, slider ranges and snapshots of a saved session. The snapshots
+    /// are solved again on worker threads with the current harvesting, forcing,
+    /// interventions and delay, and each is added once its solve finishes.
+    pub fn with_session(mut self, session: &Session) -> Self {
+        self.window = session.window;
+        for (name, range) in &session.sliders {
+            if let Some(i) = SLIDERS.iter().position(|slider| slider.0 == name) {
+                self.sliders[i] = range.including(self.params.get(name).unwrap_or_default());
+            }
+        }
+        for saved in &session.snapshots {
+            let (params, terms, schedule, delay) = (
+                saved.params,
+                self.terms.clone(),
+                self.schedule.clone(),
+                self.delay,
+            );
+            let job = Job::spawn(move |progress| {
+                solve_in_background(params, &terms, &schedule, delay, &[], progress)
+            });
+            let color = ORBIT_COLORS[self.pinned % ORBIT_COLORS.len()];
+            self.pinned += 1;
+            self.restoring.push((saved.clone(), color, job));
+        }
+        self
+    }
+
+    /// Saves the session to `dir` when the app exits.
+    pub fn saving_to(mut self, dir: Option<PathBuf>) -> Self {
+        self.session_dir = dir;
+        self
+    }
+
+    /// The parameters, harvesting, forcing and interventions as a scenario.
+    fn scenario(&self) -> Scenario {
+        Scenario {
+            params: self.params,
+            terms: self.terms.clone(),
+            schedule: self.schedule.clone(),
+            delay: self.delay,
+            plot: self.plot.clone(),
+        }
+    }
+
+    /// The state kept between sessions.
+    fn session(&self) -> Session {
+        Session {
+            scenario: self.scenario(),
+            view: self.view,
+            window: self.window,
+            sliders: SLIDERS
+                .iter()
+                .zip(self.sliders)
+                .map(|(slider, range)| (slider.0.to_string(), range))
+                .collect(),
+            snapshots: self
+                .snapshots
+                .iter()
+                .map(|snapshot| SavedSnapshot {
+                    name: snapshot.name.clone(),
+                    params: snapshot.params,
+                    visible: snapshot.visible,
+                })
+                .chain(self.restoring.iter().map(|(saved, _, _)| saved.clone()))
+                .collect(),
+        }
+    }
+
+    /// Goes back to the default parameters without harvesting, forcing, interventions or
+    /// delay, with the default slider ranges and the first view. Snapshots are kept.
+    fn restore_defaults(&mut self) {
+        let params = LotkaVolterraParameters::default();
+        self.params = params;
+        self.terms = ExternalTerms::default();
+        self.schedule = Schedule::default();
+        self.delay = None;
+        self.sliders =
+            SLIDERS.map(|(name, _, range)| range.including(params.get(name).unwrap_or_default()));
+        self.view = View::Deterministic;
+        self.phase_starts.clear();
+        self.phase_orbits.clear();
+        self.probe = None;
+        self.measured.clear();
+        self.start_solve();
+    }
+
+    /// The parameters shared by all views.
+    fn current_params(&self) -> LotkaVolterraParameters {
+        self.params
+    }
+
     /// Displays the parameter table to the terminal once at startup.
     fn print_parameter_table(&self) {
-        use prettytable::{Cell, Row, Table};
-
-        let mut table = Table::new();
-
-        table.add_row(Row::new(vec![Cell::new(
-            "+------------------------------------------+--------+",
-        )]));
-        table.add_row(Row::new(vec![Cell::new(
-            "| Parameter                                | Value  |",
-        )]));
-        table.add_row(Row::new(vec![Cell::new(
-            "+------------------------------------------+--------+",
-        )]));
-        table.add_row(Row::new(vec![
-            Cell::new("| Alpha (Prey Birth Rate)                  |"),
-            Cell::new(&format!("{:.4}  |", self.params.alpha)),
-        ]));
-        table.add_row(Row::new(vec![
-            Cell::new("| Beta (Predation Rate)                     |"),
-            Cell::new(&format!("{:.4}  |", self.params.beta)),
-        ]));
-        table.add_row(Row::new(vec![
-            Cell::new("| Delta (Predator Reproduction Rate)        |"),
-            Cell::new(&format!("{:.4}  |", self.params.delta)),
-        ]));
-        table.add_row(Row::new(vec![
-            Cell::new("| Gamma (Predator Death Rate)               |"),
-            Cell::new(&format!("{:.4}  |", self.params.gamma)),
-        ]));
-        table.add_row(Row::new(vec![
-            Cell::new("| Initial Prey Population                   |"),
-            Cell::new(&format!("{:.2}  |", self.params.initial_prey)),
-        ]));
-        table.add_row(Row::new(vec![
-            Cell::new("| Initial Predator Population               |"),
-            Cell::new(&format!("{:.2}  |", self.params.initial_predator)),
-        ]));
-        table.add_row(Row::new(vec![
-            Cell::new("| t_start                                   |"),
-            Cell::new(&format!("{:.2}  |", self.t_start)),
-        ]));
-        table.add_row(Row::new(vec![
-            Cell::new("| t_end                                     |"),
-            Cell::new(&format!("{:.2}  |", self.t_end)),
-        ]));
-        table.add_row(Row::new(vec![Cell::new(
-            "+------------------------------------------+--------+",
-        )]));
-
-        // Print table once at startup
-        table.printstd();
-    }
-
-    /// Solve the Lotka-Volterra system and update the corresponding plot data.
-    fn solve_system(&mut self) {
-        let y0 = [self.params.initial_prey, self.params.initial_predator];
-
-        // Total steps based on time range
-        let num_steps = ((self.t_end - self.t_start) / 0.1) as u64;
-        let pb = ProgressBar::new(num_steps);
-        pb.set_style(
-            ProgressStyle::default_bar()
-                .template("{spinner:.green} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {pos}/{len} steps")
-                .unwrap()
-                .progress_chars("#>-"),
-        );
+        print!("{}", parameter_table(&self.current_params()));
+    }
 
-        match solve_lotka_volterra(self.params, y0, self.t_start, self.t_end, 0.1) {
-            Ok((times, prey, predators)) => {
-                self.prey_points.clear();
-                self.predator_points.clear();
-                self.phase_points.clear();
+    /// Schedules a solve once the inputs stop changing.
+    fn request_solve(&mut self) {
+        self.debouncer.trigger();
+    }
 
-                for (i, (time, prey_val, pred_val)) in
-                    itertools::izip!(times.iter(), prey.iter(), predators.iter()).enumerate()
-                {
-                    self.prey_points.push([*time, *prey_val]);
-                    self.predator_points.push([*time, *pred_val]);
-                    self.phase_points.push([*prey_val, *pred_val]);
+    /// Starts solving the system on a worker thread, cancelling any solve in progress.
+    fn start_solve(&mut self) {
+        let params = self.current_params();
+        let valid =
+            validate_params(&params).and_then(|()| validate_delay(self.delay, &self.schedule));
+        if let Err(e) = valid {
+            self.job = None;
+            self.error_message = Some(e.to_string());
+            return;
+        }
+        let terms = self.terms.clone();
+        let schedule = self.schedule.clone();
+        let delay = self.delay;
+        let starts = self.phase_starts.clone();
+        let overlay = self.overlay;
+        // The zoomed window belongs to the solution being replaced
+        self.zoom = None;
+        self.zoom_request = None;
+        self.zoom_job = None;
+        // Replacing the job drops, and so cancels, the stale one
+        self.job = Some(Job::spawn(move |progress| {
+            let mut solution =
+                solve_in_background(params, &terms, &schedule, delay, &starts, progress)?;
+            if overlay && !progress.is_cancelled() {
+                solution.stochastic = Some(simulate_overlay(&params, &schedule, progress)?);
+            }
+            Ok(solution)
+        }));
+    }
 
-                    if i as u64 % 10 == 0 {
-                        // Update every 10 steps
-                        pb.inc(10);
-                    }
+    /// Swaps in the result of the background solve if it has finished. Returns true
+    /// when a result was received.
+    fn poll_solution(&mut self) -> bool {
+        let Some(result) = self.job.as_ref().and_then(Job::try_result) else {
+            return false;
+        };
+        self.job = None;
+        match result {
+            Ok(solution) => {
+                self.solved = Some(solution.params);
+                self.prey_points = solution
+                    .times
+                    .iter()
+                    .zip(&solution.prey)
+                    .map(|(&t, &x)| [t, x])
+                    .collect();
+                self.predator_points = solution
+                    .times
+                    .iter()
+                    .zip(&solution.predators)
+                    .map(|(&t, &y)| [t, y])
+                    .collect();
+                self.phase_points = solution
+                    .prey
+                    .iter()
+                    .zip(&solution.predators)
+                    .map(|(&x, &y)| [x, y])
+                    .collect();
+                self.phase_orbits = solution.orbits;
+                self.history.record(HistoryEntry {
+                    params: solution.params,
+                    thumbnail: decimate(&self.phase_points, 200),
+                });
+                let (times, prey, predators) = solution.stochastic.unwrap_or_default();
+                self.stochastic_prey = times.iter().zip(&prey).map(|(&t, &x)| [t, x]).collect();
+                self.stochastic_predators = times
+                    .iter()
+                    .zip(&predators)
+                    .map(|(&t, &y)| [t, y])
+                    .collect();
+                self.stochastic_phase =
+                    prey.iter().zip(&predators).map(|(&x, &y)| [x, y]).collect();
+                self.error_message = None;
+            }
+            Err(e) => self.error_message = Some(e),
+        }
+        true
+    }
+
+    /// Parameters of the classic model whose direction field, nullclines and equilibria
+    /// describe the current model, or `None` when its vector field changes in time or
+    /// depends on the past.
+    fn field_params(&self) -> Option<LotkaVolterraParameters> {
+        match self.delay {
+            Some(_) => None,
+            None => autonomous_params(&self.params, &self.terms, &self.schedule),
+        }
+    }
+
+    /// Notes which of the harvesting, forcing, interventions and maturation delay of the run
+    /// `model`, the model of the current view, leaves out. The flags tell whether it
+    /// includes harvesting and forcing, interventions and the delay.
+    fn left_out_notice(
+        &self,
+        ui: &mut egui::Ui,
+        model: &str,
+        terms: bool,
+        interventions: bool,
+        delay: bool,
+    ) {
+        let left_out: Vec<&str> = [
+            (!terms && !self.terms.harvests.is_empty(), "harvesting"),
+            (!terms && !self.terms.forcings.is_empty(), "forcing"),
+            (
+                !interventions && !self.schedule.interventions.is_empty(),
+                "interventions",
+            ),
+            (!delay && self.delay.is_some(), "maturation delay"),
+        ]
+        .into_iter()
+        .filter_map(|(left_out, name)| left_out.then_some(name))
+        .collect();
+        if let Some((last, rest)) = left_out.split_last() {
+            let list = match rest {
+                [] => last.to_string(),
+                rest => format!("{} or {}", rest.join(", "), last),
+            };
+            ui.label(format!(
+                "The {} has no {}, so this view leaves them out.",
+                model, list
+            ));
+        }
+    }
+
+    /// Solves the orbits from the clicked initial conditions.
+    fn solve_phase_orbits(&mut self) {
+        match phase_orbits(
+            &self.current_params(),
+            &self.terms,
+            &self.schedule,
+            self.delay,
+            &self.phase_starts,
+            1.0,
+        ) {
+            Ok(orbits) => self.phase_orbits = orbits,
+            Err(e) => self.error_message = Some(format!("Error solving orbits: {}", e)),
+        }
+    }
+}
+
+impl LotkaVolterraApp {
+    /// Makes a parameter set from the history current and solves it.
+    fn apply_history(&mut self, entry: Option<&HistoryEntry>) {
+        if let Some(entry) = entry {
+            self.params = entry.params;
+            self.start_solve();
+        }
+    }
+
+    fn undo(&mut self) {
+        let entry = self.history.undo().cloned();
+        self.apply_history(entry.as_ref());
+    }
+
+    fn redo(&mut self) {
+        let entry = self.history.redo().cloned();
+        self.apply_history(entry.as_ref());
+    }
+
+    /// Undo and redo buttons, and the past parameter sets, newest first, with thumbnails
+    /// of their phase plots. Clicking one restores it.
+    fn history_panel(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(self.history.can_undo(), egui::Button::new("Undo"))
+                .on_hover_text(ui.ctx().format_shortcut(&UNDO))
+                .clicked()
+            {
+                self.undo();
+            }
+            if ui
+                .add_enabled(self.history.can_redo(), egui::Button::new("Redo"))
+                .on_hover_text(ui.ctx().format_shortcut(&REDO))
+                .clicked()
+            {
+                self.redo();
+            }
+        });
+        let mut restored = None;
+        for (i, entry) in self.history.entries().iter().enumerate().rev() {
+            let current = i == self.history.cursor();
+            let p = &entry.params;
+            ui.horizontal(|ui| {
+                let clicked = thumbnail(ui, &entry.thumbnail, current).clicked();
+                let text = format!(
+                    "α {:.4}  β {:.2e}\nδ {:.2e}  γ {:.4}\nx0 {:.0}  y0 {:.0}  t {:.0}-{:.0}",
+                    p.alpha,
+                    p.beta,
+                    p.delta,
+                    p.gamma,
+                    p.initial_prey,
+                    p.initial_predator,
+                    p.t_start,
+                    p.t_end
+                );
+                if clicked || ui.selectable_label(current, text).clicked() {
+                    restored = Some(i);
                 }
+            });
+        }
+        if let Some(i) = restored {
+            let entry = self.history.restore(i).cloned();
+            self.apply_history(entry.as_ref());
+        }
+    }
 
-                pb.finish_with_message("Simulation Complete!");
-                self.error_message = None;
+    /// Pins the run shown as a named snapshot, with the parameters it was solved for
+    /// rather than any changed since.
+    fn pin_snapshot(&mut self) {
+        let Some(params) = self.solved else {
+            return;
+        };
+        self.pinned += 1;
+        let name = match self.snapshot_name.trim() {
+            "" => format!("Snapshot {}", self.pinned),
+            name => name.to_string(),
+        };
+        self.snapshots.push(Snapshot {
+            name,
+            params,
+            times: self.prey_points.iter().map(|p| p[0]).collect(),
+            prey: self.prey_points.iter().map(|p| p[1]).collect(),
+            predators: self.predator_points.iter().map(|p| p[1]).collect(),
+            color: ORBIT_COLORS[(self.pinned - 1) % ORBIT_COLORS.len()],
+            visible: true,
+        });
+        self.snapshot_name.clear();
+    }
+
+    /// Saves the current plot, trajectory or parameters to the export path with the
+    /// extension of `kind`, returning the file written.
+    fn export(&self, kind: Export) -> Result<PathBuf, Box<dyn Error>> {
+        let path = Path::new(self.export_path.trim()).with_extension(kind.extension());
+        let file = path.to_string_lossy();
+        let times: Vec<f64> = self.prey_points.iter().map(|p| p[0]).collect();
+        let prey: Vec<f64> = self.prey_points.iter().map(|p| p[1]).collect();
+        let predators: Vec<f64> = self.predator_points.iter().map(|p| p[1]).collect();
+        match kind {
+            Export::PlotPng | Export::PlotSvg => {
+                let format = PlotFormat::from_file_name(&file).unwrap_or_default();
+                if self.view == View::PhasePortrait {
+                    let (min, max) = self.phase_bounds.ok_or("The phase plot is not shown")?;
+                    let mut orbits = vec![self.phase_points.clone()];
+                    orbits.extend(self.phase_orbits.iter().cloned());
+                    let field = self.field_params();
+                    plot_phase_portrait_window(field.as_ref(), &orbits, min, max, &file)?;
+                } else {
+                    let (min, max) = self.time_bounds.ok_or("The time series is not shown")?;
+                    let config = PlotConfig {
+                        format,
+                        x_range: Some((min[0], max[0])),
+                        y_range: (!self.plot.log_y).then_some((min[1], max[1])),
+                        ..self.plot.clone()
+                    };
+                    let harvests = &self.terms.harvests;
+                    plot_time_series(&times, &prey, &predators, harvests, &config, &file)?;
+                }
+            }
+            Export::Csv => std::fs::write(&path, trajectory_csv(&times, &prey, &predators))?,
+            Export::Json => std::fs::write(
+                &path,
+                trajectory_json(&self.params, &times, &prey, &predators),
+            )?,
+            Export::Scenario => self.scenario().save(&path)?,
+        }
+        Ok(path)
+    }
+
+    /// Path field and buttons saving the plot as shown, the trajectory and the parameters.
+    fn export_panel(&mut self, ui: &mut egui::Ui) {
+        ui.add(
+            egui::TextEdit::singleline(&mut self.export_path)
+                .hint_text("File path")
+                .desired_width(200.0),
+        );
+        let plotted = matches!(self.view, View::Deterministic | View::PhasePortrait);
+        let solved = !self.prey_points.is_empty();
+        let mut kind = None;
+        ui.horizontal(|ui| {
+            ui.label("Plot:");
+            for (export, text) in [(Export::PlotPng, "PNG"), (Export::PlotSvg, "SVG")] {
+                if ui
+                    .add_enabled(plotted && solved, egui::Button::new(text))
+                    .on_disabled_hover_text(
+                        "Plots export from the Deterministic and Phase Portrait views",
+                    )
+                    .clicked()
+                {
+                    kind = Some(export);
+                }
             }
-            Err(e) => {
-                pb.finish_with_message("Error during simulation");
-                self.error_message = Some(format!("Error solving equations: {}", e));
+        });
+        ui.horizontal(|ui| {
+            ui.label("Data:");
+            for (export, text) in [(Export::Csv, "CSV"), (Export::Json, "JSON")] {
+                if ui.add_enabled(solved, egui::Button::new(text)).clicked() {
+                    kind = Some(export);
+                }
+            }
+            if ui.button("Scenario").clicked() {
+                kind = Some(Export::Scenario);
+            }
+        });
+        if let Some(kind) = kind {
+            self.export_message = Some(match self.export(kind) {
+                Ok(path) => format!("Saved {}", path.display()),
+                Err(e) => format!("Export failed: {}", e),
+            });
+        }
+        if let Some(message) = &self.export_message {
+            ui.label(message);
+        }
+    }
+
+    /// Pins the current run and lists the snapshots with visibility toggles.
+    fn snapshot_editor(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.snapshot_name)
+                    .hint_text("Name")
+                    .desired_width(120.0),
+            );
+            if ui
+                .add_enabled(self.solved.is_some(), egui::Button::new("Pin current run"))
+                .clicked()
+            {
+                self.pin_snapshot();
             }
+        });
+        let mut removed = None;
+        for (i, snapshot) in self.snapshots.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut snapshot.visible, "");
+                ui.colored_label(snapshot.color, "■");
+                ui.add(egui::TextEdit::singleline(&mut snapshot.name).desired_width(120.0))
+                    .on_hover_text(parameter_table(&snapshot.params));
+                if ui.button("Remove").clicked() {
+                    removed = Some(i);
+                }
+            });
+        }
+        if let Some(i) = removed {
+            self.snapshots.remove(i);
         }
     }
 }
 
-impl eframe::App for LotkaVolterraApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        egui::CentralPanel::default().show(ctx, |ui| {
-            // Centered main title
-            ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
-                ui.heading("Lotka-Volterra Predator-Prey Model");
+/// Draws a small phase plot of `points`, framed in the selection color when `current`.
+fn thumbnail(ui: &mut egui::Ui, points: &[[f64; 2]], current: bool) -> egui::Response {
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(64.0, 48.0), egui::Sense::click());
+    let visuals = ui.visuals();
+    let frame = if current {
+        egui::Stroke::new(2.0, visuals.selection.bg_fill)
+    } else {
+        visuals.widgets.noninteractive.bg_stroke
+    };
+    let painter = ui.painter();
+    painter.rect_stroke(rect, 2.0, frame, egui::StrokeKind::Inside);
+
+    let (mut min, mut max) = ([f64::INFINITY; 2], [f64::NEG_INFINITY; 2]);
+    for p in points {
+        for k in 0..2 {
+            min[k] = min[k].min(p[k]);
+            max[k] = max[k].max(p[k]);
+        }
+    }
+    let inner = rect.shrink(4.0);
+    let scale = |v: f64, k: usize| ((v - min[k]) / (max[k] - min[k]).max(1e-12)) as f32;
+    let line: Vec<egui::Pos2> = points
+        .iter()
+        .map(|p| {
+            egui::pos2(
+                inner.left() + inner.width() * scale(p[0], 0),
+                inner.bottom() - inner.height() * scale(p[1], 1),
+            )
+        })
+        .collect();
+    painter.add(egui::Shape::line(
+        line,
+        egui::Stroke::new(1.0, visuals.text_color()),
+    ));
+    response
+}
+
+/// Solves the model, with the maturation delay if one is given, and the orbits from the
+/// clicked initial conditions. Runs on a worker thread and stops early when cancelled.
+fn solve_in_background(
+    params: LotkaVolterraParameters,
+    terms: &ExternalTerms,
+    schedule: &Schedule,
+    delay: Option<f64>,
+    starts: &[[f64; 2]],
+    progress: &Progress,
+) -> Result<Solution, String> {
+    let (times, prey, predators) = match delay {
+        Some(tau) => {
+            let mut system = DelayLotkaVolterraSystem::new(params, tau);
+            system.terms = terms.clone();
+            solve_delay_with_progress(&system, output_step(&params), progress)
+                .map_err(|e| format!("Error solving equations: {}", e))?
+        }
+        None => solve_with_progress(params, terms, schedule, output_step(&params), progress)
+            .map_err(|e| format!("Error solving equations: {}", e))?,
+    };
+    if progress.is_cancelled() {
+        return Err(SimulationError::UserCancelled.to_string());
+    }
+    let orbits = phase_orbits(&params, terms, schedule, delay, starts, 1.0)
+        .map_err(|e| format!("Error solving orbits: {}", e))?;
+    progress.set_fraction(1.0);
+    Ok(Solution {
+        params,
+        times,
+        prey,
+        predators,
+        orbits,
+        stochastic: None,
+    })
+}
+
+/// Simulates one realisation with the Gillespie algorithm and the scheduled
+/// interventions, recorded at about 2000 times, to overlay on the deterministic solution.
+/// Stops early when cancelled.
+fn simulate_overlay(
+    params: &LotkaVolterraParameters,
+    schedule: &Schedule,
+    progress: &Progress,
+) -> Result<Realisation, String> {
+    let step = ((params.t_end - params.t_start) / 2000.0).max(1e-3);
+    let (times, states) =
+        simulate_gillespie_with_interventions(params, schedule, step, &mut rand::rng(), progress)
+            .map_err(|e| format!("Error simulating overlay: {}", e))?;
+    let (prey, predators) = states.iter().map(|s| (s[0], s[1])).unzip();
+    Ok((times, prey, predators))
+}
+
+/// Parameters edited in the side panel: name, label and default slider range. The tiny
+/// interaction rates have logarithmic sliders.
+const SLIDERS: [(&str, &str, SliderRange); 8] = [
+    ("initial_prey", "Prey", SliderRange::linear(0.0, 2000.0)),
+    (
+        "initial_predator",
+        "Predator",
+        SliderRange::linear(0.0, 2000.0),
+    ),
+    ("t_start", "Start Time", SliderRange::linear(0.0, 8000.0)),
+    ("t_end", "Time End", SliderRange::linear(0.0, 8000.0)),
+    (
+        "alpha",
+        "Alpha (Prey Birth Rate)",
+        SliderRange::linear(0.0, 0.01),
+    ),
+    (
+        "beta",
+        "Beta (Prey Death Rate)",
+        SliderRange::log(1e-8, 1e-4),
+    ),
+    (
+        "delta",
+        "Delta (Predator Birth Rate)",
+        SliderRange::log(1e-8, 1e-4),
+    ),
+    (
+        "gamma",
+        "Gamma (Predator Death Rate)",
+        SliderRange::linear(0.0, 0.01),
+    ),
+];
+
+/// Colors of the orbits added by clicking the phase plot, reused in turn.
+const ORBIT_COLORS: [egui::Color32; 6] = [
+    egui::Color32::from_rgb(230, 159, 0),
+    egui::Color32::from_rgb(0, 158, 115),
+    egui::Color32::from_rgb(204, 121, 167),
+    egui::Color32::from_rgb(86, 180, 233),
+    egui::Color32::from_rgb(213, 94, 0),
+    egui::Color32::from_rgb(120, 94, 240),
+];
+
+impl LotkaVolterraApp {
+    /// Editor for the maturation delay, harvests and forcings. Returns true if anything
+    /// changed.
+    fn terms_editor(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+        let t_end = self.params.t_end;
+
+        let mut delayed = self.delay.is_some();
+        ui.horizontal(|ui| {
+            changed |= ui.checkbox(&mut delayed, "Maturation delay").changed();
+            let mut tau = self.delay.unwrap_or(50.0);
+            if delayed {
+                changed |= ui
+                    .add(egui::Slider::new(&mut tau, 0.0..=500.0).text("Tau"))
+                    .changed();
+            }
+            self.delay = delayed.then_some(tau);
+        });
+
+        ui.add_space(5.0);
+        ui.label("Harvests:");
+        let mut removed = None;
+        for (i, harvest) in self.terms.harvests.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_salt(("harvest_species", i))
+                    .selected_text(format!("{:?}", harvest.species))
+                    .show_ui(ui, |ui| {
+                        for species in [Species::Prey, Species::Predator] {
+                            changed |= ui
+                                .selectable_value(
+                                    &mut harvest.species,
+                                    species,
+                                    format!("{:?}", species),
+                                )
+                                .changed();
+                        }
+                    });
+
+                let (mut quota, mut rate) = match harvest.strategy {
+                    HarvestStrategy::ConstantEffort(e) => (false, e),
+                    HarvestStrategy::ConstantQuota(q) => (true, q),
+                };
+                changed |= ui.checkbox(&mut quota, "Quota").changed();
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut rate)
+                            .speed(if quota { 0.1 } else { 0.0001 })
+                            .range(0.0..=f64::INFINITY)
+                            .prefix("rate: "),
+                    )
+                    .changed();
+                harvest.strategy = if quota {
+                    HarvestStrategy::ConstantQuota(rate)
+                } else {
+                    HarvestStrategy::ConstantEffort(rate)
+                };
+
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut harvest.start)
+                            .range(0.0..=harvest.end)
+                            .prefix("from: "),
+                    )
+                    .changed();
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut harvest.end)
+                            .range(harvest.start..=f64::INFINITY)
+                            .prefix("to: "),
+                    )
+                    .changed();
+                if ui.button("Remove").clicked() {
+                    removed = Some(i);
+                }
             });
-            ui.add_space(10.0);
+        }
+        if let Some(i) = removed {
+            self.terms.harvests.remove(i);
+            changed = true;
+        }
+        if ui.button("Add harvest").clicked() {
+            self.terms.harvests.push(Harvest {
+                species: Species::Prey,
+                strategy: HarvestStrategy::ConstantEffort(0.001),
+                start: 0.0,
+                end: t_end,
+            });
+            changed = true;
+        }
 
-            // Display error message if something goes wrong
-            if let Some(ref msg) = self.error_message {
-                ui.colored_label(egui::Color32::RED, msg);
+        ui.add_space(5.0);
+        ui.label("Forcings:");
+        let mut removed = None;
+        for (i, forcing) in self.terms.forcings.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_salt(("forcing_rate", i))
+                    .selected_text(forcing.rate.name())
+                    .show_ui(ui, |ui| {
+                        for rate in Rate::ALL {
+                            changed |= ui
+                                .selectable_value(&mut forcing.rate, rate, rate.name())
+                                .changed();
+                        }
+                    });
+                changed |= ui
+                    .add(egui::Slider::new(&mut forcing.amplitude, 0.0..=1.0).text("Amplitude"))
+                    .changed();
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut forcing.period)
+                            .range(1.0..=f64::INFINITY)
+                            .prefix("period: "),
+                    )
+                    .changed();
+                changed |= ui
+                    .add(
+                        egui::Slider::new(&mut forcing.phase, 0.0..=std::f64::consts::TAU)
+                            .text("Phase"),
+                    )
+                    .changed();
+                if ui.button("Remove").clicked() {
+                    removed = Some(i);
+                }
+            });
+        }
+        if let Some(i) = removed {
+            self.terms.forcings.remove(i);
+            changed = true;
+        }
+        if ui.button("Add forcing").clicked() {
+            self.terms.forcings.push(Forcing {
+                rate: Rate::Alpha,
+                amplitude: 0.2,
+                period: 365.0,
+                phase: 0.0,
+            });
+            changed = true;
+        }
+
+        changed
+    }
+}
+
+/// Shaded band marking the interval in which a harvest is active.
+fn harvest_band(harvest: &Harvest, min_pop: f64, max_pop: f64) -> Polygon<'static> {
+    let color = match harvest.species {
+        Species::Prey => egui::Color32::from_rgba_unmultiplied(0, 120, 255, 30),
+        Species::Predator => egui::Color32::from_rgba_unmultiplied(255, 60, 0, 30),
+    };
+    Polygon::new(PlotPoints::new(vec![
+        [harvest.start, min_pop],
+        [harvest.end, min_pop],
+        [harvest.end, max_pop],
+        [harvest.start, max_pop],
+    ]))
+    .fill_color(color)
+    .stroke(egui::Stroke::NONE)
+}
+
+impl LotkaVolterraApp {
+    /// Sliders with numeric entry for the shared parameters, and an editor for the slider
+    /// ranges. Returns true if any parameter changed.
+    fn parameter_editor(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+        for (i, &(name, label, _)) in SLIDERS.iter().enumerate() {
+            if i == 0 {
+                ui.label("Initial Conditions:");
+            } else if i == 4 {
                 ui.add_space(10.0);
+                ui.label("Model Parameters:");
             }
+            let range = self.sliders[i];
+            let mut value = self.params.get(name).unwrap_or_default();
+            ui.label(label);
+            ui.horizontal(|ui| {
+                changed |= ui
+                    .add(
+                        egui::Slider::new(&mut value, range.min..=range.max)
+                            .logarithmic(range.logarithmic)
+                            .smallest_positive(range.smallest_positive())
+                            .show_value(false),
+                    )
+                    .changed();
+                let drag = egui::DragValue::new(&mut value)
+                    .speed((range.max - range.min) / 1000.0)
+                    .range(0.0..=f64::INFINITY);
+                let drag = if range.logarithmic {
+                    drag.custom_formatter(|v, _| format!("{:.4e}", v))
+                } else {
+                    drag
+                };
+                changed |= ui.add(drag).changed();
+                let default = self.initial.get(name).unwrap_or_default();
+                if ui
+                    .add_enabled(value != default, egui::Button::new("Reset"))
+                    .on_hover_text(format!("Back to the launch value {}", default))
+                    .clicked()
+                {
+                    value = default;
+                    changed = true;
+                }
+            });
+            // Values typed in outside the slider widen its range
+            self.sliders[i] = range.including(value);
+            self.params.set(name, value).ok();
+        }
 
-            // Use two columns: one for Phase Plot, one for Population Over Time
-            ui.columns(2, |columns| {
-                // Phase Plot (Predator vs Prey)
-                columns[0].vertical(|ui| {
-                    ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
-                        ui.label(egui::RichText::new("Predator vs. Prey (Phase Plot)").size(14.0)); // Smaller font
+        ui.add_space(10.0);
+        egui::CollapsingHeader::new("Slider Ranges")
+            .default_open(false)
+            .show(ui, |ui| {
+                egui::Grid::new("slider_ranges").show(ui, |ui| {
+                    ui.label("Parameter");
+                    ui.label("Min");
+                    ui.label("Max");
+                    ui.label("Log");
+                    ui.end_row();
+                    for (range, &(name, _, _)) in self.sliders.iter_mut().zip(&SLIDERS) {
+                        let speed = (range.max - range.min) / 1000.0;
+                        ui.label(name);
+                        ui.add(
+                            egui::DragValue::new(&mut range.min)
+                                .speed(speed)
+                                .range(0.0..=range.max),
+                        );
+                        ui.add(
+                            egui::DragValue::new(&mut range.max)
+                                .speed(speed)
+                                .range(range.min..=f64::INFINITY),
+                        );
+                        ui.checkbox(&mut range.logarithmic, "");
+                        ui.end_row();
+                    }
+                });
+                if ui.button("Reset ranges").clicked() {
+                    let params = self.params;
+                    self.sliders = SLIDERS.map(|(name, _, range)| {
+                        range.including(params.get(name).unwrap_or_default())
                     });
+                }
+            });
+        changed
+    }
+
+    /// Populations over time, with the stochastic realisation overlaid on the same axes.
+    fn deterministic_view(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Delay, Harvesting & Forcing")
+            .default_open(false)
+            .show(ui, |ui| {
+                if self.terms_editor(ui) {
+                    self.request_solve();
+                }
+            });
+        ui.add_space(5.0);
 
-                    ui.add_space(5.0);
-
-                    if !self.phase_points.is_empty() {
-                        let min_x = self
-                            .phase_points
-                            .iter()
-                            .map(|p| p[0])
-                            .fold(f64::INFINITY, f64::min);
-                        let max_x = self
-                            .phase_points
-                            .iter()
-                            .map(|p| p[0])
-                            .fold(f64::NEG_INFINITY, f64::max);
-                        let min_y = self
-                            .phase_points
-                            .iter()
-                            .map(|p| p[1])
-                            .fold(f64::INFINITY, f64::min);
-                        let max_y = self
-                            .phase_points
-                            .iter()
-                            .map(|p| p[1])
-                            .fold(f64::NEG_INFINITY, f64::max);
-
-                        let phase_line = Line::new(PlotPoints::from_iter(
-                            self.phase_points.iter().map(|&[x, y]| [x, y]),
-                        ))
-                        .name("Phase Plot");
-
-                        Plot::new("phase_plot")
-                            .view_aspect(1.0)
-                            .legend(Legend::default().position(Corner::LeftTop))
-                            .include_x(min_x)
-                            .include_x(max_x)
-                            .include_y(min_y)
-                            .include_y(max_y)
-                            .x_axis_label("Prey Population")
-                            .y_axis_label("Predator Population")
-                            .show(ui, |plot_ui| {
-                                plot_ui.line(phase_line);
+        if self.prey_points.is_empty() || self.predator_points.is_empty() {
+            ui.label("No data available for population over time.");
+            return;
+        }
+        let min_time = self
+            .prey_points
+            .iter()
+            .map(|p| p[0])
+            .fold(f64::INFINITY, f64::min);
+        let max_time = self
+            .prey_points
+            .iter()
+            .map(|p| p[0])
+            .fold(f64::NEG_INFINITY, f64::max);
+        let min_pop = self
+            .prey_points
+            .iter()
+            .map(|p| p[1])
+            .chain(self.predator_points.iter().map(|p| p[1]))
+            .fold(f64::INFINITY, f64::min);
+        let max_pop = self
+            .prey_points
+            .iter()
+            .map(|p| p[1])
+            .chain(self.predator_points.iter().map(|p| p[1]))
+            .fold(f64::NEG_INFINITY, f64::max);
+
+        // Only the visible part is drawn, with the zoomed window solved more densely
+        let visible = self.time_bounds.map(|(min, max)| (min[0], max[0]));
+        let (dense_prey, dense_predators) = match &self.zoom {
+            Some(zoom) => (&zoom.prey[..], &zoom.predators[..]),
+            None => (&[][..], &[][..]),
+        };
+        let prey = plotted(&self.prey_points, dense_prey, visible);
+        let predators = plotted(&self.predator_points, dense_predators, visible);
+        let prey_line = Line::new(PlotPoints::from(prey))
+            .color(egui::Color32::BLUE)
+            .name("Prey Population");
+        let predator_line = Line::new(PlotPoints::from(predators))
+            .color(egui::Color32::RED)
+            .name("Predator Population");
+
+        self.probe_tools(ui, false);
+        if self.zoom_job.is_some() {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("Solving the zoomed window at a finer step...");
+            });
+        }
+
+        // A small phase plot shares the row, so that a marker placed on either plot is seen
+        // on both
+        let size = ui.available_size();
+        let phase_size = egui::Vec2::splat((size.x * 0.3).min(size.y));
+        let time_width = size.x - phase_size.x - ui.spacing().item_spacing.x;
+        let (prey_points, predator_points) = (&self.prey_points, &self.predator_points);
+        let ((clicked, bounds), phase_clicked) = ui
+            .horizontal(|ui| {
+                let time = Plot::new("populations_over_time")
+                    .width(time_width)
+                    .height(size.y)
+                    .legend(Legend::default().position(Corner::RightTop))
+                    .label_formatter(|name, value| match name {
+                        // The current run reads out both populations at the hovered time
+                        "" | "Prey Population" | "Predator Population" => {
+                            nearest_sample(prey_points, value.x)
+                                .map(|i| sample_readout(prey_points, predator_points, i))
+                                .unwrap_or_default()
+                        }
+                        name => format!("{}\nt = {:.2}\nvalue = {:.2}", name, value.x, value.y),
+                    })
+                    .include_x(min_time)
+                    .include_x(max_time)
+                    .include_y(min_pop)
+                    .include_y(max_pop)
+                    .x_axis_label("Time")
+                    .y_axis_label("Population")
+                    .show(ui, |plot_ui| {
+                        // Only the first band of each species is named, so that the legend
+                        // lists it once
+                        let harvests = &self.terms.harvests;
+                        for (i, harvest) in harvests.iter().enumerate() {
+                            let band = harvest_band(harvest, min_pop, max_pop);
+                            let first = !harvests[..i].iter().any(|h| h.species == harvest.species);
+                            plot_ui.polygon(if first {
+                                band.name(format!("{:?} Harvest", harvest.species))
+                            } else {
+                                band
+                            });
+                        }
+                        // Only the first marker is named so that the legend lists it once
+                        let times = self.schedule.times(self.params.t_start, self.params.t_end);
+                        for (i, time) in times.into_iter().enumerate() {
+                            let line = VLine::new(time)
+                                .color(egui::Color32::GRAY)
+                                .style(egui_plot::LineStyle::dashed_dense());
+                            plot_ui.vline(if i == 0 {
+                                line.name("Intervention")
+                            } else {
+                                line
                             });
-                    } else {
-                        ui.label("No data available for phase plot.");
+                        }
+                        if self.overlay {
+                            plot_ui.line(
+                                Line::new(PlotPoints::from(self.stochastic_prey.clone()))
+                                    .color(egui::Color32::from_rgb(100, 160, 255))
+                                    .name("Stochastic Prey"),
+                            );
+                            plot_ui.line(
+                                Line::new(PlotPoints::from(self.stochastic_predators.clone()))
+                                    .color(egui::Color32::from_rgb(255, 140, 100))
+                                    .name("Stochastic Predators"),
+                            );
+                        }
+                        for snapshot in self.snapshots.iter().filter(|s| s.visible) {
+                            plot_ui.line(
+                                Line::new(snapshot.series(&snapshot.prey))
+                                    .color(snapshot.color)
+                                    .name(format!("{} Prey", snapshot.name)),
+                            );
+                            plot_ui.line(
+                                Line::new(snapshot.series(&snapshot.predators))
+                                    .color(snapshot.color)
+                                    .style(egui_plot::LineStyle::dashed_loose())
+                                    .name(format!("{} Predators", snapshot.name)),
+                            );
+                        }
+                        plot_ui.line(prey_line);
+                        plot_ui.line(predator_line);
+                        for (times, color, name) in self.markers() {
+                            for t in times {
+                                plot_ui.vline(VLine::new(t).color(color).name(name));
+                                if let Some(i) = nearest_sample(prey_points, t) {
+                                    plot_ui.points(
+                                        Points::new(vec![prey_points[i], predator_points[i]])
+                                            .radius(5.0)
+                                            .color(color)
+                                            .name(name),
+                                    );
+                                }
+                            }
+                        }
+
+                        let clicked = if plot_ui.response().clicked() {
+                            plot_ui.pointer_coordinate()
+                        } else {
+                            None
+                        };
+                        let bounds = plot_ui.plot_bounds();
+                        (clicked, (bounds.min(), bounds.max()))
+                    })
+                    .inner;
+                (time, self.linked_phase_plot(ui, phase_size))
+            })
+            .inner;
+        // Draws again with the points of a window that changed since this frame started
+        if visible != Some((bounds.0[0], bounds.1[0])) {
+            ui.ctx().request_repaint();
+        }
+        self.time_bounds = Some(bounds);
+        self.request_zoom((bounds.0[0], bounds.1[0]));
+
+        let nearest = clicked.and_then(|point| nearest_sample(&self.prey_points, point.x));
+        if let Some(i) = nearest.or(phase_clicked) {
+            self.probe_click(self.prey_points[i][0]);
+        }
+    }
+
+    /// Small phase plot beside the time plot, with the probe and measurement markers.
+    /// Returns the sample nearest a click on it.
+    fn linked_phase_plot(&self, ui: &mut egui::Ui, size: egui::Vec2) -> Option<usize> {
+        let (prey_points, predator_points) = (&self.prey_points, &self.predator_points);
+        let phase_points = &self.phase_points;
+        let extent = |axis: usize| {
+            let values = phase_points.iter().map(|p| p[axis]);
+            values.clone().fold(f64::NEG_INFINITY, f64::max) - values.fold(f64::INFINITY, f64::min)
+        };
+        let scale = [extent(0), extent(1)];
+        let clicked = Plot::new("linked_phase_plot")
+            .width(size.x)
+            .height(size.y)
+            .label_formatter(move |name, value| match name {
+                "Phase Plot" => nearest_point(phase_points, [value.x, value.y], scale)
+                    .map(|i| sample_readout(prey_points, predator_points, i))
+                    .unwrap_or_default(),
+                _ => format!("prey = {:.2}\npredators = {:.2}", value.x, value.y),
+            })
+            .x_axis_label("Prey")
+            .y_axis_label("Predators")
+            .show(ui, |plot_ui| {
+                plot_ui.line(Line::new(PlotPoints::from(phase_points.clone())).name("Phase Plot"));
+                self.phase_markers(plot_ui);
+                if plot_ui.response().clicked() {
+                    plot_ui.pointer_coordinate()
+                } else {
+                    None
+                }
+            })
+            .inner;
+        clicked.and_then(|point| nearest_point(phase_points, [point.x, point.y], scale))
+    }
+
+    /// Draws the probe and measurement markers at their samples on a phase plot.
+    fn phase_markers(&self, plot_ui: &mut egui_plot::PlotUi<'_>) {
+        for (times, color, name) in self.markers() {
+            let points: Vec<[f64; 2]> = times
+                .into_iter()
+                .filter_map(|t| nearest_sample(&self.prey_points, t))
+                .map(|i| self.phase_points[i])
+                .collect();
+            plot_ui.points(Points::new(points).radius(6.0).color(color).name(name));
+        }
+    }
+
+    /// Phase plot with the direction field, nullclines, clicked orbits and the stochastic
+    /// realisation.
+    fn phase_portrait_view(&mut self, ui: &mut egui::Ui) {
+        let field = self.field_params();
+        ui.horizontal(|ui| {
+            ui.add_enabled_ui(field.is_some(), |ui| {
+                ui.checkbox(&mut self.show_field, "Direction field");
+                ui.checkbox(&mut self.show_nullclines, "Nullclines");
+            });
+            if ui.button("Clear orbits").clicked() {
+                self.phase_starts.clear();
+                self.phase_orbits.clear();
+            }
+        });
+        if field.is_none() {
+            ui.label(
+                "The direction field, nullclines and equilibria of the unforced model are \
+                 hidden, as harvesting, forcing, interventions or the delay change the flow \
+                 over time.",
+            );
+        }
+        self.probe_tools(ui, true);
+
+        if self.phase_points.is_empty() {
+            ui.label("No data available for phase plot.");
+            return;
+        }
+        let min_x = self
+            .phase_points
+            .iter()
+            .map(|p| p[0])
+            .fold(f64::INFINITY, f64::min);
+        let max_x = self
+            .phase_points
+            .iter()
+            .map(|p| p[0])
+            .fold(f64::NEG_INFINITY, f64::max);
+        let min_y = self
+            .phase_points
+            .iter()
+            .map(|p| p[1])
+            .fold(f64::INFINITY, f64::min);
+        let max_y = self
+            .phase_points
+            .iter()
+            .map(|p| p[1])
+            .fold(f64::NEG_INFINITY, f64::max);
+
+        let phase_line = Line::new(PlotPoints::from_iter(
+            self.phase_points.iter().map(|&[x, y]| [x, y]),
+        ))
+        .name("Phase Plot");
+
+        let scale = [max_x - min_x, max_y - min_y];
+        let (prey_points, predator_points) = (&self.prey_points, &self.predator_points);
+        let phase_points = &self.phase_points;
+        let (clicked, bounds) = Plot::new("phase_plot")
+            .legend(Legend::default().position(Corner::LeftTop))
+            .label_formatter(move |name, value| match name {
+                "Phase Plot" => nearest_point(phase_points, [value.x, value.y], scale)
+                    .map(|i| sample_readout(prey_points, predator_points, i))
+                    .unwrap_or_default(),
+                "" => format!("prey = {:.2}\npredators = {:.2}", value.x, value.y),
+                name => format!(
+                    "{}\nprey = {:.2}\npredators = {:.2}",
+                    name, value.x, value.y
+                ),
+            })
+            .include_x(min_x)
+            .include_x(max_x)
+            .include_y(min_y)
+            .include_y(max_y)
+            .x_axis_label("Prey Population")
+            .y_axis_label("Predator Population")
+            .show(ui, |plot_ui| {
+                let bounds = plot_ui.plot_bounds();
+                if let Some(params) = field.as_ref().filter(|_| self.show_field) {
+                    let (origins, tips): (Vec<_>, Vec<_>) =
+                        direction_field(params, bounds.min(), bounds.max(), 20)
+                            .into_iter()
+                            .unzip();
+                    plot_ui.arrows(
+                        Arrows::new(origins, tips)
+                            .color(egui::Color32::from_gray(150))
+                            .tip_length(6.0)
+                            .allow_hover(false),
+                    );
+                }
+                if let Some(params) = field.as_ref().filter(|_| self.show_nullclines) {
+                    for line in nullclines(params, bounds.max()) {
+                        let (color, name) = match line.species {
+                            Species::Prey => (egui::Color32::BLUE, "Prey Nullcline"),
+                            Species::Predator => (egui::Color32::RED, "Predator Nullcline"),
+                        };
+                        plot_ui.line(
+                            Line::new(PlotPoints::from(vec![line.from, line.to]))
+                                .color(color)
+                                .style(egui_plot::LineStyle::dashed_loose())
+                                .name(name),
+                        );
+                    }
+                    plot_ui.points(
+                        Points::new(equilibria(params))
+                            .radius(5.0)
+                            .color(egui::Color32::BLACK)
+                            .name("Equilibria"),
+                    );
+                }
+                if self.overlay {
+                    plot_ui.line(
+                        Line::new(PlotPoints::from(self.stochastic_phase.clone()))
+                            .color(egui::Color32::from_gray(170))
+                            .name("Stochastic Phase Plot"),
+                    );
+                }
+                for snapshot in self.snapshots.iter().filter(|s| s.visible) {
+                    plot_ui.line(
+                        Line::new(snapshot.phase())
+                            .color(snapshot.color)
+                            .name(&snapshot.name),
+                    );
+                }
+                plot_ui.line(phase_line);
+                for (i, (orbit, start)) in
+                    self.phase_orbits.iter().zip(&self.phase_starts).enumerate()
+                {
+                    let color = ORBIT_COLORS[i % ORBIT_COLORS.len()];
+                    plot_ui.line(
+                        Line::new(PlotPoints::from(orbit.clone()))
+                            .color(color)
+                            .name(format!("Orbit from ({:.0}, {:.0})", start[0], start[1])),
+                    );
+                    plot_ui.points(Points::new(vec![*start]).radius(4.0).color(color));
+                }
+                self.phase_markers(plot_ui);
+
+                let clicked = if plot_ui.response().clicked() {
+                    plot_ui.pointer_coordinate()
+                } else {
+                    None
+                };
+                (clicked, (bounds.min(), bounds.max()))
+            })
+            .inner;
+        self.phase_bounds = Some(bounds);
+
+        match clicked {
+            Some(point) if self.click_tool == ClickTool::Orbit => {
+                self.phase_starts.push([point.x.max(0.0), point.y.max(0.0)]);
+                self.solve_phase_orbits();
+            }
+            Some(point) => {
+                if let Some(i) = nearest_point(&self.phase_points, [point.x, point.y], scale) {
+                    self.probe_click(self.prey_points[i][0]);
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// Chooses what clicking the plots does and reads out the probe and measurement.
+    /// Adding orbits is only offered on the phase plot.
+    fn probe_tools(&mut self, ui: &mut egui::Ui, orbits: bool) {
+        if !orbits && self.click_tool == ClickTool::Orbit {
+            self.click_tool = ClickTool::Probe;
+        }
+        ui.horizontal(|ui| {
+            ui.label("Click to:");
+            ui.selectable_value(&mut self.click_tool, ClickTool::Probe, "Probe")
+                .on_hover_text("Mark the nearest sample on the time and phase plots");
+            ui.selectable_value(&mut self.click_tool, ClickTool::Measure, "Measure")
+                .on_hover_text("Mark two samples and show the time between them");
+            if orbits {
+                ui.selectable_value(&mut self.click_tool, ClickTool::Orbit, "Add orbit")
+                    .on_hover_text("Add an orbit from the clicked point");
+            }
+            if ui
+                .add_enabled(
+                    self.probe.is_some() || !self.measured.is_empty(),
+                    egui::Button::new("Clear markers"),
+                )
+                .clicked()
+            {
+                self.probe = None;
+                self.measured.clear();
+            }
+        });
+        let sample = |t: f64| {
+            nearest_sample(&self.prey_points, t)
+                .map(|i| sample_readout(&self.prey_points, &self.predator_points, i))
+        };
+        if let Some(readout) = self.probe.and_then(sample) {
+            ui.colored_label(
+                PROBE_COLOR,
+                format!("Probe: {}", readout.replace('\n', ", ")),
+            );
+        }
+        if let [first, second] = self.measured[..] {
+            ui.colored_label(
+                MEASURE_COLOR,
+                format!(
+                    "Measured: t = {:.2} to {:.2}, Δt = {:.2}",
+                    first,
+                    second,
+                    (second - first).abs()
+                ),
+            );
+        } else if self.click_tool == ClickTool::Measure {
+            ui.label(
+                "Click two points, such as successive peaks, to measure the time between them.",
+            );
+        }
+    }
+
+    /// Marks the sample at time `t` with the current click tool. A third measured sample
+    /// starts a new measurement.
+    fn probe_click(&mut self, t: f64) {
+        match self.click_tool {
+            ClickTool::Measure => {
+                if self.measured.len() == 2 {
+                    self.measured.clear();
+                }
+                self.measured.push(t);
+            }
+            _ => self.probe = Some(t),
+        }
+    }
+
+    /// Times of the probe and measurement markers with their colors and names.
+    fn markers(&self) -> [(Vec<f64>, egui::Color32, &'static str); 2] {
+        [
+            (self.probe.into_iter().collect(), PROBE_COLOR, "Probe"),
+            (self.measured.clone(), MEASURE_COLOR, "Measurement"),
+        ]
+    }
+}
+
+impl LotkaVolterraApp {
+    /// Schedules a denser solve of the visible time window `(a, b)` once zooming stops,
+    /// if the whole run has too few samples there. A margin around the window is solved
+    /// too, so that small pans need no new solve. Runs with delay are not solved again.
+    fn request_zoom(&mut self, (a, b): (f64, f64)) {
+        let visible = (a.max(self.params.t_start), b.min(self.params.t_end));
+        let width = visible.1 - visible.0;
+        let step = width / ZOOM_SAMPLES;
+        if self.delay.is_some() || width <= 0.0 || step >= output_step(&self.params) / 2.0 {
+            self.zoom_request = None;
+            return;
+        }
+        let solved = self
+            .zoom
+            .as_ref()
+            .is_some_and(|zoom| Zoom::covers(zoom.window, zoom.step, visible, step));
+        let requested = self
+            .zoom_request
+            .is_some_and(|(window, requested)| Zoom::covers(window, requested, visible, step));
+        if solved {
+            self.zoom_request = None;
+        } else if !requested {
+            let window = (
+                (visible.0 - width / 2.0).max(self.params.t_start),
+                (visible.1 + width / 2.0).min(self.params.t_end),
+            );
+            self.zoom_request = Some((window, step));
+            self.zoom_debouncer.trigger();
+        }
+    }
+
+    /// Starts the requested zoomed solve on a worker thread from the state of the whole
+    /// run at the last sample before the window.
+    fn start_zoom(&mut self) {
+        let Some((window, step)) = self.zoom_request else {
+            return;
+        };
+        if self.job.is_some() || self.prey_points.is_empty() {
+            return;
+        }
+        // The first of repeated samples is the state before an intervention due then
+        let mut i = self
+            .prey_points
+            .partition_point(|p| p[0] < window.0)
+            .saturating_sub(1);
+        while i > 0 && self.prey_points[i - 1][0] == self.prey_points[i][0] {
+            i -= 1;
+        }
+        let t0 = self.prey_points[i][0];
+        let start = (t0, [self.prey_points[i][1], self.predator_points[i][1]]);
+        let (params, terms, schedule) = (self.params, self.terms.clone(), self.schedule.clone());
+        self.zoom_job = Some(Job::spawn(move |progress| {
+            let (times, prey, predators) =
+                solve_window(params, &terms, &schedule, start, window.1, step, progress)
+                    .map_err(|e| format!("Error solving the zoomed window: {}", e))?;
+            Ok(Zoom {
+                window: (t0, window.1),
+                step,
+                prey: times.iter().zip(&prey).map(|(&t, &x)| [t, x]).collect(),
+                predators: times
+                    .iter()
+                    .zip(&predators)
+                    .map(|(&t, &y)| [t, y])
+                    .collect(),
+            })
+        }));
+    }
+
+    /// Adds the restored snapshots whose solves have finished. Returns true when a
+    /// result was received.
+    fn poll_restored(&mut self) -> bool {
+        let mut received = false;
+        self.restoring.retain(|(saved, color, job)| {
+            let Some(result) = job.try_result() else {
+                return true;
+            };
+            match result {
+                Ok(solution) => self.snapshots.push(Snapshot {
+                    name: saved.name.clone(),
+                    params: saved.params,
+                    times: solution.times,
+                    prey: solution.prey,
+                    predators: solution.predators,
+                    color: *color,
+                    visible: saved.visible,
+                }),
+                Err(e) => println!("Snapshot '{}' not restored: {}", saved.name, e),
+            }
+            received = true;
+            false
+        });
+        received
+    }
+
+    /// Swaps in the zoomed solution if it has finished. Returns true when a result was
+    /// received.
+    fn poll_zoom(&mut self) -> bool {
+        let Some(result) = self.zoom_job.as_ref().and_then(Job::try_result) else {
+            return false;
+        };
+        self.zoom_job = None;
+        match result {
+            Ok(zoom) => self.zoom = Some(zoom),
+            Err(e) => self.error_message = Some(e),
+        }
+        true
+    }
+}
+
+/// Points of a series of the whole run to draw in the time window `visible`: the `dense`
+/// samples of a zoomed solve replace those of the whole run over their time span, and
+/// what is visible is thinned to the lowest and highest points of `PLOT_BUCKETS` runs of
+/// points.
+fn plotted(whole: &[[f64; 2]], dense: &[[f64; 2]], visible: Option<(f64, f64)>) -> Vec<[f64; 2]> {
+    // One point beyond each edge, so that the lines run to the edges
+    let shown = |points: &[[f64; 2]]| match visible {
+        Some((a, b)) => {
+            let start = points.partition_point(|p| p[0] < a).saturating_sub(1);
+            let end = (points.partition_point(|p| p[0] <= b) + 1).min(points.len());
+            start..end.max(start)
+        }
+        None => 0..points.len(),
+    };
+    let whole = &whole[shown(whole)];
+    let points: Vec<[f64; 2]> = match (dense.first(), dense.last()) {
+        (Some(first), Some(last)) => {
+            let before = whole.partition_point(|p| p[0] < first[0]);
+            let after = whole.partition_point(|p| p[0] <= last[0]).max(before);
+            whole[..before]
+                .iter()
+                .chain(&dense[shown(dense)])
+                .chain(&whole[after..])
+                .copied()
+                .collect()
+        }
+        _ => whole.to_vec(),
+    };
+    decimate_min_max(&points, PLOT_BUCKETS)
+}
+
+/// Time, prey and predators of sample `i` of the time series.
+fn sample_readout(prey: &[[f64; 2]], predators: &[[f64; 2]], i: usize) -> String {
+    format!(
+        "t = {:.2}\nprey = {:.2}\npredators = {:.2}",
+        prey[i][0], prey[i][1], predators[i][1]
+    )
+}
+
+impl LotkaVolterraApp {
+    /// Differences between the current run and a snapshot over time, with the time at
+    /// which they first diverge.
+    fn difference_view(&mut self, ui: &mut egui::Ui) {
+        if self.snapshots.is_empty() {
+            ui.label("Pin a run in the side panel to compare the current run with it.");
+            return;
+        }
+        self.compared = self.compared.min(self.snapshots.len() - 1);
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_label("Compared with")
+                .selected_text(&self.snapshots[self.compared].name)
+                .show_ui(ui, |ui| {
+                    for (i, snapshot) in self.snapshots.iter().enumerate() {
+                        ui.selectable_value(&mut self.compared, i, &snapshot.name);
                     }
                 });
+            ui.add(
+                egui::Slider::new(&mut self.divergence_tolerance, 0.001..=0.5)
+                    .logarithmic(true)
+                    .text("Tolerance (fraction of the peak)"),
+            );
+        });
 
-                // Population Over Time Plot
-                columns[1].vertical(|ui| {
-                    ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
-                        ui.label(
-                            egui::RichText::new("Prey and Predator Populations Over Time")
-                                .size(14.0),
-                        ); // Smaller font
-                    });
+        let snapshot = &self.snapshots[self.compared];
+        let times: Vec<f64> = self.prey_points.iter().map(|p| p[0]).collect();
+        let prey: Vec<f64> = self.prey_points.iter().map(|p| p[1]).collect();
+        let predators: Vec<f64> = self.predator_points.iter().map(|p| p[1]).collect();
+        let difference = trajectory_difference(
+            (&snapshot.times, &snapshot.prey, &snapshot.predators),
+            (&times, &prey, &predators),
+        );
+        let threshold = [
+            self.divergence_tolerance * peak(&snapshot.prey),
+            self.divergence_tolerance * peak(&snapshot.predators),
+        ];
+        let diverged = divergence_time(&difference, threshold);
+        ui.label(match diverged {
+            Some(t) => format!(
+                "The runs differ by more than {:.1}% of the peak populations from t = {:.1}.",
+                self.divergence_tolerance * 100.0,
+                t
+            ),
+            None if difference.is_empty() => "The runs do not overlap in time.".to_string(),
+            None => "The runs stay within the tolerance.".to_string(),
+        });
 
-                    ui.add_space(5.0);
-
-                    if !self.prey_points.is_empty() && !self.predator_points.is_empty() {
-                        let min_time = self
-                            .prey_points
-                            .iter()
-                            .map(|p| p[0])
-                            .fold(f64::INFINITY, f64::min);
-                        let max_time = self
-                            .prey_points
-                            .iter()
-                            .map(|p| p[0])
-                            .fold(f64::NEG_INFINITY, f64::max);
-                        let min_pop = self
-                            .prey_points
-                            .iter()
-                            .map(|p| p[1])
-                            .chain(self.predator_points.iter().map(|p| p[1]))
-                            .fold(f64::INFINITY, f64::min);
-                        let max_pop = self
-                            .prey_points
-                            .iter()
-                            .map(|p| p[1])
-                            .chain(self.predator_points.iter().map(|p| p[1]))
-                            .fold(f64::NEG_INFINITY, f64::max);
-
-                        let prey_line = Line::new(PlotPoints::from_iter(
-                            self.prey_points.iter().map(|&[x, y]| [x, y]),
-                        ))
-                        .name("Prey Population");
-
-                        let predator_line = Line::new(PlotPoints::from_iter(
-                            self.predator_points.iter().map(|&[x, y]| [x, y]),
-                        ))
-                        .name("Predator Population");
-
-                        Plot::new("populations_over_time")
-                            .view_aspect(1.0)
-                            .legend(Legend::default().position(Corner::RightTop))
-                            .include_x(min_time)
-                            .include_x(max_time)
-                            .include_y(min_pop)
-                            .include_y(max_pop)
-                            .show(ui, |plot_ui| {
-                                plot_ui.line(prey_line);
-                                plot_ui.line(predator_line);
-                            });
-                    } else {
-                        ui.label("No data available for population over time.");
+        Plot::new("difference_plot")
+            .legend(Legend::default().position(Corner::RightTop))
+            .x_axis_label("Time")
+            .y_axis_label(format!("Current run - {}", snapshot.name))
+            .show(ui, |plot_ui| {
+                if let Some(t) = diverged {
+                    plot_ui.vline(
+                        VLine::new(t)
+                            .color(egui::Color32::GRAY)
+                            .style(egui_plot::LineStyle::dashed_dense())
+                            .name("Divergence"),
+                    );
+                }
+                plot_ui.line(
+                    Line::new(PlotPoints::from_iter(
+                        difference.iter().map(|d| [d[0], d[1]]),
+                    ))
+                    .color(egui::Color32::BLUE)
+                    .name("Prey Difference"),
+                );
+                plot_ui.line(
+                    Line::new(PlotPoints::from_iter(
+                        difference.iter().map(|d| [d[0], d[2]]),
+                    ))
+                    .color(egui::Color32::RED)
+                    .name("Predator Difference"),
+                );
+            });
+    }
+
+    /// Draws a frame of the application.
+    fn show(&mut self, ctx: &egui::Context) {
+        // Undo and redo shortcuts, unless a text field is being edited
+        if !ctx.wants_keyboard_input() {
+            let (undo, redo) = ctx.input_mut(|i| {
+                let redo = i.consume_shortcut(&REDO) || i.consume_shortcut(&REDO_ALT);
+                (i.consume_shortcut(&UNDO), redo)
+            });
+            if undo {
+                self.undo();
+            }
+            if redo {
+                self.redo();
+            }
+        }
+
+        // Remembers where the window is, to open there next time
+        let window = ctx.input(|i| {
+            let viewport = i.viewport();
+            let position = viewport.outer_rect?.min;
+            let size = viewport.inner_rect?.size();
+            Some([position.x, position.y, size.x, size.y])
+        });
+        self.window = window.or(self.window);
+
+        // Solves after the inputs settle and swaps in finished solutions
+        if self.debouncer.ready() {
+            self.start_solve();
+        }
+        self.poll_solution();
+        if self.zoom_debouncer.ready() {
+            self.start_zoom();
+        }
+        self.poll_zoom();
+        self.poll_restored();
+        let remaining = [self.debouncer.remaining(), self.zoom_debouncer.remaining()]
+            .into_iter()
+            .flatten()
+            .min();
+        if self.job.is_some() || self.zoom_job.is_some() || !self.restoring.is_empty() {
+            ctx.request_repaint();
+        } else if let Some(remaining) = remaining {
+            ctx.request_repaint_after(remaining);
+        }
+
+        // Side navigation with the parameters shared by all views
+        egui::SidePanel::left("navigation")
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.heading("Lotka-Volterra");
+                ui.add_space(5.0);
+                for view in View::ALL {
+                    ui.selectable_value(&mut self.view, view, view.name());
+                }
+                ui.separator();
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    if self.parameter_editor(ui) {
+                        self.request_solve();
+                    }
+                    ui.add_space(10.0);
+                    if ui
+                        .checkbox(&mut self.overlay, "Overlay stochastic realisation")
+                        .changed()
+                    {
+                        self.request_solve();
                     }
+                    if ui
+                        .button("Restore defaults")
+                        .on_hover_text(
+                            "Default parameters and slider ranges, without harvesting, forcing, \
+                             interventions or delay",
+                        )
+                        .clicked()
+                    {
+                        self.restore_defaults();
+                    }
+                    ui.add_space(10.0);
+                    egui::CollapsingHeader::new("Snapshots")
+                        .default_open(true)
+                        .show(ui, |ui| self.snapshot_editor(ui));
+                    egui::CollapsingHeader::new("Export")
+                        .default_open(false)
+                        .show(ui, |ui| self.export_panel(ui));
+                    egui::CollapsingHeader::new("History")
+                        .default_open(true)
+                        .show(ui, |ui| self.history_panel(ui));
                 });
             });
 
-            ui.add_space(10.0);
+        egui::CentralPanel::default().show(ctx, |ui| {
+            // Centered main title
+            ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
+                ui.heading(format!(
+                    "Lotka-Volterra Predator-Prey Model: {}",
+                    self.view.name()
+                ));
+            });
 
-            // Collapsible section for user inputs
-            egui::CollapsingHeader::new("Simulation Parameters")
-                .default_open(true)
-                .show(ui, |ui| {
-                    ui.columns(2, |columns| {
-                        // Left column: Initial conditions
-                        columns[0].vertical(|ui| {
-                            ui.label("Initial Conditions:");
-                            if ui
-                                .add(
-                                    egui::Slider::new(&mut self.params.initial_prey, 0.0..=2000.0)
-                                        .text("Prey"),
-                                )
-                                .changed()
-                            {
-                                self.solve_system();
-                            }
-                            if ui
-                                .add(
-                                    egui::Slider::new(
-                                        &mut self.params.initial_predator,
-                                        0.0..=2000.0,
-                                    )
-                                    .text("Predator"),
-                                )
-                                .changed()
-                            {
-                                self.solve_system();
-                            }
-                            if ui
-                                .add(
-                                    egui::Slider::new(&mut self.t_start, 0.0..=self.t_end - 10.0)
-                                        .text("Start Time"),
-                                )
-                                .changed()
-                            {
-                                self.solve_system();
-                            }
-                            if ui
-                                .add(
-                                    egui::Slider::new(&mut self.t_end, 0.0..=8000.0)
-                                        .text("Time End"),
-                                )
-                                .changed()
-                            {
-                                self.solve_system();
-                            }
-                        });
-
-                        // Right column: Model parameters
-                        columns[1].vertical(|ui| {
-                            ui.label("Model Parameters:");
-                            if ui
-                                .add(
-                                    egui::Slider::new(&mut self.params.alpha, 0.0..=0.01)
-                                        .text("Alpha (Prey Birth Rate)"),
-                                )
-                                .changed()
-                            {
-                                self.solve_system();
-                            }
-                            if ui
-                                .add(
-                                    egui::Slider::new(&mut self.params.beta, 0.0..=0.00001)
-                                        .text("Beta (Prety Death Rate)"),
-                                )
-                                .changed()
-                            {
-                                self.solve_system();
-                            }
-                            if ui
-                                .add(
-                                    egui::Slider::new(&mut self.params.delta, 0.0..=0.00001)
-                                        .text("Delta (Predator Birth Rate)"),
-                                )
-                                .changed()
-                            {
-                                self.solve_system();
-                            }
-                            if ui
-                                .add(
-                                    egui::Slider::new(&mut self.params.gamma, 0.0..=0.01)
-                                        .text("Gamma (Predator Death Rate)"),
-                                )
-                                .changed()
-                            {
-                                self.solve_system();
-                            }
-                        });
-                    });
+            // Progress of the background solve
+            if let Some(job) = &self.job {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.add(
+                        egui::ProgressBar::new(job.progress())
+                            .show_percentage()
+                            .desired_width(300.0),
+                    );
+                    ui.label("Solving...");
                 });
+            } else if self.debouncer.is_pending() {
+                ui.label("Waiting for changes to settle...");
+            }
+            ui.add_space(10.0);
+
+            // Display error message if something goes wrong
+            if let Some(ref msg) = self.error_message {
+                ui.colored_label(egui::Color32::RED, msg);
+                ui.add_space(10.0);
+            }
+
+            let params = self.current_params();
+            match self.view {
+                View::Deterministic => self.deterministic_view(ui),
+                View::Stochastic => {
+                    self.left_out_notice(ui, "stochastic model", false, true, false);
+                    self.stochastic_view
+                        .show(ui, &params, &self.schedule, &self.phase_points)
+                }
+                View::Ensemble => {
+                    self.left_out_notice(ui, "environmental noise model", true, true, false);
+                    self.ensemble_view.show(
+                        ui,
+                        &params,
+                        &self.terms,
+                        &self.schedule,
+                        [&self.prey_points, &self.predator_points],
+                    )
+                }
+                View::Sweep => self.sweep_view.show(ui, &self.scenario()),
+                View::PhasePortrait => self.phase_portrait_view(ui),
+                View::Fitting => {
+                    if let Some(fitted) = self.fitting_view.show(ui, &self.scenario()) {
+                        self.params = fitted;
+                        self.start_solve();
+                    }
+                }
+                View::Difference => self.difference_view(ui),
+                View::Spatial => {
+                    self.left_out_notice(ui, "spatial model", false, false, false);
+                    self.spatial_view.show(ui, &params)
+                }
+                View::Agents => {
+                    self.left_out_notice(ui, "agent-based model", false, false, false);
+                    self.agent_view.show(ui, &params)
+                }
+            }
         });
     }
+}
 
-    /// Handle window close event
+impl eframe::App for LotkaVolterraApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.show(ctx);
+    }
+
+    /// Handle window close event, saving the session for the next launch
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        if let Some(dir) = &self.session_dir
+            && let Err(e) = self.session().save(dir)
+        {
+            println!("Could not save the GUI state: {}", e);
+        }
         println!("\nSimulation terminating...");
     }
 }
 
-/// Launch the interactive GUI.
-pub fn launch_gui(params: LotkaVolterraParameters) -> Result<(), Box<dyn Error>> {
-    let options = eframe::NativeOptions::default();
+/// Launch the interactive GUI. Without a scenario the last session's parameters,
+/// harvesting, forcing and interventions are resumed, and without a view its view. The
+/// window, slider ranges and snapshots are always resumed, and the session is saved again
+/// on exit.
+pub fn launch_gui(scenario: Option<Scenario>, view: Option<View>) -> Result<(), Box<dyn Error>> {
+    launch_gui_with(scenario, view, |app| app)
+}
+
+/// Launch the interactive GUI like [`launch_gui`], with `setup` applied to the app before
+/// it opens, e.g. to set the grid of the spatial view.
+pub fn launch_gui_with(
+    scenario: Option<Scenario>,
+    view: Option<View>,
+    setup: impl FnOnce(LotkaVolterraApp) -> LotkaVolterraApp + 'static,
+) -> Result<(), Box<dyn Error>> {
+    let dir = Session::directory();
+    let session = match dir.as_ref().map(Session::load).transpose() {
+        Ok(session) => session.flatten().unwrap_or_default(),
+        Err(e) => {
+            println!("Ignoring the saved GUI state: {}", e);
+            Session::default()
+        }
+    };
+    let mut options = eframe::NativeOptions::default();
+    if let Some([x, y, width, height]) = session.window {
+        options.viewport = options
+            .viewport
+            .with_position([x, y])
+            .with_inner_size([width, height]);
+    }
     eframe::run_native(
         "Lotka-Volterra Simulation",
         options,
-        Box::new(|_cc| Ok(Box::new(LotkaVolterraApp::new(params)))),
+        Box::new(move |_cc| {
+            let scenario = scenario.unwrap_or_else(|| session.scenario.clone());
+            Ok(Box::new(setup(
+                LotkaVolterraApp::from_scenario(scenario)
+                    .with_session(&session)
+                    .with_view(view.unwrap_or(session.view))
+                    .saving_to(dir),
+            )))
+        }),
     )
     .map_err(|e| SimulationError::GuiError(e.to_string()).into())
 }
@@ -385,6 +2069,17 @@ pub fn launch_gui(params: LotkaVolterraParameters) -> Result<(), Box<dyn Error>>
 mod tests {
     use super::*;
 
+    fn wait_for_solution(app: &mut LotkaVolterraApp) {
+        let start = std::time::Instant::now();
+        while !app.poll_solution() {
+            assert!(
+                start.elapsed() < Duration::from_secs(10),
+                "Solve timed out."
+            );
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
     #[test]
     fn test_gui_initialization() {
         let params = LotkaVolterraParameters {
@@ -398,10 +2093,225 @@ mod tests {
             t_end: 200.0,
         };
 
-        let app = LotkaVolterraApp::new(params);
+        let mut app = LotkaVolterraApp::new(params);
+        wait_for_solution(&mut app);
+        assert!(!app.prey_points.is_empty());
         assert!(
             app.error_message.is_none(),
             "GUI should initialize without errors."
         );
     }
+
+    #[test]
+    fn test_sliders_start_from_launch_parameters() {
+        let params = LotkaVolterraParameters {
+            alpha: 0.5,
+            beta: 2e-9,
+            t_end: 20000.0,
+            ..LotkaVolterraParameters::default()
+        };
+        let app = LotkaVolterraApp::new(params);
+        assert_eq!(app.current_params(), params);
+
+        let range = |name| app.sliders[SLIDERS.iter().position(|s| s.0 == name).unwrap()];
+        assert!(range("alpha").max >= 0.5);
+        assert!(range("beta").min <= 2e-9 && range("beta").logarithmic);
+        assert!(range("t_end").max >= 20000.0);
+    }
+
+    #[test]
+    fn test_every_view_draws_with_shared_parameters() {
+        let mut app = LotkaVolterraApp::new(LotkaVolterraParameters {
+            t_end: 100.0,
+            ..LotkaVolterraParameters::default()
+        });
+        wait_for_solution(&mut app);
+        assert!(!app.stochastic_phase.is_empty(), "Overlay is simulated.");
+
+        // A change still being solved is not pinned with the run shown
+        let solved = app.params;
+        app.params.alpha *= 2.0;
+        app.request_solve();
+        app.pin_snapshot();
+        assert_eq!(app.snapshots[0].name, "Snapshot 1");
+        assert_eq!(app.snapshots[0].params, solved);
+        assert_eq!(app.history.entries().len(), 1);
+        assert!(!app.history.entries()[0].thumbnail.is_empty());
+
+        let ctx = egui::Context::default();
+        for view in View::ALL {
+            app.view = view;
+            let _ = ctx.run(egui::RawInput::default(), |ctx| app.show(ctx));
+        }
+        assert!(app.error_message.is_none());
+    }
+
+    #[test]
+    fn test_undo_and_redo_restore_parameters() {
+        let first = LotkaVolterraParameters {
+            t_end: 100.0,
+            ..LotkaVolterraParameters::default()
+        };
+        let mut app = LotkaVolterraApp::new(first);
+        app.overlay = false;
+        wait_for_solution(&mut app);
+
+        app.params.alpha = 0.02;
+        app.start_solve();
+        wait_for_solution(&mut app);
+        assert_eq!(app.history.entries().len(), 2);
+
+        app.undo();
+        assert_eq!(app.params, first);
+        wait_for_solution(&mut app);
+        assert_eq!(app.history.entries().len(), 2, "Undoing records nothing.");
+        app.redo();
+        assert_eq!(app.params.alpha, 0.02);
+
+        app.delay = Some(1.0);
+        app.start_solve();
+        wait_for_solution(&mut app);
+        assert_eq!(
+            app.history.entries().len(),
+            2,
+            "Only parameter changes are recorded."
+        );
+    }
+
+    #[test]
+    fn test_probe_and_measure_clicks() {
+        let mut app = LotkaVolterraApp::new(LotkaVolterraParameters {
+            t_end: 100.0,
+            ..LotkaVolterraParameters::default()
+        });
+        app.overlay = false;
+        wait_for_solution(&mut app);
+
+        app.probe_click(40.0);
+        assert_eq!(app.probe, Some(40.0));
+        app.click_tool = ClickTool::Measure;
+        for t in [10.0, 30.0, 50.0] {
+            app.probe_click(t);
+        }
+        assert_eq!(
+            app.measured,
+            [50.0],
+            "A third click starts a new measurement."
+        );
+        app.probe_click(20.0);
+
+        // The markers are drawn on both plots and the orbit tool is only kept on the phase plot
+        app.click_tool = ClickTool::Orbit;
+        let ctx = egui::Context::default();
+        for view in [View::PhasePortrait, View::Deterministic] {
+            app.view = view;
+            let _ = ctx.run(egui::RawInput::default(), |ctx| app.show(ctx));
+        }
+        assert_eq!(app.click_tool, ClickTool::Probe);
+        assert_eq!(app.markers()[1].0, [50.0, 20.0]);
+    }
+
+    #[test]
+    fn test_session_is_resumed_and_defaults_restored() {
+        let mut app = LotkaVolterraApp::new(LotkaVolterraParameters {
+            alpha: 0.02,
+            t_end: 100.0,
+            ..LotkaVolterraParameters::default()
+        });
+        app.overlay = false;
+        wait_for_solution(&mut app);
+        app.pin_snapshot();
+        app.snapshots[0].visible = false;
+        let alpha = SLIDERS.iter().position(|s| s.0 == "alpha").unwrap();
+        app.sliders[alpha].max = 0.5;
+        app.view = View::PhasePortrait;
+
+        let session = app.session();
+        let mut resumed = LotkaVolterraApp::from_scenario(session.scenario.clone())
+            .with_session(&session)
+            .with_view(session.view);
+        assert_eq!(resumed.session().snapshots, session.snapshots);
+        let start = std::time::Instant::now();
+        while !resumed.restoring.is_empty() {
+            resumed.poll_restored();
+            assert!(
+                start.elapsed() < Duration::from_secs(10),
+                "Restore timed out."
+            );
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(resumed.params, app.params);
+        assert_eq!(resumed.view, View::PhasePortrait);
+        assert_eq!(resumed.sliders, app.sliders);
+        assert_eq!(resumed.snapshots[0].prey, app.snapshots[0].prey);
+        assert!(!resumed.snapshots[0].visible);
+
+        app.restore_defaults();
+        assert_eq!(app.params, LotkaVolterraParameters::default());
+        assert_eq!(app.view, View::Deterministic);
+        assert_eq!(app.snapshots.len(), 1, "Snapshots are kept.");
+    }
+
+    #[test]
+    fn test_zoomed_window_is_solved_densely() {
+        let mut app = LotkaVolterraApp::new(LotkaVolterraParameters {
+            t_end: 1000.0,
+            ..LotkaVolterraParameters::default()
+        });
+        app.overlay = false;
+        wait_for_solution(&mut app);
+        app.request_zoom((0.0, 1000.0));
+        assert!(app.zoom_request.is_none(), "The whole run is dense enough.");
+
+        app.request_zoom((500.0, 510.0));
+        let (window, step) = app.zoom_request.unwrap();
+        assert_eq!(window, (495.0, 515.0));
+        assert!(step < 0.01);
+        app.start_zoom();
+        let start = std::time::Instant::now();
+        while !app.poll_zoom() {
+            assert!(start.elapsed() < Duration::from_secs(10), "Zoom timed out.");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert!(app.error_message.is_none());
+        app.request_zoom((502.0, 508.0));
+        assert!(app.zoom_request.is_none(), "Covered by the zoomed solve.");
+
+        // The dense samples replace the whole run's in the visible window
+        let zoom = app.zoom.as_ref().unwrap();
+        let prey = plotted(&app.prey_points, &zoom.prey, Some((500.0, 510.0)));
+        assert!(prey.len() > 1000 && prey.len() <= 2 * PLOT_BUCKETS + 2);
+        assert!(prey[0][0] < 500.0 && prey.last().unwrap()[0] > 510.0);
+        let whole = app.prey_points[5050];
+        let dense = zoom.prey[nearest_sample(&zoom.prey, whole[0]).unwrap()];
+        assert!((dense[0] - whole[0]).abs() < 1e-6);
+        assert!((dense[1] - whole[1]).abs() / whole[1] < 1e-3);
+    }
+
+    #[test]
+    fn test_export_data_and_parameters() {
+        let params = LotkaVolterraParameters {
+            t_end: 50.0,
+            ..LotkaVolterraParameters::default()
+        };
+        let mut app = LotkaVolterraApp::new(params);
+        app.overlay = false;
+        wait_for_solution(&mut app);
+        let dir = std::env::temp_dir().join(format!("lv_export_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        app.export_path = dir.join("run.png").to_string_lossy().into_owned();
+
+        let csv = app.export(Export::Csv).unwrap();
+        let lines = std::fs::read_to_string(&csv).unwrap().lines().count();
+        assert_eq!(lines, app.prey_points.len() + 1);
+        let json = std::fs::read_to_string(app.export(Export::Json).unwrap()).unwrap();
+        assert!(json.starts_with("{") && json.contains("\"predators\": ["));
+        let scenario = Scenario::load(app.export(Export::Scenario).unwrap()).unwrap();
+        assert_eq!(scenario.params, params);
+        assert!(app.export(Export::PlotSvg).is_err(), "No plot drawn yet.");
+        let _ = egui::Context::default().run(egui::RawInput::default(), |ctx| app.show(ctx));
+        let svg = std::fs::read_to_string(app.export(Export::PlotSvg).unwrap()).unwrap();
+        assert!(svg.contains("<svg"));
+        std::fs::remove_dir_all(dir).ok();
+    }
 }
@@ -0,0 +1,173 @@
+use crate::agents::{AgentParameters, AgentSimulation, validate_agents};
+use crate::models::LotkaVolterraParameters;
+use crate::stochastic::LotkaVolterraParams;
+use eframe::egui;
+use egui_plot::{Corner, Legend, Line, Plot, PlotPoints};
+
+/// Time step of the agent-based simulation.
+const AGENT_DT: f64 = 1.0;
+
+/// Agents view of the application: the agent-based model of the shared parameters on a
+/// lattice, drawn live with its counts over time. The run starts again when the
+/// parameters change.
+pub struct AgentView {
+    lattice: AgentParameters,
+    simulation: Option<AgentSimulation>,
+    steps_per_frame: usize,
+    running: bool,
+    error_message: Option<String>,
+    texture: Option<egui::TextureHandle>,
+    source: Option<LotkaVolterraParameters>, // Parameters of the running simulation
+}
+
+impl Default for AgentView {
+    fn default() -> Self {
+        Self::new(AgentParameters::default())
+    }
+}
+
+impl AgentView {
+    /// Creates the view with the given lattice.
+    pub fn new(lattice: AgentParameters) -> Self {
+        Self {
+            lattice,
+            simulation: None,
+            steps_per_frame: 5,
+            running: true,
+            error_message: None,
+            texture: None,
+            source: None,
+        }
+    }
+
+    /// Places the initial populations of `params` on the lattice again.
+    fn reset(&mut self, params: &LotkaVolterraParameters) {
+        let stochastic = LotkaVolterraParams::from_deterministic(params, AGENT_DT);
+        self.source = Some(*params);
+        match validate_agents(&stochastic, &self.lattice) {
+            Ok(()) => {
+                self.simulation = Some(AgentSimulation::new(stochastic, self.lattice));
+                self.error_message = None;
+            }
+            Err(e) => {
+                self.simulation = None;
+                self.error_message = Some(e.to_string());
+            }
+        }
+    }
+
+    fn advance(&mut self, steps: usize) {
+        let Some(simulation) = &mut self.simulation else {
+            return;
+        };
+        for _ in 0..steps {
+            if let Err(e) = simulation.step() {
+                self.running = false;
+                self.error_message = Some(e.to_string());
+                return;
+            }
+        }
+    }
+
+    /// Renders prey in green and predators in red, brighter for crowded cells.
+    fn lattice_image(simulation: &AgentSimulation) -> egui::ColorImage {
+        let (prey, predators) = simulation.occupancy();
+        let shade = |n: u32| {
+            if n == 0 {
+                0
+            } else {
+                (120 + 45 * n).min(255) as u8
+            }
+        };
+        let pixels = prey
+            .iter()
+            .zip(&predators)
+            .map(|(&x, &y)| egui::Color32::from_rgb(shade(y), shade(x), 30))
+            .collect();
+        egui::ColorImage {
+            size: [simulation.lattice.width, simulation.lattice.height],
+            pixels,
+        }
+    }
+
+    /// Shows the view for `params`, advancing the simulation while it plays.
+    pub fn show(&mut self, ui: &mut egui::Ui, params: &LotkaVolterraParameters) {
+        if self.source != Some(*params) {
+            self.reset(params);
+        }
+        if self.running && self.simulation.is_some() {
+            self.advance(self.steps_per_frame);
+            ui.ctx().request_repaint();
+        }
+
+        if let Some(ref msg) = self.error_message {
+            ui.colored_label(egui::Color32::RED, msg);
+            ui.add_space(10.0);
+        }
+        let Some(simulation) = &self.simulation else {
+            return;
+        };
+        let image = Self::lattice_image(simulation);
+        let options = egui::TextureOptions::NEAREST;
+        match &mut self.texture {
+            Some(texture) => texture.set(image, options),
+            None => self.texture = Some(ui.ctx().load_texture("agents", image, options)),
+        }
+
+        let mut reset = false;
+        ui.horizontal(|ui| {
+            let label = if self.running { "Pause" } else { "Play" };
+            if ui.button(label).clicked() {
+                self.running = !self.running;
+            }
+            if ui.button("Step").clicked() {
+                self.advance(1);
+            }
+            reset = ui.button("Reset").clicked();
+            ui.add(egui::Slider::new(&mut self.steps_per_frame, 1..=200).text("Speed"));
+            if let Some(simulation) = &self.simulation {
+                ui.label(format!(
+                    "t = {:.1}   prey = {}   predators = {}",
+                    params.t_start + simulation.time,
+                    simulation.prey.len(),
+                    simulation.predators.len()
+                ));
+            }
+        });
+        if reset {
+            self.reset(params);
+        }
+        ui.add_space(10.0);
+
+        let Some(simulation) = &self.simulation else {
+            return;
+        };
+        ui.columns(2, |columns| {
+            if let Some(texture) = &self.texture {
+                let ui = &mut columns[0];
+                let width = ui.available_width();
+                let lattice = &simulation.lattice;
+                let height = width * lattice.height as f32 / lattice.width as f32;
+                ui.add(egui::Image::new(texture).fit_to_exact_size(egui::vec2(width, height)));
+            }
+
+            let series = |counts: &[f64]| {
+                PlotPoints::from_iter(
+                    simulation
+                        .times
+                        .iter()
+                        .zip(counts)
+                        .map(|(&t, &n)| [params.t_start + t, n]),
+                )
+            };
+            Plot::new("agent_counts")
+                .legend(Legend::default().position(Corner::LeftTop))
+                .x_axis_label("Time")
+                .y_axis_label("Population")
+                .show(&mut columns[1], |plot_ui| {
+                    plot_ui.line(Line::new(series(&simulation.prey_counts)).name("Prey"));
+                    plot_ui.line(Line::new(series(&simulation.predator_counts)).name("Predators"));
+                });
+        });
+    }
+}
@@ -0,0 +1,192 @@
+use crate::error::SimulationError;
+use crate::interventions::Schedule;
+use crate::models::{ExternalTerms, LotkaVolterraParameters};
+use crate::sde::{EnvironmentalNoise, SdePath, sde_ensemble};
+use crate::stochastic::decimate;
+use crate::worker::{Debouncer, Job};
+use eframe::egui;
+use egui_plot::{Corner, Legend, Line, Plot, PlotPoints};
+use std::time::Duration;
+
+/// Parameters, harvesting and forcing, interventions, noise and number of runs of an
+/// ensemble.
+type EnsembleInputs = (
+    LotkaVolterraParameters,
+    ExternalTerms,
+    Schedule,
+    EnvironmentalNoise,
+    usize,
+);
+
+/// Realisations of an ensemble and their mean, thinned for drawing: one `[prey,
+/// predators]` pair of time series per run.
+struct EnsemblePlot {
+    runs: Vec<[Vec<[f64; 2]>; 2]>,
+    mean: [Vec<[f64; 2]>; 2],
+}
+
+/// Most points drawn per series of the ensemble.
+const ENSEMBLE_PLOT_POINTS: usize = 2000;
+
+/// Simulates the ensemble and thins it for drawing.
+fn ensemble_plot(
+    (params, terms, schedule, noise, runs): &EnsembleInputs,
+) -> Result<EnsemblePlot, SimulationError> {
+    let paths = sde_ensemble(params, terms, schedule, noise, *runs, None)?;
+    let series = |times: &[f64], values: &[f64]| -> Vec<[f64; 2]> {
+        let points: Vec<[f64; 2]> = times.iter().zip(values).map(|(&t, &v)| [t, v]).collect();
+        decimate(&points, ENSEMBLE_PLOT_POINTS)
+    };
+    let mean = |select: fn(&SdePath) -> &Vec<f64>| -> Vec<f64> {
+        let len = paths.iter().map(|p| select(p).len()).min().unwrap_or(0);
+        (0..len)
+            .map(|k| paths.iter().map(|p| select(p)[k]).sum::<f64>() / paths.len() as f64)
+            .collect()
+    };
+    let times = paths.first().map(|p| p.0.clone()).unwrap_or_default();
+    Ok(EnsemblePlot {
+        mean: [
+            series(&times, &mean(|p| &p.1)),
+            series(&times, &mean(|p| &p.2)),
+        ],
+        runs: paths
+            .iter()
+            .map(|(t, x, y)| [series(t, x), series(t, y)])
+            .collect(),
+    })
+}
+
+/// Ensemble view of the application: realisations of the environmental noise model for
+/// the shared parameters, harvesting, forcing and interventions, with their mean against
+/// the deterministic solution.
+pub struct EnsembleView {
+    noise: EnvironmentalNoise,
+    runs: usize,
+    plot: Option<EnsemblePlot>,
+    job: Option<Job<Result<EnsemblePlot, SimulationError>>>,
+    source: Option<EnsembleInputs>,
+    debouncer: Debouncer,
+    error_message: Option<String>,
+}
+
+impl Default for EnsembleView {
+    fn default() -> Self {
+        Self {
+            noise: EnvironmentalNoise::default(),
+            runs: 10,
+            plot: None,
+            job: None,
+            source: None,
+            debouncer: Debouncer::new(Duration::from_millis(300)),
+            error_message: None,
+        }
+    }
+}
+
+impl EnsembleView {
+    fn start(&mut self, inputs: &EnsembleInputs) {
+        let inputs = inputs.clone();
+        self.job = Some(Job::spawn(move |_| ensemble_plot(&inputs)));
+    }
+
+    /// Shows the view for `params`, `terms` and `schedule`, rerunning the ensemble once
+    /// its inputs settle after a change. `deterministic` holds the prey and predator
+    /// series drawn for comparison.
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        params: &LotkaVolterraParameters,
+        terms: &ExternalTerms,
+        schedule: &Schedule,
+        deterministic: [&[[f64; 2]]; 2],
+    ) {
+        let mut rerun = false;
+        ui.horizontal(|ui| {
+            ui.add(egui::Slider::new(&mut self.runs, 1..=50).text("Runs"));
+            ui.add(
+                egui::Slider::new(&mut self.noise.alpha_intensity, 0.0..=1.0).text("Alpha noise"),
+            );
+            ui.add(
+                egui::Slider::new(&mut self.noise.gamma_intensity, 0.0..=1.0).text("Gamma noise"),
+            );
+            rerun = ui.button("Rerun").clicked();
+        });
+
+        let inputs = (
+            *params,
+            terms.clone(),
+            schedule.clone(),
+            self.noise,
+            self.runs,
+        );
+        match &self.source {
+            None => rerun = true,
+            Some(source) if *source != inputs => self.debouncer.trigger(),
+            _ => {}
+        }
+        if self.debouncer.ready() || rerun {
+            self.start(&inputs);
+        }
+        self.source = Some(inputs);
+        if let Some(result) = self.job.as_ref().and_then(Job::try_result) {
+            self.job = None;
+            match result {
+                Ok(plot) => {
+                    self.plot = Some(plot);
+                    self.error_message = None;
+                }
+                Err(e) => self.error_message = Some(e.to_string()),
+            }
+        }
+        if self.job.is_some() {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("Simulating ensemble...");
+            });
+            ui.ctx().request_repaint_after(Duration::from_millis(50));
+        } else if let Some(remaining) = self.debouncer.remaining() {
+            ui.ctx().request_repaint_after(remaining);
+        }
+        if let Some(msg) = &self.error_message {
+            ui.colored_label(egui::Color32::RED, msg);
+        }
+
+        let Some(plot) = &self.plot else {
+            return;
+        };
+        ui.columns(2, |columns| {
+            for (i, (ui, name)) in columns.iter_mut().zip(["Prey", "Predators"]).enumerate() {
+                ui.label(egui::RichText::new(name).size(14.0));
+                Plot::new(("ensemble", i))
+                    .legend(Legend::default().position(Corner::RightTop))
+                    .x_axis_label("Time")
+                    .y_axis_label("Population")
+                    .show(ui, |plot_ui| {
+                        let faint = egui::Color32::from_rgba_unmultiplied(120, 150, 230, 60);
+                        for run in &plot.runs {
+                            plot_ui.line(
+                                Line::new(PlotPoints::from(run[i].clone()))
+                                    .color(faint)
+                                    .name("Realisations"),
+                            );
+                        }
+                        plot_ui.line(
+                            Line::new(PlotPoints::from(plot.mean[i].clone()))
+                                .color(egui::Color32::BLUE)
+                                .width(2.0)
+                                .name("Ensemble mean"),
+                        );
+                        plot_ui.line(
+                            Line::new(PlotPoints::from(decimate(
+                                deterministic[i],
+                                ENSEMBLE_PLOT_POINTS,
+                            )))
+                            .color(egui::Color32::RED)
+                            .width(2.0)
+                            .name("Deterministic"),
+                        );
+                    });
+            }
+        });
+    }
+}
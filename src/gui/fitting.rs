@@ -0,0 +1,192 @@
+use crate::error::SimulationError;
+use crate::fitting::{FITTED_RATES, Fit, Observations, fit_rates};
+use crate::models::LotkaVolterraParameters;
+use crate::scenario::Scenario;
+use crate::worker::Job;
+use eframe::egui;
+use egui_plot::{Corner, Legend, Line, Plot, PlotPoints, Points};
+use std::time::Duration;
+
+/// Fitting view of the application: loads observed populations from a CSV file and fits
+/// the rates of the shared model to them, with its harvesting, forcing, interventions and
+/// delay. The fitted rates can be applied to the shared parameters.
+pub struct FittingView {
+    path: String,
+    data: Option<Observations>,
+    fit: Option<Fit>,
+    job: Option<Job<Result<Fit, SimulationError>>>,
+    error_message: Option<String>,
+}
+
+impl Default for FittingView {
+    fn default() -> Self {
+        Self {
+            path: "observations.csv".to_string(),
+            data: None,
+            fit: None,
+            job: None,
+            error_message: None,
+        }
+    }
+}
+
+impl FittingView {
+    fn load(&mut self) {
+        self.job = None;
+        self.fit = None;
+        match Observations::load(&self.path) {
+            Ok(data) => {
+                self.data = Some(data);
+                self.error_message = None;
+            }
+            Err(e) => {
+                self.data = None;
+                self.error_message = Some(e.to_string());
+            }
+        }
+    }
+
+    fn start(&mut self, scenario: &Scenario) {
+        let Some(data) = &self.data else {
+            return;
+        };
+        let (scenario, data) = (scenario.clone(), data.clone());
+        self.job = Some(Job::spawn(move |progress| {
+            fit_rates(&scenario, &data, progress)
+        }));
+    }
+
+    /// Shows the view for `scenario`. Returns the fitted parameters, starting from the
+    /// first observation and spanning the observed times, when they are applied.
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        scenario: &Scenario,
+    ) -> Option<LotkaVolterraParameters> {
+        let mut applied = None;
+        ui.horizontal(|ui| {
+            ui.label("Observations (time, prey, predators):");
+            ui.text_edit_singleline(&mut self.path);
+            if ui.button("Load").clicked() {
+                self.load();
+            }
+            if ui
+                .add_enabled(
+                    self.data.is_some() && self.job.is_none(),
+                    egui::Button::new("Fit"),
+                )
+                .on_hover_text("Fit alpha, beta, delta and gamma, starting from their values")
+                .clicked()
+            {
+                self.start(scenario);
+            }
+            if ui
+                .add_enabled(self.fit.is_some(), egui::Button::new("Apply"))
+                .on_hover_text("Use the fitted rates and the observed start and time span")
+                .clicked()
+            {
+                applied = self.fit.as_ref().map(|fit| fit.params);
+            }
+        });
+
+        if let Some(result) = self.job.as_ref().and_then(Job::try_result) {
+            self.job = None;
+            match result {
+                Ok(fit) => {
+                    self.fit = Some(fit);
+                    self.error_message = None;
+                }
+                Err(e) => self.error_message = Some(e.to_string()),
+            }
+        }
+        if let Some(job) = &self.job {
+            let mut cancel = false;
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.add(
+                    egui::ProgressBar::new(job.progress())
+                        .show_percentage()
+                        .desired_width(300.0),
+                );
+                ui.label("Fitting...");
+                cancel = ui.button("Cancel").clicked();
+            });
+            if cancel {
+                // Dropping the job cancels the fit
+                self.job = None;
+            }
+            ui.ctx().request_repaint_after(Duration::from_millis(50));
+        }
+        if let Some(msg) = &self.error_message {
+            ui.colored_label(egui::Color32::RED, msg);
+        }
+        if let Some(fit) = &self.fit {
+            let rates: Vec<String> = FITTED_RATES
+                .iter()
+                .map(|&name| {
+                    format!(
+                        "{} = {:.4e}",
+                        name,
+                        fit.params.get(name).unwrap_or_default()
+                    )
+                })
+                .collect();
+            ui.label(format!(
+                "{}   (RMS scaled residual {:.3e} after {} iterations)",
+                rates.join("   "),
+                fit.error,
+                fit.iterations
+            ));
+        }
+
+        let Some(data) = &self.data else {
+            ui.label("Load a CSV file of observations, such as one saved by Export.");
+            return applied;
+        };
+        Plot::new("fitting")
+            .legend(Legend::default().position(Corner::RightTop))
+            .x_axis_label("Time")
+            .y_axis_label("Population")
+            .show(ui, |plot_ui| {
+                let observed = |values: &[f64]| -> Vec<[f64; 2]> {
+                    data.times
+                        .iter()
+                        .zip(values)
+                        .map(|(&t, &v)| [t, v])
+                        .collect()
+                };
+                plot_ui.points(
+                    Points::new(observed(&data.prey))
+                        .radius(3.0)
+                        .color(egui::Color32::BLUE)
+                        .name("Observed prey"),
+                );
+                plot_ui.points(
+                    Points::new(observed(&data.predators))
+                        .radius(3.0)
+                        .color(egui::Color32::RED)
+                        .name("Observed predators"),
+                );
+                if let Some(fit) = &self.fit {
+                    let series = |values: &[f64]| -> PlotPoints {
+                        fit.times
+                            .iter()
+                            .zip(values)
+                            .map(|(&t, &v)| [t, v])
+                            .collect()
+                    };
+                    plot_ui.line(
+                        Line::new(series(&fit.prey))
+                            .color(egui::Color32::BLUE)
+                            .name("Fitted prey"),
+                    );
+                    plot_ui.line(
+                        Line::new(series(&fit.predators))
+                            .color(egui::Color32::RED)
+                            .name("Fitted predators"),
+                    );
+                }
+            });
+        applied
+    }
+}
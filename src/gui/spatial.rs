@@ -0,0 +1,172 @@
+use crate::models::LotkaVolterraParameters;
+use crate::plot::density_color;
+use crate::spatial::{SpatialLotkaVolterraSystem, SpatialParameters, integrate_spatial};
+use eframe::egui;
+use ode_solvers::DVector;
+
+/// Spatial view of the application: the reaction-diffusion model of the shared parameters,
+/// drawn live as a heatmap of each species. The run starts again when the parameters
+/// change.
+pub struct SpatialView {
+    system: SpatialLotkaVolterraSystem,
+    state: DVector<f64>,
+    time: f64,
+    time_per_frame: f64,
+    running: bool,
+    error_message: Option<String>,
+    textures: Option<[egui::TextureHandle; 2]>,
+    source: Option<LotkaVolterraParameters>, // Parameters of the running simulation
+}
+
+impl Default for SpatialView {
+    fn default() -> Self {
+        Self::new(SpatialParameters::default())
+    }
+}
+
+impl SpatialView {
+    /// Creates the view with the given grid, diffusion and boundary settings.
+    pub fn new(spatial: SpatialParameters) -> Self {
+        let system = SpatialLotkaVolterraSystem::new(LotkaVolterraParameters::default(), spatial);
+        let state = system.initial_state();
+        Self {
+            time: system.params.t_start,
+            system,
+            state,
+            time_per_frame: 5.0,
+            running: true,
+            error_message: None,
+            textures: None,
+            source: None,
+        }
+    }
+
+    /// Starts again from the initial densities of `params`.
+    fn reset(&mut self, params: &LotkaVolterraParameters) {
+        self.system.params = *params;
+        self.state = self.system.initial_state();
+        self.time = params.t_start;
+        self.error_message = None;
+        self.source = Some(*params);
+    }
+
+    fn advance(&mut self) {
+        let t_end = self.time + self.time_per_frame;
+        match integrate_spatial(
+            &self.system,
+            self.state.clone(),
+            self.time,
+            t_end,
+            self.time_per_frame,
+        ) {
+            Ok(states) => {
+                if let Some((t, state)) = states.into_iter().last() {
+                    self.time = t;
+                    self.state = state;
+                }
+            }
+            Err(e) => {
+                self.running = false;
+                self.error_message = Some(format!("Error solving equations: {}", e));
+            }
+        }
+    }
+
+    /// Renders both species as color images, each scaled to its own density range.
+    fn heatmaps(&self) -> [egui::ColorImage; 2] {
+        let snapshot = self.system.snapshot(self.time, &self.state);
+        let image = |field: &[f64]| {
+            let min = field.iter().copied().fold(f64::INFINITY, f64::min);
+            let max = field.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            let pixels = field
+                .iter()
+                .map(|&v| {
+                    let c = density_color(v, min, max);
+                    egui::Color32::from_rgb(c.0, c.1, c.2)
+                })
+                .collect();
+            egui::ColorImage {
+                size: [snapshot.nx, snapshot.ny],
+                pixels,
+            }
+        };
+        [image(&snapshot.prey), image(&snapshot.predators)]
+    }
+
+    /// Shows the view for `params`, advancing the simulation while it plays.
+    pub fn show(&mut self, ui: &mut egui::Ui, params: &LotkaVolterraParameters) {
+        if self.source != Some(*params) {
+            self.reset(params);
+        }
+        if self.running {
+            self.advance();
+            ui.ctx().request_repaint();
+        }
+
+        let [prey_image, predator_image] = self.heatmaps();
+        let options = egui::TextureOptions::NEAREST;
+        match &mut self.textures {
+            Some([prey, predators]) => {
+                prey.set(prey_image, options);
+                predators.set(predator_image, options);
+            }
+            None => {
+                self.textures = Some([
+                    ui.ctx().load_texture("spatial_prey", prey_image, options),
+                    ui.ctx()
+                        .load_texture("spatial_predators", predator_image, options),
+                ]);
+            }
+        }
+
+        if let Some(ref msg) = self.error_message {
+            ui.colored_label(egui::Color32::RED, msg);
+            ui.add_space(10.0);
+        }
+
+        ui.horizontal(|ui| {
+            let label = if self.running { "Pause" } else { "Play" };
+            if ui.button(label).clicked() {
+                self.running = !self.running;
+            }
+            if ui.button("Step").clicked() {
+                self.advance();
+            }
+            if ui.button("Reset").clicked() {
+                self.reset(params);
+            }
+            ui.add(egui::Slider::new(&mut self.time_per_frame, 0.1..=50.0).text("Time per frame"));
+            ui.label(format!("t = {:.1}", self.time));
+        });
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::Slider::new(&mut self.system.spatial.prey_diffusion, 0.0..=10.0)
+                    .text("Prey Diffusion"),
+            );
+            ui.add(
+                egui::Slider::new(&mut self.system.spatial.predator_diffusion, 0.0..=10.0)
+                    .text("Predator Diffusion"),
+            );
+        });
+        ui.add_space(10.0);
+
+        if let Some(textures) = &self.textures {
+            ui.columns(2, |columns| {
+                for (ui, (texture, title)) in columns
+                    .iter_mut()
+                    .zip(textures.iter().zip(["Prey Density", "Predator Density"]))
+                {
+                    ui.vertical_centered(|ui| {
+                        ui.label(egui::RichText::new(title).size(14.0));
+                        let width = ui.available_width();
+                        let aspect = self.system.spatial.ny as f32 / self.system.spatial.nx as f32;
+                        let height = (width * aspect).max(40.0);
+                        ui.add(
+                            egui::Image::new(texture).fit_to_exact_size(egui::vec2(width, height)),
+                        );
+                    });
+                }
+            });
+        }
+    }
+}
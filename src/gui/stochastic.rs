@@ -0,0 +1,155 @@
+use crate::interventions::Schedule;
+use crate::models::LotkaVolterraParameters;
+use crate::stochastic::{FRAME, LotkaVolterraParams, StochasticStream, ThinnedPath, decimate};
+use crate::worker::Debouncer;
+use eframe::egui;
+use egui_plot::{Corner, Legend, Line, Plot, PlotPoints};
+use std::time::Duration;
+
+/// Most points kept for the phase path; longer runs are thinned as they stream in.
+const MAX_PLOT_POINTS: usize = 5000;
+
+/// Stochastic view of the application: streams a simulation of the shared parameters and
+/// draws its phase path as it grows, over the deterministic one.
+pub struct StochasticView {
+    dt: f64,                   // Time step of the stochastic simulation
+    phase_points: ThinnedPath, // Predator vs. prey of the stochastic path
+    stream: Option<StochasticStream>,
+    source: Option<(LotkaVolterraParameters, Schedule)>, // Inputs of the running simulation
+    steps_per_frame: usize,
+    running: bool,
+    debouncer: Debouncer,
+}
+
+impl Default for StochasticView {
+    fn default() -> Self {
+        Self {
+            dt: 0.001,
+            phase_points: ThinnedPath::new(MAX_PLOT_POINTS),
+            stream: None,
+            source: None,
+            steps_per_frame: 10000,
+            running: true,
+            debouncer: Debouncer::new(Duration::from_millis(150)),
+        }
+    }
+}
+
+impl StochasticView {
+    /// Restarts the simulation from the initial populations, dropping the old stream.
+    fn update_simulation(&mut self, params: &LotkaVolterraParameters, schedule: &Schedule) {
+        self.phase_points.clear();
+        self.stream = Some(StochasticStream::start(
+            LotkaVolterraParams::from_deterministic(params, self.dt),
+            schedule,
+            params.t_start,
+            self.steps_per_frame,
+            !self.running,
+        ));
+        self.source = Some((*params, schedule.clone()));
+    }
+
+    /// Shows the view for `params` and the scheduled interventions, restarting the
+    /// simulation once they settle after a change. `deterministic` is the deterministic
+    /// phase path drawn underneath.
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        params: &LotkaVolterraParameters,
+        schedule: &Schedule,
+        deterministic: &[[f64; 2]],
+    ) {
+        match &self.source {
+            None => self.update_simulation(params, schedule),
+            Some(source) if source.0 != *params || source.1 != *schedule => {
+                self.source = Some((*params, schedule.clone()));
+                self.debouncer.trigger();
+            }
+            _ => {}
+        }
+        if self.debouncer.ready() {
+            self.update_simulation(params, schedule);
+        }
+        let Some(stream) = &mut self.stream else {
+            return;
+        };
+        self.phase_points.extend(stream.receive());
+        if !stream.is_finished() || self.debouncer.is_pending() {
+            ui.ctx().request_repaint_after(FRAME);
+        }
+
+        // Playback controls
+        let mut restart = false;
+        ui.horizontal(|ui| {
+            let label = if self.running { "Pause" } else { "Play" };
+            if ui.button(label).clicked() {
+                self.running = !self.running;
+                stream.set_paused(!self.running);
+            }
+            if ui
+                .add_enabled(!self.running, egui::Button::new("Step"))
+                .clicked()
+            {
+                stream.step(self.steps_per_frame);
+            }
+            restart |= ui.button("Restart").clicked();
+            if ui
+                .add(
+                    egui::Slider::new(&mut self.steps_per_frame, 100..=200000)
+                        .logarithmic(true)
+                        .text("Speed (steps per frame)"),
+                )
+                .changed()
+            {
+                stream.set_speed(self.steps_per_frame);
+            }
+            restart |= ui
+                .add(
+                    egui::Slider::new(&mut self.dt, 0.0001..=0.01)
+                        .logarithmic(true)
+                        .text("dt (Time Step)"),
+                )
+                .changed();
+        });
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::ProgressBar::new(stream.progress())
+                    .show_percentage()
+                    .desired_width(200.0),
+            );
+            if let Some([prey, predators]) = self.phase_points.last() {
+                ui.label(format!(
+                    "t = {:.2}   prey = {}   predators = {}",
+                    params.t_start + self.phase_points.len().saturating_sub(1) as f64 * self.dt,
+                    prey,
+                    predators
+                ));
+            }
+        });
+        if restart {
+            self.update_simulation(params, schedule);
+        }
+        ui.add_space(10.0);
+
+        // Plot the stochastic phase plot, thinned so that long runs stay responsive
+        if !self.phase_points.is_empty() {
+            Plot::new("stochastic_phase_plot")
+                .legend(Legend::default().position(Corner::LeftTop))
+                .x_axis_label("Prey Population")
+                .y_axis_label("Predator Population")
+                .show(ui, |plot_ui| {
+                    plot_ui.line(
+                        Line::new(PlotPoints::from(decimate(deterministic, MAX_PLOT_POINTS)))
+                            .color(egui::Color32::GRAY)
+                            .name("Deterministic"),
+                    );
+                    plot_ui.line(
+                        Line::new(PlotPoints::from(self.phase_points.points()))
+                            .name("Stochastic Phase Plot"),
+                    );
+                });
+        } else {
+            ui.label("No data available for stochastic phase plot.");
+        }
+    }
+}
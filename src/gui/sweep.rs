@@ -0,0 +1,149 @@
+use crate::error::SimulationError;
+use crate::plot_config::PlotConfig;
+use crate::scenario::Scenario;
+use crate::sensitivity::{ParameterRange, ScalarOutput, parameter_sweep};
+use crate::worker::{Debouncer, Job};
+use eframe::egui;
+use egui_plot::{Corner, Legend, Line, Plot, PlotPoints, Points, VLine};
+use std::time::Duration;
+
+/// Inputs of a sweep: the scenario, the parameter, the range fraction, the number of
+/// points and the output.
+type SweepInputs = (Scenario, &'static str, f64, usize, ScalarOutput);
+
+/// Sweep view of the application: one output of the deterministic model against one
+/// parameter, varied around its value in the shared scenario.
+pub struct SweepView {
+    parameter: &'static str,
+    fraction: f64,
+    points: usize,
+    output: ScalarOutput,
+    sweep: Vec<[f64; 2]>,
+    job: Option<Job<Result<Vec<[f64; 2]>, SimulationError>>>,
+    source: Option<SweepInputs>,
+    debouncer: Debouncer,
+    error_message: Option<String>,
+}
+
+impl Default for SweepView {
+    fn default() -> Self {
+        Self {
+            parameter: "alpha",
+            fraction: 0.5,
+            points: 21,
+            output: ScalarOutput::Period,
+            sweep: Vec::new(),
+            job: None,
+            source: None,
+            debouncer: Debouncer::new(Duration::from_millis(300)),
+            error_message: None,
+        }
+    }
+}
+
+impl SweepView {
+    fn start(&mut self, (scenario, parameter, fraction, points, output): &SweepInputs) {
+        let value = scenario.params.get(parameter).unwrap_or_default();
+        let range = ParameterRange {
+            name: parameter.to_string(),
+            min: value * (1.0 - fraction),
+            max: value * (1.0 + fraction),
+        };
+        let (scenario, points, output) = (scenario.clone(), *points, *output);
+        self.job = Some(Job::spawn(move |_| {
+            parameter_sweep(&scenario, &range, points, output, 1.0)
+        }));
+    }
+
+    /// Shows the view for `scenario`, with its harvesting, forcing, interventions and
+    /// delay, rerunning the sweep once its inputs settle after a change.
+    pub fn show(&mut self, ui: &mut egui::Ui, scenario: &Scenario) {
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_label("Parameter")
+                .selected_text(self.parameter)
+                .show_ui(ui, |ui| {
+                    for name in [
+                        "alpha",
+                        "beta",
+                        "delta",
+                        "gamma",
+                        "initial_prey",
+                        "initial_predator",
+                    ] {
+                        ui.selectable_value(&mut self.parameter, name, name);
+                    }
+                });
+            egui::ComboBox::from_label("Output")
+                .selected_text(self.output.label())
+                .show_ui(ui, |ui| {
+                    for output in [ScalarOutput::Period, ScalarOutput::PeakPrey] {
+                        ui.selectable_value(&mut self.output, output, output.label());
+                    }
+                });
+            ui.add(egui::Slider::new(&mut self.fraction, 0.05..=0.95).text("Range (+/- fraction)"));
+            ui.add(egui::Slider::new(&mut self.points, 3..=101).text("Points"));
+        });
+
+        // The plot settings do not change the sweep
+        let scenario = Scenario {
+            plot: PlotConfig::default(),
+            ..scenario.clone()
+        };
+        let inputs = (
+            scenario,
+            self.parameter,
+            self.fraction,
+            self.points,
+            self.output,
+        );
+        match &self.source {
+            None => self.start(&inputs),
+            Some(source) if *source != inputs => self.debouncer.trigger(),
+            _ => {}
+        }
+        if self.debouncer.ready() {
+            self.start(&inputs);
+        }
+        let current = inputs.0.params.get(self.parameter).unwrap_or_default();
+        self.source = Some(inputs);
+        if let Some(result) = self.job.as_ref().and_then(Job::try_result) {
+            self.job = None;
+            match result {
+                Ok(sweep) => {
+                    self.sweep = sweep;
+                    self.error_message = None;
+                }
+                Err(e) => self.error_message = Some(e.to_string()),
+            }
+        }
+        if self.job.is_some() {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("Sweeping...");
+            });
+            ui.ctx().request_repaint_after(Duration::from_millis(50));
+        } else if let Some(remaining) = self.debouncer.remaining() {
+            ui.ctx().request_repaint_after(remaining);
+        }
+        if let Some(msg) = &self.error_message {
+            ui.colored_label(egui::Color32::RED, msg);
+        }
+
+        Plot::new("parameter_sweep")
+            .legend(Legend::default().position(Corner::LeftTop))
+            .x_axis_label(self.parameter)
+            .y_axis_label(self.output.label())
+            .show(ui, |plot_ui| {
+                plot_ui.vline(
+                    VLine::new(current)
+                        .color(egui::Color32::GRAY)
+                        .style(egui_plot::LineStyle::dashed_dense())
+                        .name("Current value"),
+                );
+                plot_ui.line(
+                    Line::new(PlotPoints::from(self.sweep.clone())).name(self.output.label()),
+                );
+                plot_ui.points(Points::new(self.sweep.clone()).radius(3.0));
+            });
+    }
+}
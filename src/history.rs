@@ -0,0 +1,116 @@
+/// Undo/redo history of states. Recording a state after undoing discards the states
+/// that could have been redone, and the oldest states are dropped beyond the capacity.
+#[derive(Debug, Clone)]
+pub struct History<T> {
+    entries: Vec<T>,
+    cursor: usize,
+    capacity: usize,
+}
+
+impl<T: PartialEq> History<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Vec::new(),
+            cursor: 0,
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Records a new current state. Returns false, recording nothing, if it equals the
+    /// current state.
+    pub fn record(&mut self, state: T) -> bool {
+        if self.current() == Some(&state) {
+            return false;
+        }
+        self.entries.truncate(self.cursor + 1);
+        self.entries.push(state);
+        if self.entries.len() > self.capacity {
+            self.entries.remove(0);
+        }
+        self.cursor = self.entries.len() - 1;
+        true
+    }
+
+    pub fn current(&self) -> Option<&T> {
+        self.entries.get(self.cursor)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.cursor > 0
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.cursor + 1 < self.entries.len()
+    }
+
+    /// Steps back to the previous state and returns it.
+    pub fn undo(&mut self) -> Option<&T> {
+        if !self.can_undo() {
+            return None;
+        }
+        self.cursor -= 1;
+        self.current()
+    }
+
+    /// Steps forward to the next state and returns it.
+    pub fn redo(&mut self) -> Option<&T> {
+        if !self.can_redo() {
+            return None;
+        }
+        self.cursor += 1;
+        self.current()
+    }
+
+    /// Jumps to the state at `index`, keeping the others so that it can be left again.
+    pub fn restore(&mut self, index: usize) -> Option<&T> {
+        if index >= self.entries.len() {
+            return None;
+        }
+        self.cursor = index;
+        self.current()
+    }
+
+    /// All states, oldest first.
+    pub fn entries(&self) -> &[T] {
+        &self.entries
+    }
+
+    /// Index of the current state in [`History::entries`].
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo_redo_and_branching() {
+        let mut history = History::new(10);
+        assert!(history.record(1) && history.record(2) && history.record(3));
+        assert!(!history.record(3), "Repeated states are not recorded.");
+
+        assert_eq!(history.undo(), Some(&2));
+        assert_eq!(history.undo(), Some(&1));
+        assert_eq!(history.undo(), None);
+        assert_eq!(history.redo(), Some(&2));
+
+        // Recording after undoing drops the redo branch
+        history.record(4);
+        assert_eq!(history.entries(), &[1, 2, 4]);
+        assert!(!history.can_redo());
+        assert_eq!(history.restore(0), Some(&1));
+        assert_eq!(history.redo(), Some(&2));
+    }
+
+    #[test]
+    fn test_capacity_drops_oldest() {
+        let mut history = History::new(3);
+        for state in 0..5 {
+            history.record(state);
+        }
+        assert_eq!(history.entries(), &[2, 3, 4]);
+        assert_eq!(history.cursor(), 2);
+    }
+}
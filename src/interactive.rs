@@ -1,13 +1,22 @@
 use crate::error::SimulationError;
 use crate::models::LotkaVolterraParameters;
-use crate::stochastic::launch_stochastic_gui;
+use crate::session::View;
 use dialoguer::{Input, Select};
 use std::error::Error;
 
+/// Option chosen in the interactive menu.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MenuChoice {
+    /// Simulate with the given parameters
+    Run(LotkaVolterraParameters),
+    /// Open the GUI on the given view
+    Gui(View),
+}
+
 /// Interactive mode displaying a menu for the user.
-/// Returns `Ok(Some(params))` if the user selects "Use default parameters" or "Enter custom parameters".
-/// Returns `Ok(None)` if the user selects "Interactive Plot" or "Interactive Stochastic Plot".
-pub fn interactive_mode() -> Result<Option<LotkaVolterraParameters>, Box<dyn Error>> {
+/// Returns `MenuChoice::Run` if the user selects "Use default parameters" or "Enter custom parameters".
+/// Returns `MenuChoice::Gui` if the user selects "Interactive Plot" or "Interactive Stochastic Plot".
+pub fn interactive_mode() -> Result<MenuChoice, Box<dyn Error>> {
     println!("\nWelcome to the Lotka-Volterra Simulation CLI!");
 
     let choices = &[
@@ -28,7 +37,7 @@ pub fn interactive_mode() -> Result<Option<LotkaVolterraParameters>, Box<dyn Err
             // Use default parameters
             let params = LotkaVolterraParameters::default();
             validate_params(&params)?;
-            Ok(Some(params))
+            Ok(MenuChoice::Run(params))
         }
         1 => {
             // Enter custom parameters
@@ -59,31 +68,19 @@ pub fn interactive_mode() -> Result<Option<LotkaVolterraParameters>, Box<dyn Err
                     .interact_text()?,
             };
             validate_params(&params)?;
-            Ok(Some(params))
-        }
-        2 => {
-            // Interactive deterministic Plot; skips confirmation and return None
-            Ok(None)
-        }
-        3 => {
-            // Interactive stochastic plot
-            println!("\nLaunching Stochastic Simulation GUI...\n");
-
-            if let Err(e) = launch_stochastic_gui() {
-                eprintln!("Error launching GUI: {}", e);
-                return Err(Box::new(e));
-            }
-
-            // Ensure the program exits after the GUI closes
-            std::process::exit(0);
+            Ok(MenuChoice::Run(params))
         }
+        // Interactive deterministic plot; skips confirmation
+        2 => Ok(MenuChoice::Gui(View::Deterministic)),
+        // Interactive stochastic plot, in the same application
+        3 => Ok(MenuChoice::Gui(View::Stochastic)),
         // This should never happen
         _ => unreachable!(),
     }
 }
 
 /// Validates the parameters given for the Lotka-Volterra differential equation.
-fn validate_params(params: &LotkaVolterraParameters) -> Result<(), SimulationError> {
+pub fn validate_params(params: &LotkaVolterraParameters) -> Result<(), SimulationError> {
     if params.alpha < 0.0
         || params.beta < 0.0
         || params.delta < 0.0
@@ -0,0 +1,213 @@
+use crate::error::SimulationError;
+use crate::models::{LotkaVolterraParameters, Species};
+use crate::stochastic::LotkaVolterraParams;
+
+/// A discrete change applied to the system at a scheduled time.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InterventionAction {
+    /// Adds individuals to a population (stocking); negative amounts remove them.
+    Add { species: Species, amount: f64 },
+    /// Removes a fraction of a population (culling).
+    Remove { species: Species, fraction: f64 },
+    /// Switches a model rate to a new value.
+    SetParameter { name: String, value: f64 },
+}
+
+/// An action applied at `time`, and then every `every` time units if given.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Intervention {
+    pub time: f64,
+    pub every: Option<f64>,
+    pub action: InterventionAction,
+}
+
+impl InterventionAction {
+    /// Rates that `set` interventions may switch. Times and initial populations cannot
+    /// change during a run.
+    pub const SETTABLE: [&'static str; 4] = ["alpha", "beta", "delta", "gamma"];
+
+    /// Applies the action to a deterministic state and parameter set.
+    pub fn apply(
+        &self,
+        params: &mut LotkaVolterraParameters,
+        state: &mut [f64; 2],
+    ) -> Result<(), SimulationError> {
+        match self {
+            InterventionAction::Add { species, amount } => {
+                let n = &mut state[species.index()];
+                *n = (*n + amount).max(0.0);
+            }
+            InterventionAction::Remove { species, fraction } => {
+                state[species.index()] *= 1.0 - fraction.clamp(0.0, 1.0);
+            }
+            InterventionAction::SetParameter { name, value } => params.set(name, *value)?,
+        }
+        Ok(())
+    }
+
+    /// Applies the action to a stochastic state. Populations stay whole numbers, and
+    /// parameter names follow the deterministic convention.
+    pub fn apply_stochastic(
+        &self,
+        params: &mut LotkaVolterraParams,
+        state: &mut [f64; 2],
+    ) -> Result<(), SimulationError> {
+        match self {
+            InterventionAction::Add { species, amount } => {
+                let n = &mut state[species.index()];
+                *n = (*n + amount.round()).max(0.0);
+            }
+            InterventionAction::Remove { species, fraction } => {
+                let n = &mut state[species.index()];
+                *n -= (*n * fraction.clamp(0.0, 1.0)).round();
+            }
+            InterventionAction::SetParameter { name, value } => {
+                let rate = match name.as_str() {
+                    "alpha" => &mut params.alpha,
+                    "beta" => &mut params.beta,
+                    "delta" => &mut params.gamma,
+                    "gamma" => &mut params.delta,
+                    _ => {
+                        return Err(SimulationError::InvalidParameter(format!(
+                            "Parameter '{}' cannot be changed during a stochastic run",
+                            name
+                        )));
+                    }
+                };
+                *rate = *value;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Collection of scheduled interventions.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Schedule {
+    pub interventions: Vec<Intervention>,
+}
+
+impl Schedule {
+    /// All intervention times in `[t0, t_end)`, in chronological order. Actions due at
+    /// the same time keep the order in which they were defined.
+    pub fn events(&self, t0: f64, t_end: f64) -> Vec<(f64, &InterventionAction)> {
+        let mut events = Vec::new();
+        for intervention in &self.interventions {
+            let mut time = intervention.time;
+            match intervention.every {
+                Some(every) if every > 0.0 => {
+                    while time < t_end {
+                        if time >= t0 {
+                            events.push((time, &intervention.action));
+                        }
+                        time += every;
+                    }
+                }
+                _ => {
+                    if time >= t0 && time < t_end {
+                        events.push((time, &intervention.action));
+                    }
+                }
+            }
+        }
+        events.sort_by(|a, b| a.0.total_cmp(&b.0));
+        events
+    }
+
+    /// Distinct intervention times in `[t0, t_end)`, used for plot markers.
+    pub fn times(&self, t0: f64, t_end: f64) -> Vec<f64> {
+        let mut times: Vec<f64> = self.events(t0, t_end).iter().map(|e| e.0).collect();
+        times.dedup();
+        times
+    }
+
+    /// Validates intervention times, fractions, repeat intervals and the names of the
+    /// rates they switch.
+    pub fn validate(&self) -> Result<(), SimulationError> {
+        for intervention in &self.interventions {
+            if let InterventionAction::SetParameter { name, .. } = &intervention.action
+                && !InterventionAction::SETTABLE.contains(&name.as_str())
+            {
+                return Err(SimulationError::InvalidParameter(format!(
+                    "Interventions can only set {}, not '{}'.",
+                    InterventionAction::SETTABLE.join(", "),
+                    name
+                )));
+            }
+            let bad_fraction = matches!(
+                intervention.action,
+                InterventionAction::Remove { fraction, .. } if !(0.0..=1.0).contains(&fraction)
+            );
+            if intervention.time < 0.0
+                || intervention.every.is_some_and(|every| every <= 0.0)
+                || bad_fraction
+            {
+                return Err(SimulationError::InvalidParameter(
+                    "Interventions need non-negative times, positive repeat intervals and fractions in [0, 1].".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeating_events_are_sorted() {
+        let schedule = Schedule {
+            interventions: vec![
+                Intervention {
+                    time: 250.0,
+                    every: None,
+                    action: InterventionAction::SetParameter {
+                        name: "alpha".to_string(),
+                        value: 0.02,
+                    },
+                },
+                Intervention {
+                    time: 200.0,
+                    every: Some(200.0),
+                    action: InterventionAction::Remove {
+                        species: Species::Predator,
+                        fraction: 0.3,
+                    },
+                },
+            ],
+        };
+        assert_eq!(schedule.times(0.0, 700.0), vec![200.0, 250.0, 400.0, 600.0]);
+    }
+
+    #[test]
+    fn test_apply_remove_fraction() {
+        let mut params = LotkaVolterraParameters::default();
+        let mut state = [100.0, 50.0];
+        InterventionAction::Remove {
+            species: Species::Predator,
+            fraction: 0.3,
+        }
+        .apply(&mut params, &mut state)
+        .unwrap();
+        assert_eq!(state, [100.0, 35.0]);
+    }
+
+    #[test]
+    fn test_only_rates_can_be_set() {
+        let set = |name: &str| Schedule {
+            interventions: vec![Intervention {
+                time: 10.0,
+                every: None,
+                action: InterventionAction::SetParameter {
+                    name: name.to_string(),
+                    value: 1.0,
+                },
+            }],
+        };
+        assert!(set("gamma").validate().is_ok());
+        for name in ["t_end", "initial_prey", "kappa"] {
+            assert!(set(name).validate().is_err(), "{} was accepted", name);
+        }
+    }
+}
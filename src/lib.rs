@@ -1,21 +1,69 @@
 //! Exposes modules for main.rs
 //! File structure
 //! src/
+//! ├── agents.rs
+//! ├── analysis.rs
+//! ├── animation.rs
 //! ├── bin/main.rs
 //! ├── cli.rs
+//! ├── continuation.rs
+//! ├── dde.rs
+//! ├── discrete.rs
 //! ├── error.rs
+//! ├── fitting.rs
 //! ├── gui.rs
+//! ├── gui/
+//! │   ├── agents.rs
+//! │   ├── ensemble.rs
+//! │   ├── fitting.rs
+//! │   ├── spatial.rs
+//! │   ├── stochastic.rs
+//! │   └── sweep.rs
+//! ├── history.rs
 //! ├── interactive.rs
+//! ├── interventions.rs
 //! ├── lib.rs
+//! ├── metapopulation.rs
 //! ├── models.rs
+//! ├── parallel.rs
+//! ├── phase.rs
 //! ├── plot.rs
+//! ├── plot_config.rs
+//! ├── report.rs
+//! ├── scenario.rs
+//! ├── sde.rs
+//! ├── sensitivity.rs
+//! ├── session.rs
 //! ├── solver.rs
+//! ├── spatial.rs
+//! ├── stochastic.rs
+//! ├── worker.rs
 
+pub mod agents;
+pub mod analysis;
+pub mod animation;
 pub mod cli;
+pub mod continuation;
+pub mod dde;
+pub mod discrete;
 pub mod error;
+pub mod fitting;
 pub mod gui;
+pub mod history;
 pub mod interactive;
+pub mod interventions;
+pub mod metapopulation;
 pub mod models;
+pub mod parallel;
+pub mod phase;
 pub mod plot;
+pub mod plot_config;
+pub mod report;
+pub mod scenario;
+pub mod sde;
+pub mod sensitivity;
+pub mod session;
 pub mod solver;
+pub mod spatial;
 pub mod stochastic;
+pub mod worker;
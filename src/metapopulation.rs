@@ -0,0 +1,272 @@
+use crate::error::SimulationError;
+use crate::models::{
+    ContinuousModel, LotkaVolterraParameters, PopulationModel, StochasticModel, Transition,
+    lotka_volterra_transitions,
+};
+use std::path::Path;
+
+/// Habitat patches connected by weighted, undirected migration routes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatchNetwork {
+    pub patches: usize,
+    pub edges: Vec<(usize, usize, f64)>,
+}
+
+impl PatchNetwork {
+    /// Reads an edge-list file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SimulationError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            SimulationError::InvalidParameter(format!("Cannot read {}: {}", path.display(), e))
+        })?;
+        Self::parse(&text)
+    }
+
+    /// Parses an edge list with one `from to [weight]` entry per line. Patches are
+    /// numbered from zero, the weight defaults to 1 and `#` starts a comment.
+    pub fn parse(text: &str) -> Result<Self, SimulationError> {
+        let mut edges = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let invalid = || {
+                SimulationError::InvalidParameter(format!(
+                    "line {}: expected 'from to [weight]'",
+                    number + 1
+                ))
+            };
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (from, to, weight) = match fields.as_slice() {
+                [from, to] => (from, to, "1"),
+                [from, to, weight] => (from, to, *weight),
+                _ => return Err(invalid()),
+            };
+            let from: usize = from.parse().map_err(|_| invalid())?;
+            let to: usize = to.parse().map_err(|_| invalid())?;
+            let weight: f64 = weight.parse().map_err(|_| invalid())?;
+            if weight < 0.0 {
+                return Err(invalid());
+            }
+            edges.push((from, to, weight));
+        }
+
+        let patches = edges
+            .iter()
+            .map(|&(from, to, _)| from.max(to) + 1)
+            .max()
+            .ok_or_else(|| {
+                SimulationError::InvalidParameter("The network has no edges.".to_string())
+            })?;
+        Ok(Self { patches, edges })
+    }
+}
+
+/// Local Lotka-Volterra dynamics in every patch, coupled by migration. The state holds
+/// the prey and predators of patch `i` at indices `2i` and `2i + 1`.
+#[derive(Debug, Clone)]
+pub struct Metapopulation {
+    pub params: LotkaVolterraParameters,
+    pub network: PatchNetwork,
+    pub prey_migration: f64, // Per-capita prey migration rate per unit weight
+    pub predator_migration: f64, // Per-capita predator migration rate per unit weight
+    pub initial_spread: f64, // Relative spread of initial prey across patches
+}
+
+impl Metapopulation {
+    pub fn new(
+        params: LotkaVolterraParameters,
+        network: PatchNetwork,
+        prey_migration: f64,
+        predator_migration: f64,
+    ) -> Self {
+        Self {
+            params,
+            network,
+            prey_migration,
+            predator_migration,
+            initial_spread: 0.25,
+        }
+    }
+
+    /// Migration routes in both directions.
+    fn routes(&self) -> impl Iterator<Item = (usize, usize, f64)> + '_ {
+        self.network
+            .edges
+            .iter()
+            .flat_map(|&(a, b, w)| [(a, b, w), (b, a, w)])
+            .filter(|&(a, b, _)| a != b)
+    }
+}
+
+impl PopulationModel for Metapopulation {
+    fn dimension(&self) -> usize {
+        2 * self.network.patches
+    }
+
+    /// Initial prey vary linearly across patches by +/- `initial_spread`, so that the
+    /// patches start out of phase; predators start at the same value everywhere.
+    fn initial_state(&self) -> Vec<f64> {
+        let n = self.network.patches;
+        (0..n)
+            .flat_map(|i| {
+                let offset = if n > 1 {
+                    2.0 * i as f64 / (n - 1) as f64 - 1.0
+                } else {
+                    0.0
+                };
+                [
+                    (self.params.initial_prey * (1.0 + self.initial_spread * offset)).round(),
+                    self.params.initial_predator,
+                ]
+            })
+            .collect()
+    }
+}
+
+impl ContinuousModel for Metapopulation {
+    fn derivative(&self, _t: f64, state: &[f64], dydt: &mut [f64]) {
+        let p = &self.params;
+        for i in 0..self.network.patches {
+            let (x, y) = (state[2 * i], state[2 * i + 1]);
+            dydt[2 * i] = p.alpha * x - p.beta * x * y;
+            dydt[2 * i + 1] = p.delta * x * y - p.gamma * y;
+        }
+        for (from, to, weight) in self.routes() {
+            for (offset, rate) in [(0, self.prey_migration), (1, self.predator_migration)] {
+                let flow = rate * weight * state[2 * from + offset];
+                dydt[2 * from + offset] -= flow;
+                dydt[2 * to + offset] += flow;
+            }
+        }
+    }
+}
+
+impl StochasticModel for Metapopulation {
+    fn transitions(&self, state: &[f64], out: &mut Vec<Transition>) {
+        for i in 0..self.network.patches {
+            lotka_volterra_transitions(
+                &self.params,
+                state[2 * i],
+                state[2 * i + 1],
+                2 * i,
+                2 * i + 1,
+                out,
+            );
+        }
+        for (from, to, weight) in self.routes() {
+            for (offset, rate) in [(0, self.prey_migration), (1, self.predator_migration)] {
+                out.push(Transition {
+                    rate: rate * weight * state[2 * from + offset],
+                    changes: [(2 * from + offset, -1.0), (2 * to + offset, 1.0)],
+                });
+            }
+        }
+    }
+}
+
+/// Splits metapopulation states into per-patch prey and predator series.
+pub fn patch_series(states: &[Vec<f64>], patches: usize) -> (Vec<Vec<f64>>, Vec<Vec<f64>>) {
+    let series = |offset: usize| {
+        (0..patches)
+            .map(|i| states.iter().map(|s| s[2 * i + offset]).collect())
+            .collect()
+    };
+    (series(0), series(1))
+}
+
+/// Sums per-patch series into a global series.
+pub fn global_series(series: &[Vec<f64>]) -> Vec<f64> {
+    let len = series.first().map_or(0, Vec::len);
+    (0..len)
+        .map(|k| series.iter().map(|s| s[k]).sum())
+        .collect()
+}
+
+fn correlation(a: &[f64], b: &[f64]) -> f64 {
+    let n = a.len().min(b.len()) as f64;
+    let (mean_a, mean_b) = (a.iter().sum::<f64>() / n, b.iter().sum::<f64>() / n);
+    let (mut cov, mut var_a, mut var_b) = (0.0, 0.0, 0.0);
+    for (x, y) in a.iter().zip(b) {
+        cov += (x - mean_a) * (y - mean_b);
+        var_a += (x - mean_a).powi(2);
+        var_b += (y - mean_b).powi(2);
+    }
+    if var_a > 0.0 && var_b > 0.0 {
+        cov / (var_a * var_b).sqrt()
+    } else {
+        // Constant series move together trivially
+        1.0
+    }
+}
+
+/// Synchrony of the patches: the mean pairwise Pearson correlation of their series.
+/// Values near 1 mean the patches oscillate in phase.
+pub fn synchrony(series: &[Vec<f64>]) -> f64 {
+    let mut total = 0.0;
+    let mut pairs = 0;
+    for i in 0..series.len() {
+        for j in i + 1..series.len() {
+            total += correlation(&series[i], &series[j]);
+            pairs += 1;
+        }
+    }
+    if pairs == 0 {
+        1.0
+    } else {
+        total / pairs as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::solve_model;
+
+    fn test_params() -> LotkaVolterraParameters {
+        LotkaVolterraParameters {
+            alpha: 0.1,
+            beta: 0.02,
+            gamma: 0.02,
+            delta: 0.1,
+            initial_prey: 40.0,
+            initial_predator: 9.0,
+            t_start: 0.0,
+            t_end: 50.0,
+        }
+    }
+
+    #[test]
+    fn test_parse_network() {
+        let network = PatchNetwork::parse("# ring\n0 1 0.5\n1 2\n2 0 2.0").unwrap();
+        assert_eq!(network.patches, 3);
+        assert_eq!(network.edges[1], (1, 2, 1.0));
+        assert!(PatchNetwork::parse("0 x").is_err());
+    }
+
+    #[test]
+    fn test_migration_conserves_individuals() {
+        let params = LotkaVolterraParameters {
+            alpha: 0.0,
+            beta: 0.0,
+            gamma: 0.0,
+            delta: 0.0,
+            ..test_params()
+        };
+        let network = PatchNetwork::parse("0 1\n1 2").unwrap();
+        let model = Metapopulation::new(params, network, 0.1, 0.1);
+        let (_, states) = solve_model(&model, 0.0, 50.0, 1.0).unwrap();
+        let (prey, _) = patch_series(&states, 3);
+        let total = global_series(&prey);
+
+        assert!((total[0] - total.last().unwrap()).abs() < 1e-6);
+        assert!((prey[0].last().unwrap() - prey[2].last().unwrap()).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_synchrony_of_identical_series() {
+        let series = vec![vec![1.0, 2.0, 3.0], vec![2.0, 4.0, 6.0]];
+        assert!((synchrony(&series) - 1.0).abs() < 1e-12);
+    }
+}
@@ -1,7 +1,8 @@
+use crate::error::SimulationError;
 use ode_solvers::SVector;
 
 /// The parameters required for solving the Lotka-Volterra differential equation.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct LotkaVolterraParameters {
     pub alpha: f64,            // Prey birth rate
     pub beta: f64,             // Prety death rate
@@ -29,23 +30,355 @@ impl Default for LotkaVolterraParameters {
     }
 }
 
+impl LotkaVolterraParameters {
+    /// Names accepted by [`LotkaVolterraParameters::get`] and [`LotkaVolterraParameters::set`].
+    pub const NAMES: [&'static str; 8] = [
+        "alpha",
+        "beta",
+        "delta",
+        "gamma",
+        "initial_prey",
+        "initial_predator",
+        "t_start",
+        "t_end",
+    ];
+
+    /// Looks up a parameter by name.
+    pub fn get(&self, name: &str) -> Result<f64, SimulationError> {
+        match name {
+            "alpha" => Ok(self.alpha),
+            "beta" => Ok(self.beta),
+            "delta" => Ok(self.delta),
+            "gamma" => Ok(self.gamma),
+            "initial_prey" => Ok(self.initial_prey),
+            "initial_predator" => Ok(self.initial_predator),
+            "t_start" => Ok(self.t_start),
+            "t_end" => Ok(self.t_end),
+            _ => Err(SimulationError::InvalidParameter(format!(
+                "Unknown parameter '{}'",
+                name
+            ))),
+        }
+    }
+
+    /// Sets a parameter by name.
+    pub fn set(&mut self, name: &str, value: f64) -> Result<(), SimulationError> {
+        let field = match name {
+            "alpha" => &mut self.alpha,
+            "beta" => &mut self.beta,
+            "delta" => &mut self.delta,
+            "gamma" => &mut self.gamma,
+            "initial_prey" => &mut self.initial_prey,
+            "initial_predator" => &mut self.initial_predator,
+            "t_start" => &mut self.t_start,
+            "t_end" => &mut self.t_end,
+            _ => {
+                return Err(SimulationError::InvalidParameter(format!(
+                    "Unknown parameter '{}'",
+                    name
+                )));
+            }
+        };
+        *field = value;
+        Ok(())
+    }
+}
+
 pub type State = SVector<f64, 2>;
 
+/// Population a harvest or intervention applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Species {
+    Prey,
+    Predator,
+}
+
+impl Species {
+    /// Index of the species in the state vector.
+    pub fn index(&self) -> usize {
+        match self {
+            Species::Prey => 0,
+            Species::Predator => 1,
+        }
+    }
+}
+
+/// How a population is harvested.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HarvestStrategy {
+    /// Removes a fixed fraction of the population per unit time (`-E * x`).
+    ConstantEffort(f64),
+    /// Removes a fixed number of individuals per unit time while any remain (`-Q`).
+    ConstantQuota(f64),
+}
+
+/// Harvesting of one species over the interval `[start, end]`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Harvest {
+    pub species: Species,
+    pub strategy: HarvestStrategy,
+    pub start: f64,
+    pub end: f64,
+}
+
+impl Harvest {
+    /// Removal rate at time `t` for a population of size `n`.
+    pub fn rate(&self, t: f64, n: f64) -> f64 {
+        if t < self.start || t > self.end || n <= 0.0 {
+            return 0.0;
+        }
+        match self.strategy {
+            HarvestStrategy::ConstantEffort(effort) => effort * n,
+            HarvestStrategy::ConstantQuota(quota) => quota,
+        }
+    }
+}
+
+/// Model rate that can be driven by external forcing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rate {
+    Alpha,
+    Beta,
+    Delta,
+    Gamma,
+}
+
+impl Rate {
+    pub const ALL: [Rate; 4] = [Rate::Alpha, Rate::Beta, Rate::Delta, Rate::Gamma];
+
+    /// Parameter name of the rate.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rate::Alpha => "alpha",
+            Rate::Beta => "beta",
+            Rate::Delta => "delta",
+            Rate::Gamma => "gamma",
+        }
+    }
+}
+
+/// Seasonal forcing of a rate: `rate(t) = rate * (1 + amplitude * sin(2 pi t / period + phase))`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Forcing {
+    pub rate: Rate,
+    pub amplitude: f64,
+    pub period: f64,
+    pub phase: f64,
+}
+
+impl Forcing {
+    /// Multiplier applied to the base rate at time `t`.
+    pub fn factor(&self, t: f64) -> f64 {
+        1.0 + self.amplitude * (2.0 * std::f64::consts::PI * t / self.period + self.phase).sin()
+    }
+}
+
+/// Harvesting and forcing terms added on top of the classic model.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExternalTerms {
+    pub harvests: Vec<Harvest>,
+    pub forcings: Vec<Forcing>,
+}
+
+impl ExternalTerms {
+    /// Rates at time `t` after applying all forcings.
+    pub fn forced_rates(&self, params: &LotkaVolterraParameters, t: f64) -> [f64; 4] {
+        let mut rates = [params.alpha, params.beta, params.delta, params.gamma];
+        for forcing in &self.forcings {
+            rates[forcing.rate as usize] *= forcing.factor(t);
+        }
+        rates
+    }
+
+    /// Total removal rate of a species at time `t`.
+    pub fn harvest_rate(&self, species: Species, t: f64, n: f64) -> f64 {
+        self.harvests
+            .iter()
+            .filter(|h| h.species == species)
+            .map(|h| h.rate(t, n))
+            .sum()
+    }
+
+    /// Validates harvest intervals, rates and forcing periods.
+    pub fn validate(&self) -> Result<(), SimulationError> {
+        for harvest in &self.harvests {
+            let rate = match harvest.strategy {
+                HarvestStrategy::ConstantEffort(e) => e,
+                HarvestStrategy::ConstantQuota(q) => q,
+            };
+            if rate < 0.0 || harvest.end < harvest.start {
+                return Err(SimulationError::InvalidParameter(
+                    "Harvest rates must be non-negative and end after they start.".to_string(),
+                ));
+            }
+        }
+        if self.forcings.iter().any(|f| f.period <= 0.0) {
+            return Err(SimulationError::InvalidParameter(
+                "Forcing periods must be positive.".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A population model with a fixed number of state variables. Models describe their
+/// dynamics through [`ContinuousModel`] and/or [`StochasticModel`].
+pub trait PopulationModel {
+    /// Number of state variables.
+    fn dimension(&self) -> usize;
+
+    /// State at the start of the simulation.
+    fn initial_state(&self) -> Vec<f64>;
+}
+
+/// Model given by ordinary differential equations.
+pub trait ContinuousModel: PopulationModel {
+    /// Writes the time derivative of `state` at time `t` into `dydt`.
+    fn derivative(&self, t: f64, state: &[f64], dydt: &mut [f64]);
+}
+
+/// A demographic event: its rate, and up to two state changes it causes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transition {
+    pub rate: f64,
+    pub changes: [(usize, f64); 2],
+}
+
+/// Model given by demographic events with state-dependent rates.
+pub trait StochasticModel: PopulationModel {
+    /// Appends the events possible in `state` to `out`.
+    fn transitions(&self, state: &[f64], out: &mut Vec<Transition>);
+}
+
+/// Model with non-overlapping generations, advanced one generation at a time.
+pub trait DiscreteModel: PopulationModel {
+    /// Writes the state of the next generation into `next`.
+    fn next_generation(&self, state: &[f64], next: &mut [f64]);
+}
+
 pub struct LotkaVolterraSystem {
     pub params: LotkaVolterraParameters,
+    pub terms: ExternalTerms,
 }
 
 /// Creates the Lotka-Volterra system with the provided parameters.
 impl LotkaVolterraSystem {
     pub fn new(params: LotkaVolterraParameters) -> Self {
-        Self { params }
+        Self::with_terms(params, ExternalTerms::default())
+    }
+
+    /// Creates the system with harvesting and forcing terms.
+    pub fn with_terms(params: LotkaVolterraParameters, terms: ExternalTerms) -> Self {
+        Self { params, terms }
     }
 }
 
 /// Solves the differential equation.
 impl ode_solvers::System<f64, State> for LotkaVolterraSystem {
-    fn system(&self, _t: f64, y: &State, dydt: &mut State) {
-        dydt[0] = self.params.alpha * y[0] - self.params.beta * y[0] * y[1];
-        dydt[1] = self.params.delta * y[0] * y[1] - self.params.gamma * y[1];
+    fn system(&self, t: f64, y: &State, dydt: &mut State) {
+        let [alpha, beta, delta, gamma] = self.terms.forced_rates(&self.params, t);
+        dydt[0] =
+            alpha * y[0] - beta * y[0] * y[1] - self.terms.harvest_rate(Species::Prey, t, y[0]);
+        dydt[1] = delta * y[0] * y[1]
+            - gamma * y[1]
+            - self.terms.harvest_rate(Species::Predator, t, y[1]);
+    }
+}
+
+impl PopulationModel for LotkaVolterraSystem {
+    fn dimension(&self) -> usize {
+        2
+    }
+
+    fn initial_state(&self) -> Vec<f64> {
+        vec![self.params.initial_prey, self.params.initial_predator]
+    }
+}
+
+impl ContinuousModel for LotkaVolterraSystem {
+    fn derivative(&self, t: f64, state: &[f64], dydt: &mut [f64]) {
+        let mut out = State::zeros();
+        ode_solvers::System::system(self, t, &State::new(state[0], state[1]), &mut out);
+        dydt.copy_from_slice(out.as_slice());
+    }
+}
+
+/// Prey births, predation, predator births and predator deaths. Harvesting and
+/// forcing terms are not part of the event model.
+impl StochasticModel for LotkaVolterraSystem {
+    fn transitions(&self, state: &[f64], out: &mut Vec<Transition>) {
+        lotka_volterra_transitions(&self.params, state[0], state[1], 0, 1, out);
+    }
+}
+
+/// Appends the four Lotka-Volterra events for prey at `prey_index` and predators at
+/// `predator_index`. Shared by every model built from local Lotka-Volterra dynamics.
+pub fn lotka_volterra_transitions(
+    params: &LotkaVolterraParameters,
+    prey: f64,
+    predators: f64,
+    prey_index: usize,
+    predator_index: usize,
+    out: &mut Vec<Transition>,
+) {
+    let single = |index, change| [(index, change), (index, 0.0)];
+    out.push(Transition {
+        rate: params.alpha * prey,
+        changes: single(prey_index, 1.0),
+    });
+    out.push(Transition {
+        rate: params.beta * prey * predators,
+        changes: single(prey_index, -1.0),
+    });
+    out.push(Transition {
+        rate: params.delta * prey * predators,
+        changes: single(predator_index, 1.0),
+    });
+    out.push(Transition {
+        rate: params.gamma * predators,
+        changes: single(predator_index, -1.0),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_and_get_by_name() {
+        let mut params = LotkaVolterraParameters::default();
+        params.set("gamma", 0.5).unwrap();
+        assert_eq!(params.get("gamma").unwrap(), 0.5);
+        assert!(params.set("omega", 1.0).is_err());
+    }
+
+    #[test]
+    fn test_harvest_only_inside_interval() {
+        let harvest = Harvest {
+            species: Species::Prey,
+            strategy: HarvestStrategy::ConstantEffort(0.1),
+            start: 10.0,
+            end: 20.0,
+        };
+        assert_eq!(harvest.rate(5.0, 100.0), 0.0);
+        assert_eq!(harvest.rate(15.0, 100.0), 10.0);
+        assert_eq!(harvest.rate(15.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn test_forcing_uses_time() {
+        let terms = ExternalTerms {
+            harvests: Vec::new(),
+            forcings: vec![Forcing {
+                rate: Rate::Alpha,
+                amplitude: 0.5,
+                period: 4.0,
+                phase: 0.0,
+            }],
+        };
+        let params = LotkaVolterraParameters::default();
+        let rates = terms.forced_rates(&params, 1.0);
+        assert!((rates[0] - 1.5 * params.alpha).abs() < 1e-12);
+        assert_eq!(rates[3], params.gamma);
     }
 }
@@ -0,0 +1,338 @@
+use crate::dde::solve_run;
+use crate::error::SimulationError;
+use crate::interventions::{InterventionAction, Schedule};
+use crate::models::{ExternalTerms, HarvestStrategy, LotkaVolterraParameters, Species};
+use crate::parallel::par_map;
+
+/// A straight nullcline segment, on which the growth rate of `species` vanishes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Nullcline {
+    pub species: Species,
+    pub from: [f64; 2],
+    pub to: [f64; 2],
+}
+
+/// The parameters of the classic model that has the same vector field as the model with
+/// `terms` and `schedule`, or `None` when the field changes during the run. Constant
+/// effort harvesting over the whole run lowers the prey birth rate or raises the
+/// predator death rate by the effort; forcing, other harvesting and interventions that
+/// switch a rate make the field change in time. Stocking and culling only move the state.
+pub fn autonomous_params(
+    params: &LotkaVolterraParameters,
+    terms: &ExternalTerms,
+    schedule: &Schedule,
+) -> Option<LotkaVolterraParameters> {
+    let switches_rate = schedule
+        .interventions
+        .iter()
+        .any(|i| matches!(i.action, InterventionAction::SetParameter { .. }));
+    if !terms.forcings.is_empty() || switches_rate {
+        return None;
+    }
+    let mut params = *params;
+    for harvest in &terms.harvests {
+        let HarvestStrategy::ConstantEffort(effort) = harvest.strategy else {
+            return None;
+        };
+        if harvest.start > params.t_start || harvest.end < params.t_end {
+            return None;
+        }
+        match harvest.species {
+            Species::Prey => params.alpha -= effort,
+            Species::Predator => params.gamma += effort,
+        }
+    }
+    Some(params)
+}
+
+/// Time derivative of (prey, predators) at a point of the phase plane.
+pub fn velocity(params: &LotkaVolterraParameters, [x, y]: [f64; 2]) -> [f64; 2] {
+    [
+        params.alpha * x - params.beta * x * y,
+        params.delta * x * y - params.gamma * y,
+    ]
+}
+
+/// Equilibria of the model: extinction and, when the rates allow it, coexistence.
+pub fn equilibria(params: &LotkaVolterraParameters) -> Vec<[f64; 2]> {
+    let mut points = vec![[0.0, 0.0]];
+    if params.beta > 0.0 && params.delta > 0.0 {
+        points.push([params.gamma / params.delta, params.alpha / params.beta]);
+    }
+    points
+}
+
+/// Jacobian of the vector field at a point of the phase plane.
+pub fn jacobian(params: &LotkaVolterraParameters, [x, y]: [f64; 2]) -> [[f64; 2]; 2] {
+    [
+        [params.alpha - params.beta * y, -params.beta * x],
+        [params.delta * y, params.delta * x - params.gamma],
+    ]
+}
+
+/// Local type of an equilibrium, from the eigenvalues of its Jacobian.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EquilibriumKind {
+    Saddle,
+    Center,
+    StableNode,
+    UnstableNode,
+    StableFocus,
+    UnstableFocus,
+}
+
+impl EquilibriumKind {
+    /// Description for reports.
+    pub fn describe(&self) -> &'static str {
+        match self {
+            EquilibriumKind::Saddle => "saddle (unstable)",
+            EquilibriumKind::Center => "center (neutrally stable cycles)",
+            EquilibriumKind::StableNode => "stable node",
+            EquilibriumKind::UnstableNode => "unstable node",
+            EquilibriumKind::StableFocus => "stable focus",
+            EquilibriumKind::UnstableFocus => "unstable focus",
+        }
+    }
+}
+
+/// An equilibrium with the eigenvalues `(re, im)` of its Jacobian and its type.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EquilibriumAnalysis {
+    pub point: [f64; 2],
+    pub eigenvalues: [(f64, f64); 2],
+    pub kind: EquilibriumKind,
+}
+
+impl EquilibriumAnalysis {
+    /// Period `2 pi / im` of the small oscillations around a center or focus.
+    pub fn linear_period(&self) -> Option<f64> {
+        let im = self.eigenvalues[0].1.abs();
+        (im > 0.0).then(|| 2.0 * std::f64::consts::PI / im)
+    }
+}
+
+/// Linear stability analysis of every equilibrium.
+pub fn analyze_equilibria(params: &LotkaVolterraParameters) -> Vec<EquilibriumAnalysis> {
+    equilibria(params)
+        .into_iter()
+        .map(|point| {
+            let j = jacobian(params, point);
+            let trace = j[0][0] + j[1][1];
+            let det = j[0][0] * j[1][1] - j[0][1] * j[1][0];
+            let disc = trace * trace / 4.0 - det;
+            let scale = trace.abs().max(det.abs().sqrt()).max(f64::MIN_POSITIVE);
+            let (eigenvalues, kind) = if disc >= 0.0 {
+                let root = disc.sqrt();
+                let kind = if det < 0.0 {
+                    EquilibriumKind::Saddle
+                } else if trace < 0.0 {
+                    EquilibriumKind::StableNode
+                } else {
+                    EquilibriumKind::UnstableNode
+                };
+                ([(trace / 2.0 + root, 0.0), (trace / 2.0 - root, 0.0)], kind)
+            } else {
+                let root = (-disc).sqrt();
+                let kind = if trace.abs() < 1e-12 * scale {
+                    EquilibriumKind::Center
+                } else if trace < 0.0 {
+                    EquilibriumKind::StableFocus
+                } else {
+                    EquilibriumKind::UnstableFocus
+                };
+                ([(trace / 2.0, root), (trace / 2.0, -root)], kind)
+            };
+            EquilibriumAnalysis {
+                point,
+                eigenvalues,
+                kind,
+            }
+        })
+        .collect()
+}
+
+/// Nullclines clipped to the box `[0, max[0]] x [0, max[1]]`. The prey nullclines are
+/// `x = 0` and `y = alpha / beta`; the predator nullclines are `y = 0` and
+/// `x = gamma / delta`.
+pub fn nullclines(params: &LotkaVolterraParameters, max: [f64; 2]) -> Vec<Nullcline> {
+    let mut lines = vec![
+        Nullcline {
+            species: Species::Prey,
+            from: [0.0, 0.0],
+            to: [0.0, max[1]],
+        },
+        Nullcline {
+            species: Species::Predator,
+            from: [0.0, 0.0],
+            to: [max[0], 0.0],
+        },
+    ];
+    if params.beta > 0.0 {
+        let y = params.alpha / params.beta;
+        lines.push(Nullcline {
+            species: Species::Prey,
+            from: [0.0, y],
+            to: [max[0], y],
+        });
+    }
+    if params.delta > 0.0 {
+        let x = params.gamma / params.delta;
+        lines.push(Nullcline {
+            species: Species::Predator,
+            from: [x, 0.0],
+            to: [x, max[1]],
+        });
+    }
+    lines
+}
+
+/// Direction field on an `n x n` grid over the box `[min, max]`. Each entry holds the
+/// grid point and the tip of an arrow of fixed length along the local flow, so the
+/// arrows show direction only.
+pub fn direction_field(
+    params: &LotkaVolterraParameters,
+    min: [f64; 2],
+    max: [f64; 2],
+    n: usize,
+) -> Vec<([f64; 2], [f64; 2])> {
+    let cell = [(max[0] - min[0]) / n as f64, (max[1] - min[1]) / n as f64];
+    let mut arrows = Vec::with_capacity(n * n);
+    for i in 0..n {
+        for j in 0..n {
+            let origin = [
+                min[0] + (i as f64 + 0.5) * cell[0],
+                min[1] + (j as f64 + 0.5) * cell[1],
+            ];
+            let v = velocity(params, origin);
+            // Scales each component to the cell size so both axes are comparable
+            let scaled = [v[0] / cell[0], v[1] / cell[1]];
+            let norm = scaled[0].hypot(scaled[1]);
+            if norm == 0.0 || !norm.is_finite() {
+                continue;
+            }
+            let length = 0.4;
+            arrows.push((
+                origin,
+                [
+                    origin[0] + length * cell[0] * scaled[0] / norm,
+                    origin[1] + length * cell[1] * scaled[1] / norm,
+                ],
+            ));
+        }
+    }
+    arrows
+}
+
+/// Solves one orbit per initial condition, in parallel, over the parameters' time span
+/// with the harvesting, forcing and interventions of the run, or with the maturation
+/// delay `delay` if given.
+pub fn phase_orbits(
+    params: &LotkaVolterraParameters,
+    terms: &ExternalTerms,
+    schedule: &Schedule,
+    delay: Option<f64>,
+    initials: &[[f64; 2]],
+    step: f64,
+) -> Result<Vec<Vec<[f64; 2]>>, SimulationError> {
+    par_map(initials, |&[x, y]| {
+        let start = LotkaVolterraParameters {
+            initial_prey: x,
+            initial_predator: y,
+            ..*params
+        };
+        let (_, prey, predators) = solve_run(&start, terms, schedule, delay, step)?;
+        Ok(prey
+            .into_iter()
+            .zip(predators)
+            .map(|(x, y)| [x, y])
+            .collect())
+    })
+    .into_iter()
+    .collect()
+}
+
+/// Upper corner of a box containing the orbits and the equilibria of `field`, if given,
+/// with a margin.
+pub fn portrait_extent(
+    field: Option<&LotkaVolterraParameters>,
+    orbits: &[Vec<[f64; 2]>],
+) -> [f64; 2] {
+    let mut max = [1.0_f64, 1.0_f64];
+    let equilibria = field.map(equilibria).unwrap_or_default();
+    for point in orbits.iter().flatten().chain(&equilibria) {
+        max[0] = max[0].max(point[0]);
+        max[1] = max[1].max(point[1]);
+    }
+    [max[0] * 1.1, max[1] * 1.1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flow_vanishes_at_equilibria() {
+        let params = LotkaVolterraParameters::default();
+        for point in equilibria(&params) {
+            let v = velocity(&params, point);
+            assert!(v[0].abs() < 1e-12 && v[1].abs() < 1e-12);
+        }
+        let coexistence = equilibria(&params)[1];
+        assert!((coexistence[0] - 1000.0).abs() < 1e-9 && (coexistence[1] - 1000.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_direction_field_follows_flow() {
+        let params = LotkaVolterraParameters::default();
+        let arrows = direction_field(&params, [0.0, 0.0], [2000.0, 2000.0], 4);
+        assert_eq!(arrows.len(), 16);
+
+        // Below both nullclines the prey grow and the predators decline
+        let (origin, tip) = arrows[0];
+        assert!(tip[0] > origin[0] && tip[1] < origin[1]);
+    }
+
+    #[test]
+    fn test_equilibrium_analysis() {
+        let params = LotkaVolterraParameters::default();
+        let analysis = analyze_equilibria(&params);
+
+        assert_eq!(analysis[0].kind, EquilibriumKind::Saddle);
+        assert_eq!(analysis[1].kind, EquilibriumKind::Center);
+        // Small oscillations have period 2 pi / sqrt(alpha gamma)
+        let period = analysis[1].linear_period().unwrap();
+        assert!((period - 2.0 * std::f64::consts::PI / 0.01).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_constant_effort_harvesting_moves_equilibrium() {
+        use crate::models::{Forcing, Harvest, Rate};
+
+        let params = LotkaVolterraParameters::default();
+        let mut terms = ExternalTerms {
+            harvests: vec![Harvest {
+                species: Species::Prey,
+                strategy: HarvestStrategy::ConstantEffort(0.002),
+                start: params.t_start,
+                end: params.t_end,
+            }],
+            forcings: Vec::new(),
+        };
+        let schedule = Schedule::default();
+        let autonomous = autonomous_params(&params, &terms, &schedule).unwrap();
+        // Fewer prey births leave fewer predators at coexistence
+        let coexistence = equilibria(&autonomous)[1];
+        assert!((coexistence[1] - 0.008 / params.beta).abs() < 1e-9);
+
+        terms.harvests[0].end = params.t_end / 2.0;
+        assert_eq!(autonomous_params(&params, &terms, &schedule), None);
+        terms.harvests.clear();
+        terms.forcings.push(Forcing {
+            rate: Rate::Alpha,
+            amplitude: 0.1,
+            period: 100.0,
+            phase: 0.0,
+        });
+        assert_eq!(autonomous_params(&params, &terms, &schedule), None);
+    }
+}
@@ -1,36 +1,1156 @@
+use crate::analysis::{conserved_drift, summarize};
+use crate::continuation::{Bifurcation, BifurcationKind, BranchPoint};
+use crate::models::{Harvest, LotkaVolterraParameters, Species};
+use crate::phase::{direction_field, equilibria, nullclines, portrait_extent};
+use crate::plot_config::{LegendPosition, PlotConfig, PlotFormat};
+use crate::sensitivity::IndexTable;
+use crate::spatial::SpatialSnapshot;
+use plotters::coord::ranged1d::{AsRangedCoord, ValueFormatter};
 use plotters::prelude::*;
 use std::error::Error;
 
-/// Statically plots the solution of the Lotka-Volterra system.
-pub fn plot_lotka_volterra(
+/// Statically plots the solution with the given appearance, shading the intervals in
+/// which harvesting is active. The file is written in the configured format.
+pub fn plot_time_series(
     times: &[f64],
     prey: &[f64],
     predators: &[f64],
+    harvests: &[Harvest],
+    config: &PlotConfig,
     output_file: &str,
 ) -> Result<(), Box<dyn Error>> {
+    let size = (config.width, config.height);
+    match config.format {
+        PlotFormat::Png => {
+            let root = BitMapBackend::new(output_file, size).into_drawing_area();
+            draw_time_series(&root, times, prey, predators, harvests, config)?;
+            root.present()?;
+        }
+        PlotFormat::Svg => {
+            let root = SVGBackend::new(output_file, size).into_drawing_area();
+            draw_time_series(&root, times, prey, predators, harvests, config)?;
+            root.present()?;
+        }
+    }
+    Ok(())
+}
+
+/// Axis ranges of a time-series plot: the configured ones, or ranges fitted to the
+/// data. Fitted linear ranges start at zero; logarithmic ones span the positive values.
+fn time_series_ranges(
+    times: &[f64],
+    populations: &[&[f64]],
+    config: &PlotConfig,
+) -> Result<[(f64, f64); 2], Box<dyn Error>> {
+    let x_range = config.x_range.unwrap_or_else(|| {
+        let start = times.first().copied().unwrap_or(0.0);
+        let end = times.last().copied().unwrap_or(1.0);
+        (start, if end > start { end } else { start + 1.0 })
+    });
+    let values = || {
+        populations
+            .iter()
+            .flat_map(|series| series.iter().copied())
+            .filter(|v| v.is_finite())
+    };
+    let max = values().fold(0.0_f64, f64::max);
+    let y_range = match config.y_range {
+        Some((min, _)) if config.log_y && min <= 0.0 => {
+            return Err("a logarithmic y range must be positive".into());
+        }
+        Some(range) => range,
+        None if config.log_y => {
+            let min = values().filter(|&v| v > 0.0).fold(f64::INFINITY, f64::min);
+            if min.is_finite() {
+                (min * 0.8, (max * 1.25).max(min * 10.0))
+            } else {
+                (1.0, 10.0)
+            }
+        }
+        None => (0.0, if max > 0.0 { max * 1.05 } else { 1.0 }),
+    };
+    Ok([x_range, y_range])
+}
+
+fn draw_time_series<DB: DrawingBackend>(
+    root: &DrawingArea<DB, plotters::coord::Shift>,
+    times: &[f64],
+    prey: &[f64],
+    predators: &[f64],
+    harvests: &[Harvest],
+    config: &PlotConfig,
+) -> Result<(), Box<dyn Error>>
+where
+    DB::ErrorType: 'static,
+{
+    root.fill(&config.theme.background())?;
+    let [x_range, y_range] = time_series_ranges(times, &[prey, predators], config)?;
+    let x_spec = x_range.0..x_range.1;
+    let y_spec = y_range.0..y_range.1;
+    if config.log_y {
+        draw_time_series_chart(
+            root,
+            x_spec,
+            y_spec.log_scale(),
+            y_range,
+            (times, prey, predators),
+            harvests,
+            config,
+        )
+    } else {
+        draw_time_series_chart(
+            root,
+            x_spec,
+            y_spec,
+            y_range,
+            (times, prey, predators),
+            harvests,
+            config,
+        )
+    }
+}
+
+/// Draws the chart on a linear or logarithmic y coordinate.
+fn draw_time_series_chart<DB, Y>(
+    root: &DrawingArea<DB, plotters::coord::Shift>,
+    x_spec: std::ops::Range<f64>,
+    y_spec: Y,
+    (y_min, y_max): (f64, f64),
+    (times, prey, predators): (&[f64], &[f64], &[f64]),
+    harvests: &[Harvest],
+    config: &PlotConfig,
+) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+    Y: AsRangedCoord<Value = f64>,
+    Y::CoordDescType: ValueFormatter<f64>,
+{
+    let foreground = config.theme.foreground();
+    let text = |size: u32| ("sans-serif", size).into_font().color(&foreground);
+    let mut chart = ChartBuilder::on(root)
+        .caption(&config.title, text(30))
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(if config.y_label.is_empty() { 50 } else { 70 })
+        .build_cartesian_2d(x_spec, y_spec)?;
+
+    let mut mesh = chart.configure_mesh();
+    mesh.label_style(text(15))
+        .axis_style(foreground)
+        .bold_line_style(config.theme.grid())
+        .light_line_style(config.theme.grid().mix(0.4));
+    if !config.x_label.is_empty() {
+        mesh.x_desc(&config.x_label);
+    }
+    if !config.y_label.is_empty() {
+        mesh.y_desc(&config.y_label);
+    }
+    mesh.draw()?;
+
+    for harvest in harvests {
+        let color = match harvest.species {
+            Species::Prey => config.prey_color.mix(0.1),
+            Species::Predator => config.predator_color.mix(0.1),
+        };
+        chart.draw_series(std::iter::once(Rectangle::new(
+            [(harvest.start, y_min), (harvest.end, y_max)],
+            color.filled(),
+        )))?;
+    }
+
+    // Non-positive values are drawn at the bottom of a logarithmic axis
+    let floor = if config.log_y {
+        y_min
+    } else {
+        f64::NEG_INFINITY
+    };
+    let series = [
+        ("Prey", prey, config.prey_color),
+        ("Predators", predators, config.predator_color),
+    ];
+    for (label, values, color) in series {
+        let style = color.stroke_width(config.line_width);
+        chart
+            .draw_series(LineSeries::new(
+                times.iter().zip(values).map(|(&t, &v)| (t, v.max(floor))),
+                style,
+            ))?
+            .label(label)
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], style));
+    }
+
+    let position = match config.legend {
+        LegendPosition::UpperLeft => SeriesLabelPosition::UpperLeft,
+        LegendPosition::UpperRight => SeriesLabelPosition::UpperRight,
+        LegendPosition::LowerLeft => SeriesLabelPosition::LowerLeft,
+        LegendPosition::LowerRight => SeriesLabelPosition::LowerRight,
+        LegendPosition::Hidden => return Ok(()),
+    };
+    chart
+        .configure_series_labels()
+        .position(position)
+        .label_font(text(15))
+        .background_style(config.theme.background().mix(0.8))
+        .border_style(foreground)
+        .draw()?;
+    Ok(())
+}
+
+/// Plots a table of sensitivity indices as a grouped bar chart.
+pub fn plot_sensitivity_indices(
+    table: &IndexTable,
+    output_file: &str,
+) -> Result<(), Box<dyn Error>> {
+    let colors = [BLUE, RED, GREEN];
+    let groups = table.rows.len().max(1);
+    let bars = table.columns.len().max(1);
+    let max_value = table
+        .rows
+        .iter()
+        .flat_map(|(_, values)| values.iter().copied())
+        .fold(1.0_f64, f64::max);
+    let min_value = table
+        .rows
+        .iter()
+        .flat_map(|(_, values)| values.iter().copied())
+        .fold(0.0_f64, f64::min);
+
     let root = BitMapBackend::new(output_file, (800, 600)).into_drawing_area();
     root.fill(&WHITE)?;
+    let names: Vec<String> = table.rows.iter().map(|(name, _)| name.clone()).collect();
     let mut chart = ChartBuilder::on(&root)
-        .caption("Lotka-Volterra Predator-Prey Model", ("sans-serif", 30))
+        .caption(
+            format!("{} Indices: {}", table.method, table.output),
+            ("sans-serif", 30),
+        )
         .margin(20)
         .x_label_area_size(40)
-        .y_label_area_size(40)
-        .build_cartesian_2d(0.0..times.last().copied().unwrap_or(1.0), 0.0..3000.0)?;
-    chart.configure_mesh().draw()?;
+        .y_label_area_size(50)
+        .build_cartesian_2d(0.0..groups as f64, min_value * 1.1..max_value * 1.1)?;
     chart
-        .draw_series(LineSeries::new(
-            times.iter().zip(prey.iter()).map(|(&x, &y)| (x, y)),
-            &BLUE,
-        ))?
-        .label("Prey")
-        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLUE));
+        .configure_mesh()
+        .disable_x_mesh()
+        .x_labels(groups * 2 + 1)
+        .x_label_formatter(&|x| {
+            let offset = x - x.floor();
+            if (offset - 0.5).abs() < 1e-6 {
+                names.get(*x as usize).cloned().unwrap_or_default()
+            } else {
+                String::new()
+            }
+        })
+        .draw()?;
+
+    let width = 0.8 / bars as f64;
+    for (column, name) in table.columns.iter().enumerate() {
+        let color = colors[column % colors.len()];
+        chart
+            .draw_series(table.rows.iter().enumerate().map(|(group, (_, values))| {
+                let x0 = group as f64 + 0.1 + column as f64 * width;
+                Rectangle::new([(x0, 0.0), (x0 + width, values[column])], color.filled())
+            }))?
+            .label(*name)
+            .legend(move |(x, y)| Rectangle::new([(x, y - 5), (x + 10, y + 5)], color.filled()));
+    }
+    chart
+        .configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()?;
+    Ok(())
+}
+
+/// Viridis color of a density within the range `[min, max]`.
+pub fn density_color(value: f64, min: f64, max: f64) -> RGBColor {
+    let t = if max > min {
+        ((value - min) / (max - min)).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    ViridisRGB.get_color(t as f32)
+}
+
+/// Draws one spatial snapshot: side-by-side heatmaps for a 2D grid, or density
+/// profiles for a 1D grid. Each heatmap is scaled to its own density range.
+fn draw_spatial_snapshot<DB: DrawingBackend>(
+    root: &DrawingArea<DB, plotters::coord::Shift>,
+    snapshot: &SpatialSnapshot,
+) -> Result<(), Box<dyn Error>>
+where
+    DB::ErrorType: 'static,
+{
+    root.fill(&WHITE)?;
+    let title = format!("Spatial Lotka-Volterra Model (t = {:.1})", snapshot.time);
+    let root = root.titled(&title, ("sans-serif", 24))?;
+
+    if snapshot.ny == 1 {
+        let mut chart = ChartBuilder::on(&root)
+            .margin(20)
+            .x_label_area_size(40)
+            .y_label_area_size(50)
+            .build_cartesian_2d(
+                0.0..snapshot.nx as f64,
+                0.0..snapshot.max_density().max(1.0) * 1.05,
+            )?;
+        chart
+            .configure_mesh()
+            .x_desc("Position")
+            .y_desc("Density")
+            .draw()?;
+        for (field, color, name) in [
+            (&snapshot.prey, BLUE, "Prey"),
+            (&snapshot.predators, RED, "Predators"),
+        ] {
+            chart
+                .draw_series(LineSeries::new(
+                    field.iter().enumerate().map(|(i, &v)| (i as f64, v)),
+                    &color,
+                ))?
+                .label(name)
+                .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+        }
+        chart.configure_series_labels().draw()?;
+        return Ok(());
+    }
+
+    let panels = root.split_evenly((1, 2));
+    for (panel, (field, name)) in panels.iter().zip([
+        (&snapshot.prey, "Prey Density"),
+        (&snapshot.predators, "Predator Density"),
+    ]) {
+        let min = field.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = field.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let mut chart = ChartBuilder::on(panel)
+            .caption(
+                format!("{} ({:.1} to {:.1})", name, min, max),
+                ("sans-serif", 18),
+            )
+            .margin(10)
+            .x_label_area_size(25)
+            .y_label_area_size(30)
+            .build_cartesian_2d(0..snapshot.nx, 0..snapshot.ny)?;
+        chart.configure_mesh().disable_mesh().draw()?;
+        chart.draw_series(field.iter().enumerate().map(|(k, &v)| {
+            let (i, j) = (k % snapshot.nx, k / snapshot.nx);
+            let color = density_color(v, min, max);
+            Rectangle::new([(i, j), (i + 1, j + 1)], color.filled())
+        }))?;
+    }
+    Ok(())
+}
+
+/// Saves each spatial snapshot as a numbered PNG frame, e.g. `frame_0000.png`.
+pub fn plot_spatial_frames(
+    snapshots: &[SpatialSnapshot],
+    prefix: &str,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut files = Vec::with_capacity(snapshots.len());
+    for (i, snapshot) in snapshots.iter().enumerate() {
+        let file = format!("{}_{:04}.png", prefix, i);
+        {
+            let root = BitMapBackend::new(&file, (1000, 500)).into_drawing_area();
+            draw_spatial_snapshot(&root, snapshot)?;
+            root.present()?;
+        }
+        files.push(file);
+    }
+    Ok(files)
+}
+
+/// Saves the spatial snapshots as an animated GIF with `frame_delay` milliseconds per frame.
+pub fn animate_spatial(
+    snapshots: &[SpatialSnapshot],
+    output_file: &str,
+    frame_delay: u32,
+) -> Result<(), Box<dyn Error>> {
+    let root = BitMapBackend::gif(output_file, (1000, 500), frame_delay)?.into_drawing_area();
+    for snapshot in snapshots {
+        draw_spatial_snapshot(&root, snapshot)?;
+        root.present()?;
+    }
+    Ok(())
+}
+
+/// Plots prey (top) and predator (bottom) series of several patches, one color per
+/// patch, with automatic axis ranges.
+pub fn plot_patch_populations(
+    times: &[f64],
+    prey: &[Vec<f64>],
+    predators: &[Vec<f64>],
+    labels: &[String],
+    title: &str,
+    output_file: &str,
+) -> Result<(), Box<dyn Error>> {
+    let root = BitMapBackend::new(output_file, (1000, 800)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let root = root.titled(title, ("sans-serif", 28))?;
+    let t_max = times.last().copied().unwrap_or(1.0);
+    let t_min = times.first().copied().unwrap_or(0.0);
+
+    for (panel, (series, name)) in root
+        .split_evenly((2, 1))
+        .iter()
+        .zip([(prey, "Prey"), (predators, "Predators")])
+    {
+        let y_max = series.iter().flatten().copied().fold(1.0_f64, f64::max);
+        let mut chart = ChartBuilder::on(panel)
+            .caption(name, ("sans-serif", 20))
+            .margin(15)
+            .x_label_area_size(35)
+            .y_label_area_size(55)
+            .build_cartesian_2d(t_min..t_max, 0.0..y_max * 1.05)?;
+        chart.configure_mesh().draw()?;
+        for (i, (values, label)) in series.iter().zip(labels).enumerate() {
+            let color = Palette99::pick(i).to_rgba();
+            chart
+                .draw_series(LineSeries::new(
+                    times.iter().copied().zip(values.iter().copied()),
+                    color,
+                ))?
+                .label(label)
+                .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+        }
+        chart
+            .configure_series_labels()
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()?;
+    }
+    Ok(())
+}
+
+/// Plots the realisations of a stochastic ensemble with their mean against the
+/// deterministic trajectory, with prey on top and predators below.
+pub fn plot_ensemble(
+    times: &[f64],
+    prey_runs: &[Vec<f64>],
+    predator_runs: &[Vec<f64>],
+    deterministic: (&[f64], &[f64], &[f64]),
+    title: &str,
+    output_file: &str,
+) -> Result<(), Box<dyn Error>> {
+    let root = BitMapBackend::new(output_file, (1000, 800)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let root = root.titled(title, ("sans-serif", 28))?;
+    let t_min = times.first().copied().unwrap_or(0.0);
+    let t_max = times.last().copied().unwrap_or(1.0);
+    let (det_times, det_prey, det_predators) = deterministic;
+
+    for (panel, (runs, reference, name)) in root.split_evenly((2, 1)).iter().zip([
+        (prey_runs, det_prey, "Prey"),
+        (predator_runs, det_predators, "Predators"),
+    ]) {
+        let y_max = runs
+            .iter()
+            .flatten()
+            .chain(reference)
+            .copied()
+            .fold(1.0_f64, f64::max);
+        let mut chart = ChartBuilder::on(panel)
+            .caption(name, ("sans-serif", 20))
+            .margin(15)
+            .x_label_area_size(35)
+            .y_label_area_size(55)
+            .build_cartesian_2d(t_min..t_max, 0.0..y_max * 1.05)?;
+        chart.configure_mesh().draw()?;
+
+        let faint = RGBColor(170, 190, 235);
+        for (i, run) in runs.iter().enumerate() {
+            let series = chart.draw_series(LineSeries::new(
+                times.iter().copied().zip(run.iter().copied()),
+                faint,
+            ))?;
+            if i == 0 {
+                series
+                    .label("Realisations")
+                    .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], faint));
+            }
+        }
+
+        if runs.len() > 1 {
+            let mean = (0..times.len())
+                .map(|k| runs.iter().map(|run| run[k]).sum::<f64>() / runs.len() as f64);
+            chart
+                .draw_series(LineSeries::new(
+                    times.iter().copied().zip(mean),
+                    BLUE.stroke_width(2),
+                ))?
+                .label("Ensemble mean")
+                .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLUE));
+        }
+
+        chart
+            .draw_series(LineSeries::new(
+                det_times.iter().copied().zip(reference.iter().copied()),
+                RED.stroke_width(2),
+            ))?
+            .label("Deterministic")
+            .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED));
+
+        chart
+            .configure_series_labels()
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()?;
+    }
+    Ok(())
+}
+
+/// Cobweb plot of the host generations: the staircase between successive densities
+/// `H(t) -> H(t + 1)` drawn against the diagonal.
+pub fn plot_cobweb(hosts: &[f64], output_file: &str) -> Result<(), Box<dyn Error>> {
+    let root = BitMapBackend::new(output_file, (800, 800)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let max = hosts
+        .iter()
+        .copied()
+        .filter(|h| h.is_finite())
+        .fold(1.0_f64, f64::max)
+        * 1.05;
+
+    let mut chart = ChartBuilder::on(&root)
+        .caption("Cobweb Plot of Host Generations", ("sans-serif", 28))
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(0.0..max, 0.0..max)?;
+    chart
+        .configure_mesh()
+        .x_desc("H(t)")
+        .y_desc("H(t + 1)")
+        .draw()?;
+
+    chart.draw_series(LineSeries::new(
+        [(0.0, 0.0), (max, max)],
+        RGBColor(150, 150, 150),
+    ))?;
+    let mut staircase = Vec::new();
+    if let Some(&first) = hosts.first() {
+        staircase.push((first, 0.0));
+    }
+    for pair in hosts.windows(2) {
+        staircase.push((pair[0], pair[1]));
+        staircase.push((pair[1], pair[1]));
+    }
+    chart.draw_series(LineSeries::new(staircase, BLUE.stroke_width(2)))?;
+    chart.draw_series(
+        hosts
+            .windows(2)
+            .map(|pair| Circle::new((pair[0], pair[1]), 3, RED.filled())),
+    )?;
+
+    root.present()?;
+    Ok(())
+}
+
+/// Phase plot of a discrete map, one point per generation joined in order.
+pub fn plot_map_phase(
+    hosts: &[f64],
+    parasitoids: &[f64],
+    output_file: &str,
+) -> Result<(), Box<dyn Error>> {
+    let root = BitMapBackend::new(output_file, (800, 800)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let finite_max = |values: &[f64]| {
+        values
+            .iter()
+            .copied()
+            .filter(|v| v.is_finite())
+            .fold(1.0_f64, f64::max)
+            * 1.05
+    };
+
+    let mut chart = ChartBuilder::on(&root)
+        .caption("Host-Parasitoid Phase Plot", ("sans-serif", 28))
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(0.0..finite_max(hosts), 0.0..finite_max(parasitoids))?;
+    chart
+        .configure_mesh()
+        .x_desc("Hosts")
+        .y_desc("Parasitoids")
+        .draw()?;
+
+    let points: Vec<(f64, f64)> = hosts
+        .iter()
+        .copied()
+        .zip(parasitoids.iter().copied())
+        .collect();
+    chart.draw_series(LineSeries::new(points.clone(), BLUE.mix(0.4)))?;
+    chart.draw_series(points.iter().map(|&p| Circle::new(p, 3, BLUE.filled())))?;
+
+    root.present()?;
+    Ok(())
+}
+
+/// Bifurcation diagram: the long-run host densities against the swept parameter.
+pub fn plot_bifurcation(
+    diagram: &[(f64, Vec<f64>)],
+    parameter: &str,
+    output_file: &str,
+) -> Result<(), Box<dyn Error>> {
+    let root = BitMapBackend::new(output_file, (1000, 700)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let x_min = diagram.first().map_or(0.0, |d| d.0);
+    let x_max = diagram
+        .last()
+        .map_or(1.0, |d| d.0)
+        .max(x_min + f64::EPSILON);
+    let y_max = diagram
+        .iter()
+        .flat_map(|d| d.1.iter())
+        .copied()
+        .fold(1.0_f64, f64::max)
+        * 1.05;
+
+    let mut chart = ChartBuilder::on(&root)
+        .caption(
+            format!("Bifurcation Diagram over {}", parameter),
+            ("sans-serif", 28),
+        )
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(x_min..x_max, 0.0..y_max)?;
+    chart
+        .configure_mesh()
+        .x_desc(parameter)
+        .y_desc("Host density")
+        .draw()?;
+
+    chart.draw_series(diagram.iter().flat_map(|(x, hosts)| {
+        hosts
+            .iter()
+            .map(move |&h| Circle::new((*x, h), 1, BLACK.filled()))
+    }))?;
+
+    root.present()?;
+    Ok(())
+}
+
+/// Plots a continued branch of equilibria (prey component; solid where stable, dashed
+/// where unstable), its bifurcation points and the prey maxima of an orbit diagram.
+/// The file is written as SVG if its name ends in `.svg` and as PNG otherwise.
+pub fn plot_continuation(
+    branch: &[BranchPoint],
+    bifurcations: &[Bifurcation],
+    orbit: &[(f64, Vec<f64>)],
+    parameter: &str,
+    output_file: &str,
+) -> Result<(), Box<dyn Error>> {
+    if output_file.ends_with(".svg") {
+        let root = SVGBackend::new(output_file, (1000, 700)).into_drawing_area();
+        draw_continuation(&root, branch, bifurcations, orbit, parameter)?;
+        root.present()?;
+    } else {
+        let root = BitMapBackend::new(output_file, (1000, 700)).into_drawing_area();
+        draw_continuation(&root, branch, bifurcations, orbit, parameter)?;
+        root.present()?;
+    }
+    Ok(())
+}
+
+fn draw_continuation<DB: DrawingBackend>(
+    root: &DrawingArea<DB, plotters::coord::Shift>,
+    branch: &[BranchPoint],
+    bifurcations: &[Bifurcation],
+    orbit: &[(f64, Vec<f64>)],
+    parameter: &str,
+) -> Result<(), Box<dyn Error>>
+where
+    DB::ErrorType: 'static,
+{
+    root.fill(&WHITE)?;
+    let parameters = branch
+        .iter()
+        .map(|p| p.parameter)
+        .chain(orbit.iter().map(|o| o.0));
+    let (x_min, x_max) = parameters.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| {
+        (lo.min(p), hi.max(p))
+    });
+    if x_min >= x_max {
+        return Err("Nothing to plot: the branch and orbit diagram are empty".into());
+    }
+    let y_max = branch
+        .iter()
+        .map(|p| p.state[0])
+        .chain(orbit.iter().flat_map(|o| o.1.iter().copied()))
+        .filter(|y| y.is_finite())
+        .fold(1.0_f64, f64::max)
+        * 1.05;
+
+    let mut chart = ChartBuilder::on(root)
+        .caption(
+            format!("Equilibria and Limit Cycles over {}", parameter),
+            ("sans-serif", 28),
+        )
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(x_min..x_max, 0.0..y_max)?;
+    chart
+        .configure_mesh()
+        .x_desc(parameter)
+        .y_desc("Prey")
+        .draw()?;
+
+    if !orbit.is_empty() {
+        chart
+            .draw_series(orbit.iter().flat_map(|(x, maxima)| {
+                maxima
+                    .iter()
+                    .map(move |&y| Circle::new((*x, y), 2, BLACK.filled()))
+            }))?
+            .label("Prey maxima")
+            .legend(|(x, y)| Circle::new((x + 10, y), 3, BLACK.filled()));
+    }
+
+    // Splits the branch into runs of equal stability, sharing the end points
+    let mut start = 0;
+    let (mut stable_labelled, mut unstable_labelled) = (false, false);
+    for end in 1..=branch.len() {
+        if end < branch.len() && branch[end].stable() == branch[start].stable() {
+            continue;
+        }
+        let run = branch[start..(end + 1).min(branch.len())]
+            .iter()
+            .map(|p| (p.parameter, p.state[0]));
+        if branch[start].stable() {
+            let series = chart.draw_series(LineSeries::new(run, BLUE.stroke_width(2)))?;
+            if !stable_labelled {
+                series.label("Stable equilibrium").legend(|(x, y)| {
+                    PathElement::new(vec![(x, y), (x + 20, y)], BLUE.stroke_width(2))
+                });
+                stable_labelled = true;
+            }
+        } else {
+            let series =
+                chart.draw_series(DashedLineSeries::new(run, 8, 6, BLUE.stroke_width(2)))?;
+            if !unstable_labelled {
+                series.label("Unstable equilibrium").legend(|(x, y)| {
+                    PathElement::new(vec![(x, y), (x + 8, y)], BLUE.stroke_width(2))
+                });
+                unstable_labelled = true;
+            }
+        }
+        start = end;
+    }
+
+    for (kind, color, name) in [
+        (BifurcationKind::Hopf, RED, "Hopf"),
+        (BifurcationKind::Fold, GREEN, "Fold"),
+    ] {
+        let points: Vec<_> = bifurcations.iter().filter(|b| b.kind == kind).collect();
+        if points.is_empty() {
+            continue;
+        }
+        chart
+            .draw_series(
+                points
+                    .iter()
+                    .map(|b| Circle::new((b.parameter, b.state[0]), 6, color.filled())),
+            )?
+            .label(name)
+            .legend(move |(x, y)| Circle::new((x + 10, y), 5, color.filled()));
+    }
+
+    chart
+        .configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()?;
+    Ok(())
+}
+
+/// Plots a phase portrait: one orbit per entry of `orbits`, each in its own color, over
+/// the direction field, dashed prey and predator nullclines and equilibria of the model
+/// with the parameters `field`. Without `field`, as when the vector field changes in
+/// time, only the orbits are drawn.
+pub fn plot_phase_portrait(
+    field: Option<&LotkaVolterraParameters>,
+    orbits: &[Vec<[f64; 2]>],
+    output_file: &str,
+) -> Result<(), Box<dyn Error>> {
+    let max = portrait_extent(field, orbits);
+    plot_phase_portrait_window(field, orbits, [0.0, 0.0], max, output_file)
+}
+
+/// Plots a phase portrait of the window from `min` to `max`, as SVG if the file name
+/// ends in `.svg` and as PNG otherwise.
+pub fn plot_phase_portrait_window(
+    field: Option<&LotkaVolterraParameters>,
+    orbits: &[Vec<[f64; 2]>],
+    min: [f64; 2],
+    max: [f64; 2],
+    output_file: &str,
+) -> Result<(), Box<dyn Error>> {
+    let size = (900, 800);
+    if PlotFormat::from_file_name(output_file) == Some(PlotFormat::Svg) {
+        let root = SVGBackend::new(output_file, size).into_drawing_area();
+        draw_phase_portrait(&root, field, orbits, min, max)?;
+        root.present()?;
+    } else {
+        let root = BitMapBackend::new(output_file, size).into_drawing_area();
+        draw_phase_portrait(&root, field, orbits, min, max)?;
+        root.present()?;
+    }
+    Ok(())
+}
+
+fn draw_phase_portrait<DB: DrawingBackend>(
+    root: &DrawingArea<DB, plotters::coord::Shift>,
+    field: Option<&LotkaVolterraParameters>,
+    orbits: &[Vec<[f64; 2]>],
+    min: [f64; 2],
+    max: [f64; 2],
+) -> Result<(), Box<dyn Error>>
+where
+    DB::ErrorType: 'static,
+{
+    root.fill(&WHITE)?;
+    let mut chart = ChartBuilder::on(root)
+        .caption("Phase Portrait", ("sans-serif", 28))
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(min[0]..max[0], min[1]..max[1])?;
+    chart
+        .configure_mesh()
+        .x_desc("Prey Population")
+        .y_desc("Predator Population")
+        .draw()?;
+
+    // Arrows with heads drawn in axis-relative coordinates so they look the same on both axes
+    let arrow_color = RGBColor(150, 150, 150);
+    let span = [max[0] - min[0], max[1] - min[1]];
+    let arrows = field.map(|params| direction_field(params, min, max, 20));
+    for (origin, tip) in arrows.unwrap_or_default() {
+        let d = [
+            (tip[0] - origin[0]) / span[0],
+            (tip[1] - origin[1]) / span[1],
+        ];
+        let head = |side: f64| {
+            (
+                tip[0] + (-0.35 * d[0] - side * 0.2 * d[1]) * span[0],
+                tip[1] + (-0.35 * d[1] + side * 0.2 * d[0]) * span[1],
+            )
+        };
+        chart.draw_series(LineSeries::new(
+            [(origin[0], origin[1]), (tip[0], tip[1])],
+            arrow_color,
+        ))?;
+        chart.draw_series(LineSeries::new(
+            [head(1.0), (tip[0], tip[1]), head(-1.0)],
+            arrow_color,
+        ))?;
+    }
+
+    let (mut prey_labelled, mut predator_labelled) = (false, false);
+    for line in field
+        .map(|params| nullclines(params, max))
+        .unwrap_or_default()
+    {
+        let (color, name, labelled) = match line.species {
+            Species::Prey => (BLUE, "Prey nullcline", &mut prey_labelled),
+            Species::Predator => (RED, "Predator nullcline", &mut predator_labelled),
+        };
+        let series = chart.draw_series(DashedLineSeries::new(
+            [(line.from[0], line.from[1]), (line.to[0], line.to[1])],
+            10,
+            6,
+            color.stroke_width(2),
+        ))?;
+        if !*labelled {
+            series.label(name).legend(move |(x, y)| {
+                PathElement::new(vec![(x, y), (x + 20, y)], color.stroke_width(2))
+            });
+            *labelled = true;
+        }
+    }
+
+    for (i, orbit) in orbits.iter().enumerate() {
+        let color = Palette99::pick(i + 2).to_rgba();
+        let start = orbit.first().copied().unwrap_or_default();
+        chart
+            .draw_series(LineSeries::new(
+                orbit.iter().map(|p| (p[0], p[1])),
+                color.stroke_width(2),
+            ))?
+            .label(format!("Orbit from ({:.0}, {:.0})", start[0], start[1]))
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+        chart.draw_series([Circle::new((start[0], start[1]), 4, color.filled())])?;
+    }
+
+    if let Some(params) = field {
+        chart
+            .draw_series(
+                equilibria(params)
+                    .into_iter()
+                    .map(|p| Circle::new((p[0], p[1]), 6, BLACK.filled())),
+            )?
+            .label("Equilibria")
+            .legend(|(x, y)| Circle::new((x + 10, y), 5, BLACK.filled()));
+    }
+
+    chart
+        .configure_series_labels()
+        .position(SeriesLabelPosition::UpperRight)
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()?;
+    Ok(())
+}
+
+/// Composite report figure: the time series, the phase-plane orbit with the equilibria of
+/// the model with the parameters `field`, if given, the drift of the conserved quantity
+/// and a summary of the parameters and derived metrics. The file is written as SVG if its
+/// name ends in `.svg` and as PNG otherwise.
+pub fn plot_report(
+    params: &LotkaVolterraParameters,
+    field: Option<&LotkaVolterraParameters>,
+    times: &[f64],
+    prey: &[f64],
+    predators: &[f64],
+    output_file: &str,
+) -> Result<(), Box<dyn Error>> {
+    let size = (1400, 1000);
+    if PlotFormat::from_file_name(output_file) == Some(PlotFormat::Svg) {
+        let root = SVGBackend::new(output_file, size).into_drawing_area();
+        draw_report(&root, params, field, times, prey, predators)?;
+        root.present()?;
+    } else {
+        let root = BitMapBackend::new(output_file, size).into_drawing_area();
+        draw_report(&root, params, field, times, prey, predators)?;
+        root.present()?;
+    }
+    Ok(())
+}
+
+fn draw_report<DB: DrawingBackend>(
+    root: &DrawingArea<DB, plotters::coord::Shift>,
+    params: &LotkaVolterraParameters,
+    field: Option<&LotkaVolterraParameters>,
+    times: &[f64],
+    prey: &[f64],
+    predators: &[f64],
+) -> Result<(), Box<dyn Error>>
+where
+    DB::ErrorType: 'static,
+{
+    root.fill(&WHITE)?;
+    let root = root.titled("Lotka-Volterra Report", ("sans-serif", 36))?;
+    let panels = root.split_evenly((2, 2));
+
+    let series = PlotConfig {
+        title: "Time Series".to_string(),
+        x_label: "Time".to_string(),
+        y_label: "Population".to_string(),
+        legend: LegendPosition::UpperRight,
+        ..PlotConfig::default()
+    };
+    draw_time_series(&panels[0], times, prey, predators, &[], &series)?;
+
+    draw_phase_plane(&panels[1], field, prey, predators)?;
+    draw_conserved_drift(&panels[2], params, times, prey, predators)?;
+
+    // Parameters and derived metrics
+    let summary = summarize(params, times, prey, predators);
+    let period = |p: Option<f64>| p.map_or("none".to_string(), |p| format!("{:.1}", p));
+    let lines = [
+        "Parameters".to_string(),
+        format!("alpha = {}, beta = {}", params.alpha, params.beta),
+        format!("delta = {}, gamma = {}", params.delta, params.gamma),
+        format!(
+            "initial prey = {}, initial predators = {}",
+            params.initial_prey, params.initial_predator
+        ),
+        format!("time = {} to {}", params.t_start, params.t_end),
+        String::new(),
+        "Derived metrics".to_string(),
+        format!(
+            "period: prey {}, predators {}",
+            period(summary.prey_period),
+            period(summary.predator_period)
+        ),
+        format!(
+            "mean population: prey {:.1}, predators {:.1}",
+            summary.mean_prey, summary.mean_predator
+        ),
+        format!(
+            "peak population: prey {:.1}, predators {:.1}",
+            summary.peak_prey, summary.peak_predator
+        ),
+        format!("extinction: {}", if summary.extinct { "yes" } else { "no" }),
+        format!(
+            "maximum conserved quantity drift: {:.2e}",
+            summary.max_drift
+        ),
+    ];
+    for (i, line) in lines.iter().enumerate() {
+        let font = if line == "Parameters" || line == "Derived metrics" {
+            ("sans-serif", 26).into_font().style(FontStyle::Bold)
+        } else {
+            ("sans-serif", 22).into_font()
+        };
+        panels[3].draw(&Text::new(line.as_str(), (40, 40 + 34 * i as i32), font))?;
+    }
+    Ok(())
+}
+
+/// Stride that thins a series to about 2000 points; thick lines through many short
+/// segments render poorly.
+fn thinning_stride(len: usize) -> usize {
+    (len / 2000).max(1)
+}
+
+/// Draws the phase-plane orbit of a solution with the equilibria of the model with the
+/// parameters `field`, if given.
+fn draw_phase_plane<DB: DrawingBackend>(
+    area: &DrawingArea<DB, plotters::coord::Shift>,
+    field: Option<&LotkaVolterraParameters>,
+    prey: &[f64],
+    predators: &[f64],
+) -> Result<(), Box<dyn Error>>
+where
+    DB::ErrorType: 'static,
+{
+    let points: Vec<[f64; 2]> = prey
+        .iter()
+        .zip(predators)
+        .step_by(thinning_stride(prey.len()))
+        .map(|(&x, &y)| [x, y])
+        .collect();
+    let max = portrait_extent(field, std::slice::from_ref(&points));
+    let mut chart = ChartBuilder::on(area)
+        .caption("Phase Plane", ("sans-serif", 30))
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(70)
+        .build_cartesian_2d(0.0..max[0], 0.0..max[1])?;
+    chart
+        .configure_mesh()
+        .x_desc("Prey Population")
+        .y_desc("Predator Population")
+        .draw()?;
+    let color = RGBColor(120, 60, 160);
     chart
         .draw_series(LineSeries::new(
-            times.iter().zip(predators.iter()).map(|(&x, &y)| (x, y)),
-            &RED,
+            points.iter().map(|p| (p[0], p[1])),
+            color.stroke_width(2),
         ))?
-        .label("Predators")
-        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED));
-    chart.configure_series_labels().draw()?;
+        .label("Orbit")
+        .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+    if let Some(params) = field {
+        chart
+            .draw_series(
+                equilibria(params)
+                    .into_iter()
+                    .map(|p| Circle::new((p[0], p[1]), 6, BLACK.filled())),
+            )?
+            .label("Equilibria")
+            .legend(|(x, y)| Circle::new((x + 10, y), 5, BLACK.filled()));
+    }
+    chart
+        .configure_series_labels()
+        .position(SeriesLabelPosition::UpperRight)
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()?;
+    Ok(())
+}
+
+/// Draws the relative drift of the first integral, which measures the integration error.
+fn draw_conserved_drift<DB: DrawingBackend>(
+    area: &DrawingArea<DB, plotters::coord::Shift>,
+    params: &LotkaVolterraParameters,
+    times: &[f64],
+    prey: &[f64],
+    predators: &[f64],
+) -> Result<(), Box<dyn Error>>
+where
+    DB::ErrorType: 'static,
+{
+    let drift = conserved_drift(params, prey, predators);
+    let finite = || drift.iter().copied().filter(|d| d.is_finite());
+    let low = finite().fold(0.0_f64, f64::min);
+    let high = finite().fold(0.0_f64, f64::max);
+    let pad = ((high - low) * 0.1).max(1e-12);
+    let t_start = times.first().copied().unwrap_or(0.0);
+    let t_end = times.last().copied().unwrap_or(1.0).max(t_start + 1e-9);
+    let mut chart = ChartBuilder::on(area)
+        .caption("Conserved Quantity Drift", ("sans-serif", 30))
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(80)
+        .build_cartesian_2d(t_start..t_end, low - pad..high + pad)?;
+    chart
+        .configure_mesh()
+        .x_desc("Time")
+        .y_desc("(V - V0) / |V0|")
+        .y_label_formatter(&|v| format!("{:.1e}", v))
+        .draw()?;
+    chart.draw_series(LineSeries::new(
+        times
+            .iter()
+            .zip(&drift)
+            .step_by(thinning_stride(times.len()))
+            .map(|(&t, &d)| (t, d)),
+        RGBColor(0, 130, 90).stroke_width(2),
+    ))?;
     Ok(())
 }
+
+/// Renders the configured time-series plot as an SVG document.
+pub fn time_series_svg(
+    times: &[f64],
+    prey: &[f64],
+    predators: &[f64],
+    harvests: &[Harvest],
+    config: &PlotConfig,
+) -> Result<String, Box<dyn Error>> {
+    let mut svg = String::new();
+    {
+        let root =
+            SVGBackend::with_string(&mut svg, (config.width, config.height)).into_drawing_area();
+        draw_time_series(&root, times, prey, predators, harvests, config)?;
+        root.present()?;
+    }
+    Ok(svg)
+}
+
+/// Renders the phase-plane orbit with the equilibria as an SVG document.
+pub fn phase_plane_svg(
+    field: Option<&LotkaVolterraParameters>,
+    prey: &[f64],
+    predators: &[f64],
+    size: (u32, u32),
+) -> Result<String, Box<dyn Error>> {
+    let mut svg = String::new();
+    {
+        let root = SVGBackend::with_string(&mut svg, size).into_drawing_area();
+        root.fill(&WHITE)?;
+        draw_phase_plane(&root, field, prey, predators)?;
+        root.present()?;
+    }
+    Ok(svg)
+}
+
+/// Renders the drift of the conserved quantity as an SVG document.
+pub fn conserved_drift_svg(
+    params: &LotkaVolterraParameters,
+    times: &[f64],
+    prey: &[f64],
+    predators: &[f64],
+    size: (u32, u32),
+) -> Result<String, Box<dyn Error>> {
+    let mut svg = String::new();
+    {
+        let root = SVGBackend::with_string(&mut svg, size).into_drawing_area();
+        root.fill(&WHITE)?;
+        draw_conserved_drift(&root, params, times, prey, predators)?;
+        root.present()?;
+    }
+    Ok(svg)
+}
@@ -0,0 +1,307 @@
+use crate::error::SimulationError;
+use plotters::style::RGBColor;
+
+/// Output formats of the static plots. PDF is not offered since plotters has no PDF
+/// backend; SVG files convert losslessly to PDF with external tools.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PlotFormat {
+    #[default]
+    Png,
+    Svg,
+}
+
+impl PlotFormat {
+    /// File extension of the format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            PlotFormat::Png => "png",
+            PlotFormat::Svg => "svg",
+        }
+    }
+
+    /// Format matching the extension of a file name, if it is a supported one.
+    pub fn from_file_name(file: &str) -> Option<Self> {
+        let (_, extension) = file.rsplit_once('.')?;
+        extension.parse().ok()
+    }
+}
+
+impl std::str::FromStr for PlotFormat {
+    type Err = SimulationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "png" => Ok(PlotFormat::Png),
+            "svg" => Ok(PlotFormat::Svg),
+            "pdf" => Err(SimulationError::InvalidParameter(
+                "PDF output is not supported; use svg and convert it".to_string(),
+            )),
+            other => Err(SimulationError::InvalidParameter(format!(
+                "Unknown plot format '{}' (expected png or svg)",
+                other
+            ))),
+        }
+    }
+}
+
+/// Corner of the chart holding the legend, or no legend at all.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum LegendPosition {
+    #[default]
+    UpperLeft,
+    UpperRight,
+    LowerLeft,
+    LowerRight,
+    Hidden,
+}
+
+impl std::str::FromStr for LegendPosition {
+    type Err = SimulationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.replace('_', "-").as_str() {
+            "upper-left" => Ok(LegendPosition::UpperLeft),
+            "upper-right" => Ok(LegendPosition::UpperRight),
+            "lower-left" => Ok(LegendPosition::LowerLeft),
+            "lower-right" => Ok(LegendPosition::LowerRight),
+            "none" | "hidden" => Ok(LegendPosition::Hidden),
+            other => Err(SimulationError::InvalidParameter(format!(
+                "Unknown legend position '{}' (expected upper-left, upper-right, lower-left, lower-right or none)",
+                other
+            ))),
+        }
+    }
+}
+
+/// Background and text colors of a plot.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Theme {
+    #[default]
+    Light,
+    Dark,
+}
+
+impl Theme {
+    /// Background color.
+    pub fn background(&self) -> RGBColor {
+        match self {
+            Theme::Light => RGBColor(255, 255, 255),
+            Theme::Dark => RGBColor(30, 30, 36),
+        }
+    }
+
+    /// Color of the caption, labels and axes.
+    pub fn foreground(&self) -> RGBColor {
+        match self {
+            Theme::Light => RGBColor(0, 0, 0),
+            Theme::Dark => RGBColor(225, 225, 225),
+        }
+    }
+
+    /// Color of the grid lines.
+    pub fn grid(&self) -> RGBColor {
+        match self {
+            Theme::Light => RGBColor(220, 220, 220),
+            Theme::Dark => RGBColor(70, 70, 80),
+        }
+    }
+}
+
+impl std::str::FromStr for Theme {
+    type Err = SimulationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "light" => Ok(Theme::Light),
+            "dark" => Ok(Theme::Dark),
+            other => Err(SimulationError::InvalidParameter(format!(
+                "Unknown theme '{}' (expected light or dark)",
+                other
+            ))),
+        }
+    }
+}
+
+/// Appearance of the static time-series plot. Axis ranges left as `None` are fitted
+/// to the data.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlotConfig {
+    pub format: PlotFormat,
+    pub width: u32,
+    pub height: u32,
+    pub x_range: Option<(f64, f64)>,
+    pub y_range: Option<(f64, f64)>,
+    pub log_y: bool,
+    pub title: String,
+    pub x_label: String,
+    pub y_label: String,
+    pub prey_color: RGBColor,
+    pub predator_color: RGBColor,
+    pub line_width: u32,
+    pub legend: LegendPosition,
+    pub theme: Theme,
+}
+
+impl Default for PlotConfig {
+    fn default() -> Self {
+        Self {
+            format: PlotFormat::Png,
+            width: 800,
+            height: 600,
+            x_range: None,
+            y_range: None,
+            log_y: false,
+            title: "Lotka-Volterra Predator-Prey Model".to_string(),
+            x_label: String::new(),
+            y_label: String::new(),
+            prey_color: RGBColor(0, 0, 255),
+            predator_color: RGBColor(255, 0, 0),
+            line_width: 1,
+            legend: LegendPosition::UpperLeft,
+            theme: Theme::Light,
+        }
+    }
+}
+
+impl PlotConfig {
+    /// Sets an option from its textual value, as given in parameter files.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), SimulationError> {
+        let value = value.trim();
+        match name {
+            "format" => self.format = value.parse()?,
+            "width" => self.width = parse_pixels(value)?,
+            "height" => self.height = parse_pixels(value)?,
+            "size" => (self.width, self.height) = parse_size(value)?,
+            "x_range" => self.x_range = parse_range(value)?,
+            "y_range" => self.y_range = parse_range(value)?,
+            "log_y" => {
+                self.log_y = value.parse().map_err(|_| {
+                    SimulationError::InvalidParameter(format!(
+                        "log_y must be true or false, not '{}'",
+                        value
+                    ))
+                })?
+            }
+            "title" => self.title = value.to_string(),
+            "x_label" => self.x_label = value.to_string(),
+            "y_label" => self.y_label = value.to_string(),
+            "prey_color" => self.prey_color = parse_color(value)?,
+            "predator_color" => self.predator_color = parse_color(value)?,
+            "line_width" => self.line_width = parse_pixels(value)?,
+            "legend" => self.legend = value.parse()?,
+            "theme" => self.theme = value.parse()?,
+            _ => {
+                return Err(SimulationError::InvalidParameter(format!(
+                    "Unknown plot option '{}'",
+                    name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Name of the output file for `stem` in the configured format.
+    pub fn file_name(&self, stem: &str) -> String {
+        format!("{}.{}", stem, self.format.extension())
+    }
+}
+
+fn parse_pixels(value: &str) -> Result<u32, SimulationError> {
+    match value.parse() {
+        Ok(pixels) if pixels > 0 => Ok(pixels),
+        _ => Err(SimulationError::InvalidParameter(format!(
+            "'{}' is not a positive whole number of pixels",
+            value
+        ))),
+    }
+}
+
+/// Parses image dimensions given as `WIDTHxHEIGHT`.
+pub fn parse_size(value: &str) -> Result<(u32, u32), SimulationError> {
+    let (width, height) = value.split_once(['x', 'X']).ok_or_else(|| {
+        SimulationError::InvalidParameter(format!(
+            "Size '{}' must have the form WIDTHxHEIGHT",
+            value
+        ))
+    })?;
+    Ok((parse_pixels(width.trim())?, parse_pixels(height.trim())?))
+}
+
+/// Parses an axis range given as `min:max`, or `auto` to fit the data.
+pub fn parse_range(value: &str) -> Result<Option<(f64, f64)>, SimulationError> {
+    if value == "auto" {
+        return Ok(None);
+    }
+    let invalid = || {
+        SimulationError::InvalidParameter(format!(
+            "Range '{}' must be 'auto' or have the form min:max with min < max",
+            value
+        ))
+    };
+    let (min, max) = value.split_once(':').ok_or_else(invalid)?;
+    let min: f64 = min.trim().parse().map_err(|_| invalid())?;
+    let max: f64 = max.trim().parse().map_err(|_| invalid())?;
+    if min.is_nan() || max.is_nan() || min >= max {
+        return Err(invalid());
+    }
+    Ok(Some((min, max)))
+}
+
+/// Parses a color given as `#rrggbb` or as one of a few common names.
+pub fn parse_color(value: &str) -> Result<RGBColor, SimulationError> {
+    let named = match value.to_ascii_lowercase().as_str() {
+        "black" => Some(RGBColor(0, 0, 0)),
+        "white" => Some(RGBColor(255, 255, 255)),
+        "red" => Some(RGBColor(255, 0, 0)),
+        "green" => Some(RGBColor(0, 160, 0)),
+        "blue" => Some(RGBColor(0, 0, 255)),
+        "orange" => Some(RGBColor(255, 140, 0)),
+        "purple" => Some(RGBColor(128, 0, 160)),
+        "brown" => Some(RGBColor(140, 80, 20)),
+        "gray" | "grey" => Some(RGBColor(128, 128, 128)),
+        _ => None,
+    };
+    if let Some(color) = named {
+        return Ok(color);
+    }
+    let invalid =
+        || SimulationError::InvalidParameter(format!("'{}' is not a color name or #rrggbb", value));
+    let hex = value.strip_prefix('#').ok_or_else(invalid)?;
+    if hex.len() != 6 || !hex.is_ascii() {
+        return Err(invalid());
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| invalid());
+    Ok(RGBColor(channel(0)?, channel(2)?, channel(4)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_plot_options() {
+        let mut config = PlotConfig::default();
+        config.set("size", "1200x400").unwrap();
+        config.set("y_range", "0:5000").unwrap();
+        config.set("prey_color", "#1f77b4").unwrap();
+        config.set("legend", "lower-right").unwrap();
+        config.set("format", "svg").unwrap();
+
+        assert_eq!((config.width, config.height), (1200, 400));
+        assert_eq!(config.y_range, Some((0.0, 5000.0)));
+        assert_eq!(config.prey_color, RGBColor(0x1f, 0x77, 0xb4));
+        assert_eq!(config.legend, LegendPosition::LowerRight);
+        assert_eq!(config.file_name("lotka_volterra"), "lotka_volterra.svg");
+    }
+
+    #[test]
+    fn test_invalid_plot_options() {
+        let mut config = PlotConfig::default();
+        assert!(config.set("y_range", "10:1").is_err());
+        assert!(config.set("width", "0").is_err());
+        assert!(config.set("format", "pdf").is_err());
+        assert!(config.set("prey_color", "#12345").is_err());
+        assert!(config.set("color", "red").is_err());
+        assert_eq!(config, PlotConfig::default());
+    }
+}
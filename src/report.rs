@@ -0,0 +1,494 @@
+use crate::analysis::summarize;
+use crate::error::SimulationError;
+use crate::interventions::Schedule;
+use crate::models::{ExternalTerms, LotkaVolterraParameters};
+use crate::phase::{analyze_equilibria, autonomous_params};
+use crate::plot::{conserved_drift_svg, phase_plane_svg, time_series_svg};
+use crate::plot_config::PlotConfig;
+use crate::solver::{SolverStats, TOLERANCE};
+use std::fmt::Write;
+
+/// Rows of the parameter table: description and formatted value.
+pub fn parameter_rows(params: &LotkaVolterraParameters) -> Vec<(&'static str, String)> {
+    vec![
+        ("Alpha (Prey Birth Rate)", format!("{:.4}", params.alpha)),
+        ("Beta (Predation Rate)", format!("{:.4e}", params.beta)),
+        (
+            "Delta (Predator Reproduction Rate)",
+            format!("{:.4e}", params.delta),
+        ),
+        (
+            "Gamma (Predator Death Rate)",
+            format!("{:.4}", params.gamma),
+        ),
+        (
+            "Initial Prey Population",
+            format!("{:.2}", params.initial_prey),
+        ),
+        (
+            "Initial Predator Population",
+            format!("{:.2}", params.initial_predator),
+        ),
+        ("t_start", format!("{:.2}", params.t_start)),
+        ("t_end", format!("{:.2}", params.t_end)),
+    ]
+}
+
+/// Plain-text table for the terminal, with a header row. The first column is aligned
+/// left and the others, which hold values, right.
+pub fn text_table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let widths: Vec<usize> = (0..header.len())
+        .map(|i| {
+            rows.iter()
+                .filter_map(|row| row.get(i))
+                .map(String::len)
+                .chain([header[i].len()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let dashes: Vec<String> = widths.iter().map(|&w| "-".repeat(w)).collect();
+    let rule = format!("+-{}-+\n", dashes.join("-+-"));
+
+    let mut table = rule.clone();
+    let cells: Vec<String> = header
+        .iter()
+        .zip(&widths)
+        .map(|(cell, &width)| format!("{:<width$}", cell))
+        .collect();
+    table += &format!("| {} |\n", cells.join(" | "));
+    table += &rule;
+    for row in rows {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(i, (cell, &width))| match i {
+                0 => format!("{:<width$}", cell),
+                _ => format!("{:>width$}", cell),
+            })
+            .collect();
+        table += &format!("| {} |\n", cells.join(" | "));
+    }
+    table += &rule;
+    table
+}
+
+/// Plain-text table of the parameters for the terminal.
+pub fn parameter_table(params: &LotkaVolterraParameters) -> String {
+    let rows: Vec<Vec<String>> = parameter_rows(params)
+        .into_iter()
+        .map(|(name, value)| vec![name.to_string(), value])
+        .collect();
+    text_table(&["Parameter", "Value"], &rows)
+}
+
+/// A trajectory as CSV with columns `time,prey,predators`.
+pub fn trajectory_csv(times: &[f64], prey: &[f64], predators: &[f64]) -> String {
+    let mut csv = String::from("time,prey,predators\n");
+    for ((t, x), y) in times.iter().zip(prey).zip(predators) {
+        writeln!(csv, "{},{},{}", t, x, y).ok();
+    }
+    csv
+}
+
+/// A trajectory and its parameters as JSON, with the `parameters` as an object and
+/// `time`, `prey` and `predators` as arrays. Non-finite values are written as `null`.
+pub fn trajectory_json(
+    params: &LotkaVolterraParameters,
+    times: &[f64],
+    prey: &[f64],
+    predators: &[f64],
+) -> String {
+    let number = |v: f64| {
+        if v.is_finite() {
+            v.to_string()
+        } else {
+            "null".to_string()
+        }
+    };
+    let array = |values: &[f64]| {
+        let items: Vec<String> = values.iter().map(|&v| number(v)).collect();
+        format!("[{}]", items.join(","))
+    };
+    let fields: Vec<String> = LotkaVolterraParameters::NAMES
+        .iter()
+        .map(|&name| {
+            format!(
+                "\"{}\": {}",
+                name,
+                number(params.get(name).unwrap_or_default())
+            )
+        })
+        .collect();
+    format!(
+        "{{\n  \"parameters\": {{{}}},\n  \"time\": {},\n  \"prey\": {},\n  \"predators\": {}\n}}\n",
+        fields.join(", "),
+        array(times),
+        array(prey),
+        array(predators)
+    )
+}
+
+/// A solved run with what the HTML report shows about it. A run with a maturation
+/// `delay` is solved with fixed steps, so only the elapsed time of its `stats` is shown.
+pub struct RunReport<'a> {
+    pub params: LotkaVolterraParameters,
+    pub terms: &'a ExternalTerms,
+    pub schedule: &'a Schedule,
+    pub delay: Option<f64>,
+    pub times: &'a [f64],
+    pub prey: &'a [f64],
+    pub predators: &'a [f64],
+    pub step: f64,
+    pub stats: SolverStats,
+    pub plot: &'a PlotConfig,
+}
+
+impl RunReport<'_> {
+    /// The solution as CSV with columns `time,prey,predators`.
+    pub fn to_csv(&self) -> String {
+        trajectory_csv(self.times, self.prey, self.predators)
+    }
+
+    /// A self-contained HTML page with the parameter table, embedded SVG plots, solver
+    /// statistics, equilibrium analysis and the data as a downloadable CSV.
+    pub fn to_html(&self) -> Result<String, SimulationError> {
+        let plot_error = |e: Box<dyn std::error::Error>| SimulationError::PlotError(e.to_string());
+        let p = &self.params;
+        // Equilibria only describe the run when its flow does not change over time or
+        // depend on the past
+        let field = match self.delay {
+            Some(_) => None,
+            None => autonomous_params(p, self.terms, self.schedule),
+        };
+        let series = time_series_svg(
+            self.times,
+            self.prey,
+            self.predators,
+            &self.terms.harvests,
+            self.plot,
+        )
+        .map_err(plot_error)?;
+        let phase = phase_plane_svg(field.as_ref(), self.prey, self.predators, (700, 600))
+            .map_err(plot_error)?;
+        let drift = conserved_drift_svg(p, self.times, self.prey, self.predators, (700, 600))
+            .map_err(plot_error)?;
+        let summary = summarize(p, self.times, self.prey, self.predators);
+        let stats = &self.stats;
+
+        let mut html = String::new();
+        html += "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n";
+        html += "<title>Lotka-Volterra Simulation Report</title>\n<style>\n";
+        html += STYLE;
+        html += "</style>\n</head>\n<body>\n<h1>Lotka-Volterra Simulation Report</h1>\n";
+        match self.delay {
+            Some(_) => {
+                html += "<p>Predator-prey dynamics <code>dx/dt = alpha x - beta x y</code>, \
+                         <code>dy/dt = delta x(t - tau) y(t - tau) - gamma y</code>, with \
+                         <code>x</code> prey, <code>y</code> predators and <code>tau</code> \
+                         the maturation delay.</p>\n";
+            }
+            None => {
+                html += "<p>Predator-prey dynamics <code>dx/dt = alpha x - beta x y</code>, \
+                         <code>dy/dt = delta x y - gamma y</code>, with <code>x</code> prey \
+                         and <code>y</code> predators.</p>\n";
+            }
+        }
+
+        html += "<h2>Parameters</h2>\n";
+        let mut rows: Vec<Vec<String>> = parameter_rows(p)
+            .into_iter()
+            .map(|(name, value)| vec![name.to_string(), value])
+            .collect();
+        if let Some(tau) = self.delay {
+            rows.push(vec![
+                "Maturation Delay (tau)".to_string(),
+                format!("{:.2}", tau),
+            ]);
+        }
+        html += &table(&["Parameter", "Value"], &rows);
+        let (harvests, forcings) = (self.terms.harvests.len(), self.terms.forcings.len());
+        let interventions = self.schedule.interventions.len();
+        if self.delay.is_some() {
+            html += "<p class=\"note\">The run includes a maturation delay, so the quantity \
+                     below is not conserved.</p>\n";
+        } else if harvests + forcings + interventions > 0 {
+            writeln!(
+                html,
+                "<p class=\"note\">The run includes {} harvest, {} forcing and {} intervention \
+                 entries, so the quantity below is not conserved.</p>",
+                harvests, forcings, interventions
+            )
+            .ok();
+        }
+
+        html += "<h2>Populations</h2>\n<figure>";
+        html += &series;
+        html += "</figure>\n<div class=\"row\"><figure>";
+        html += &phase;
+        html += "</figure><figure>";
+        html += &drift;
+        html += "</figure></div>\n";
+
+        html += "<h2>Summary</h2>\n";
+        let period = |p: Option<f64>| p.map_or("none".to_string(), |p| format!("{:.1}", p));
+        let rows = vec![
+            vec![
+                "Oscillation period".to_string(),
+                period(summary.prey_period),
+                period(summary.predator_period),
+            ],
+            vec![
+                "Mean population".to_string(),
+                format!("{:.1}", summary.mean_prey),
+                format!("{:.1}", summary.mean_predator),
+            ],
+            vec![
+                "Peak population".to_string(),
+                format!("{:.1}", summary.peak_prey),
+                format!("{:.1}", summary.peak_predator),
+            ],
+        ];
+        html += &table(&["", "Prey", "Predators"], &rows);
+        writeln!(
+            html,
+            "<p>Extinction: <strong>{}</strong>. Largest relative drift of the conserved \
+             quantity: {:.2e}.</p>",
+            if summary.extinct { "yes" } else { "no" },
+            summary.max_drift
+        )
+        .ok();
+
+        html += "<h2>Equilibrium Analysis</h2>\n";
+        match field {
+            Some(field) => {
+                let rows: Vec<Vec<String>> = analyze_equilibria(&field)
+                    .iter()
+                    .map(|e| {
+                        let [(re1, im1), (re2, im2)] = e.eigenvalues;
+                        vec![
+                            format!("({:.1}, {:.1})", e.point[0], e.point[1]),
+                            format!("{:.4e} {:+.4e}i, {:.4e} {:+.4e}i", re1, im1, re2, im2),
+                            e.kind.describe().to_string(),
+                            e.linear_period()
+                                .map_or("-".to_string(), |t| format!("{:.1}", t)),
+                        ]
+                    })
+                    .collect();
+                html += &table(
+                    &[
+                        "Equilibrium (prey, predators)",
+                        "Eigenvalues",
+                        "Type",
+                        "Linear period",
+                    ],
+                    &rows,
+                );
+            }
+            None if self.delay.is_some() => {
+                html += "<p class=\"note\">The maturation delay makes the flow of this run \
+                         depend on the past, so the equilibrium analysis of the model without \
+                         delay does not apply and is left out.</p>\n";
+            }
+            None => {
+                html += "<p class=\"note\">Harvesting, forcing or interventions change the \
+                         flow of this run over time, so it has no fixed equilibria to \
+                         analyse.</p>\n";
+            }
+        }
+
+        html += "<h2>Solver Statistics</h2>\n";
+        let (method, adaptive) = match self.delay {
+            Some(_) => ("Classical Runge-Kutta, fixed step", false),
+            None => ("Dormand-Prince 5(4), adaptive step", true),
+        };
+        let mut rows = vec![vec!["Method".to_string(), method.to_string()]];
+        if adaptive {
+            rows.push(vec![
+                "Relative and absolute tolerance".to_string(),
+                format!("{:e}", TOLERANCE),
+            ]);
+        }
+        rows.push(vec!["Output step".to_string(), format!("{}", self.step)]);
+        rows.push(vec![
+            "Output points".to_string(),
+            self.times.len().to_string(),
+        ]);
+        if adaptive {
+            rows.extend([
+                vec![
+                    "Integration segments".to_string(),
+                    stats.segments.to_string(),
+                ],
+                vec![
+                    "Accepted steps".to_string(),
+                    stats.accepted_steps.to_string(),
+                ],
+                vec![
+                    "Rejected steps".to_string(),
+                    stats.rejected_steps.to_string(),
+                ],
+                vec![
+                    "Function evaluations".to_string(),
+                    stats.function_evaluations.to_string(),
+                ],
+            ]);
+        }
+        rows.push(vec![
+            "Wall-clock time".to_string(),
+            format!("{:.1} ms", stats.elapsed.as_secs_f64() * 1000.0),
+        ]);
+        html += &table(&["Statistic", "Value"], &rows);
+
+        html += "<h2>Data</h2>\n";
+        writeln!(
+            html,
+            "<p><a download=\"lotka_volterra.csv\" href=\"data:text/csv;charset=utf-8,{}\">\
+             Download the populations as CSV</a> ({} rows).</p>",
+            percent_encode(&self.to_csv()),
+            self.times.len()
+        )
+        .ok();
+        html += "</body>\n</html>\n";
+        Ok(html)
+    }
+}
+
+const STYLE: &str = "body { font-family: sans-serif; max-width: 1450px; margin: 2em auto; \
+padding: 0 1em; color: #222; }
+table { border-collapse: collapse; margin: 1em 0; }
+th, td { border: 1px solid #ccc; padding: 0.3em 0.8em; text-align: left; }
+th { background: #f0f0f0; }
+figure { margin: 0.5em 0; }
+svg { max-width: 100%; height: auto; }
+.row { display: flex; flex-wrap: wrap; gap: 1em; }
+.note { color: #8a5a00; }
+";
+
+/// An HTML table with escaped contents.
+fn table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let mut html = String::from("<table>\n<tr>");
+    for cell in header {
+        write!(html, "<th>{}</th>", escape_html(cell)).ok();
+    }
+    html += "</tr>\n";
+    for row in rows {
+        html += "<tr>";
+        for cell in row {
+            write!(html, "<td>{}</td>", escape_html(cell)).ok();
+        }
+        html += "</tr>\n";
+    }
+    html += "</table>\n";
+    html
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Percent-encodes text for a data URL, keeping the characters that are safe in one.
+fn percent_encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len() * 11 / 10);
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || b",.-_:".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            write!(encoded, "%{:02X}", byte).ok();
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::solve_lotka_volterra;
+
+    #[test]
+    fn test_parameter_table_is_aligned() {
+        let table = parameter_table(&LotkaVolterraParameters::default());
+        let widths: Vec<usize> = table.lines().map(str::len).collect();
+        assert_eq!(widths.len(), 12);
+        assert!(widths.iter().all(|&w| w == widths[0]));
+        assert!(table.contains("Alpha (Prey Birth Rate)"));
+    }
+
+    #[test]
+    fn test_trajectory_json() {
+        let json = trajectory_json(
+            &LotkaVolterraParameters::default(),
+            &[0.0, 0.5],
+            &[2000.0, f64::NAN],
+            &[10.0, 12.5],
+        );
+        assert!(json.contains("\"alpha\": 0.01, \"beta\": 0.00001"));
+        assert!(json.contains("\"time\": [0,0.5]"));
+        assert!(json.contains("\"prey\": [2000,null]"));
+        assert!(json.contains("\"predators\": [10,12.5]"));
+    }
+
+    #[test]
+    fn test_html_report_is_self_contained() {
+        let params = LotkaVolterraParameters {
+            t_end: 500.0,
+            ..LotkaVolterraParameters::default()
+        };
+        let y0 = [params.initial_prey, params.initial_predator];
+        let (times, prey, predators) = solve_lotka_volterra(params, y0, 0.0, 500.0, 1.0).unwrap();
+        let report = RunReport {
+            params,
+            terms: &ExternalTerms::default(),
+            schedule: &Schedule::default(),
+            delay: None,
+            times: &times,
+            prey: &prey,
+            predators: &predators,
+            step: 1.0,
+            stats: SolverStats::default(),
+            plot: &PlotConfig::default(),
+        };
+        let html = report.to_html().unwrap();
+
+        assert_eq!(html.matches("<svg").count(), 3);
+        assert!(html.contains("center (neutrally stable cycles)"));
+        assert!(html.contains("data:text/csv;charset=utf-8,time,prey,predators%0A0,2000,2000%0A"));
+        assert!(!html.contains("src=\"http"), "No external resources.");
+        assert_eq!(report.to_csv().lines().count(), times.len() + 1);
+
+        // Seasonal forcing leaves no fixed equilibria to analyse
+        let terms = ExternalTerms {
+            forcings: vec![crate::models::Forcing {
+                rate: crate::models::Rate::Alpha,
+                amplitude: 0.2,
+                period: 100.0,
+                phase: 0.0,
+            }],
+            ..ExternalTerms::default()
+        };
+        let html = RunReport {
+            terms: &terms,
+            ..report
+        }
+        .to_html()
+        .unwrap();
+        assert!(!html.contains("center (neutrally stable cycles)"));
+        assert!(html.contains("no fixed equilibria"));
+
+        // The equilibria of the model without delay are left out of a delayed run
+        let html = RunReport {
+            delay: Some(5.0),
+            ..report
+        }
+        .to_html()
+        .unwrap();
+        assert!(html.contains("x(t - tau)"));
+        assert!(!html.contains("center (neutrally stable cycles)"));
+        assert!(html.contains("equilibrium analysis of the model without delay"));
+        assert!(!html.contains("Accepted steps"));
+    }
+}
@@ -0,0 +1,379 @@
+use crate::dde::validate_delay;
+use crate::error::SimulationError;
+use crate::interactive::validate_params;
+use crate::interventions::{Intervention, InterventionAction, Schedule};
+use crate::models::{
+    ExternalTerms, Forcing, Harvest, HarvestStrategy, LotkaVolterraParameters, Rate, Species,
+};
+use crate::plot_config::PlotConfig;
+use std::path::Path;
+
+/// A parameter file: model parameters plus optional harvesting and forcing terms and
+/// scheduled interventions.
+///
+/// Files contain one `key = value` entry per line, and a `#` at the start of a line or
+/// followed by a space starts a comment, so colors can be written as `#rrggbb`. Keys are
+/// the parameter names (`alpha`, `initial_prey`, `t_end`, ...) and the repeatable
+/// entries
+///
+/// ```text
+/// harvest = <prey|predator> <effort|quota> <rate> <start> <end>
+/// forcing = <alpha|beta|delta|gamma> <amplitude> <period> [phase]
+/// intervention = <time> add <prey|predator> <amount> [every <interval>]
+/// intervention = <time> remove <prey|predator> <fraction> [every <interval>]
+/// intervention = <time> set <alpha|beta|delta|gamma> <value> [every <interval>]
+/// ```
+///
+/// A `delay = <tau>` entry selects the maturation-delay model. Keys starting with
+/// `plot_` set the appearance of the static plot (`plot_size = 1200x600`,
+/// `plot_y_range = auto`, `plot_log_y = true`, `plot_prey_color = #1f77b4`, ...; see
+/// [`PlotConfig::set`]). Parameters that are not listed keep their default values.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Scenario {
+    pub params: LotkaVolterraParameters,
+    pub terms: ExternalTerms,
+    pub schedule: Schedule,
+    pub delay: Option<f64>,
+    pub plot: PlotConfig,
+}
+
+impl Scenario {
+    /// Reads and parses a parameter file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SimulationError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            SimulationError::InvalidParameter(format!("Cannot read {}: {}", path.display(), e))
+        })?;
+        Self::parse(&text)
+    }
+
+    /// Parses the contents of a parameter file.
+    pub fn parse(text: &str) -> Result<Self, SimulationError> {
+        let mut scenario = Scenario::default();
+
+        for (number, line) in text.lines().enumerate() {
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            let invalid = |msg: &str| {
+                SimulationError::InvalidParameter(format!("line {}: {}", number + 1, msg))
+            };
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| invalid("expected 'key = value'"))?;
+            let key = key.trim().replace('-', "_");
+            let fields: Vec<&str> = value.split_whitespace().collect();
+
+            match key.as_str() {
+                "harvest" => scenario
+                    .terms
+                    .harvests
+                    .push(parse_harvest(&fields).map_err(|e| invalid(&e))?),
+                "intervention" => scenario
+                    .schedule
+                    .interventions
+                    .push(parse_intervention(&fields).map_err(|e| invalid(&e))?),
+                "forcing" => scenario
+                    .terms
+                    .forcings
+                    .push(parse_forcing(&fields).map_err(|e| invalid(&e))?),
+                "delay" => {
+                    let tau = parse_number(value.trim()).map_err(|e| invalid(&e))?;
+                    if tau < 0.0 {
+                        return Err(invalid("delay must be non-negative"));
+                    }
+                    scenario.delay = Some(tau);
+                }
+                _ if key.starts_with("plot_") => scenario
+                    .plot
+                    .set(&key["plot_".len()..], value)
+                    .map_err(|e| invalid(&e.to_string()))?,
+                _ => {
+                    let value = parse_number(value.trim()).map_err(|e| invalid(&e))?;
+                    scenario
+                        .params
+                        .set(&key, value)
+                        .map_err(|e| invalid(&e.to_string()))?;
+                }
+            }
+        }
+
+        validate_params(&scenario.params)?;
+        scenario.terms.validate()?;
+        scenario.schedule.validate()?;
+        validate_delay(scenario.delay, &scenario.schedule)?;
+        Ok(scenario)
+    }
+
+    /// Writes the scenario as a parameter file that [`Scenario::parse`] reads back. The
+    /// plot appearance is not written.
+    pub fn to_text(&self) -> String {
+        let mut text = String::from("# Lotka-Volterra parameter file\n");
+        for name in LotkaVolterraParameters::NAMES {
+            let value = self.params.get(name).unwrap_or_default();
+            text += &format!("{} = {}\n", name, value);
+        }
+        if let Some(tau) = self.delay {
+            text += &format!("delay = {}\n", tau);
+        }
+        for harvest in &self.terms.harvests {
+            let (strategy, rate) = match harvest.strategy {
+                HarvestStrategy::ConstantEffort(e) => ("effort", e),
+                HarvestStrategy::ConstantQuota(q) => ("quota", q),
+            };
+            text += &format!(
+                "harvest = {} {} {} {} {}\n",
+                species_name(harvest.species),
+                strategy,
+                rate,
+                harvest.start,
+                harvest.end
+            );
+        }
+        for forcing in &self.terms.forcings {
+            text += &format!(
+                "forcing = {} {} {} {}\n",
+                forcing.rate.name(),
+                forcing.amplitude,
+                forcing.period,
+                forcing.phase
+            );
+        }
+        for intervention in &self.schedule.interventions {
+            let action = match &intervention.action {
+                InterventionAction::Add { species, amount } => {
+                    format!("add {} {}", species_name(*species), amount)
+                }
+                InterventionAction::Remove { species, fraction } => {
+                    format!("remove {} {}", species_name(*species), fraction)
+                }
+                InterventionAction::SetParameter { name, value } => {
+                    format!("set {} {}", name, value)
+                }
+            };
+            let every = intervention
+                .every
+                .map_or(String::new(), |interval| format!(" every {}", interval));
+            text += &format!("intervention = {} {}{}\n", intervention.time, action, every);
+        }
+        text
+    }
+
+    /// Writes the scenario to a parameter file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SimulationError> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_text()).map_err(|e| {
+            SimulationError::InvalidParameter(format!("Cannot write {}: {}", path.display(), e))
+        })
+    }
+}
+
+fn species_name(species: Species) -> &'static str {
+    match species {
+        Species::Prey => "prey",
+        Species::Predator => "predator",
+    }
+}
+
+/// Removes a comment: a `#` at the start of the line or followed by whitespace.
+fn strip_comment(line: &str) -> &str {
+    let bytes = line.as_bytes();
+    let start = line.char_indices().find(|&(i, c)| {
+        c == '#'
+            && (line[..i].trim().is_empty()
+                || bytes.get(i + 1).is_none_or(|b| b.is_ascii_whitespace()))
+    });
+    match start {
+        Some((i, _)) => &line[..i],
+        None => line,
+    }
+}
+
+fn parse_number(field: &str) -> Result<f64, String> {
+    field
+        .parse()
+        .map_err(|_| format!("'{}' is not a number", field))
+}
+
+fn parse_species(field: &str) -> Result<Species, String> {
+    match field {
+        "prey" => Ok(Species::Prey),
+        "predator" => Ok(Species::Predator),
+        other => Err(format!("unknown species '{}'", other)),
+    }
+}
+
+fn parse_intervention(fields: &[&str]) -> Result<Intervention, String> {
+    let (fields, every) = match fields {
+        [rest @ .., "every", interval] => (rest, Some(parse_number(interval)?)),
+        _ => (fields, None),
+    };
+    let action = match fields {
+        [_, "add", species, amount] => InterventionAction::Add {
+            species: parse_species(species)?,
+            amount: parse_number(amount)?,
+        },
+        [_, "remove", species, fraction] => InterventionAction::Remove {
+            species: parse_species(species)?,
+            fraction: parse_number(fraction)?,
+        },
+        [_, "set", name, value] => {
+            let name = name.replace('-', "_");
+            if !InterventionAction::SETTABLE.contains(&name.as_str()) {
+                return Err(format!(
+                    "interventions can only set {}, not '{}'",
+                    InterventionAction::SETTABLE.join(", "),
+                    name
+                ));
+            }
+            InterventionAction::SetParameter {
+                name,
+                value: parse_number(value)?,
+            }
+        }
+        _ => {
+            return Err("intervention needs: time add|remove|set ... [every interval]".to_string());
+        }
+    };
+    Ok(Intervention {
+        time: parse_number(fields[0])?,
+        every,
+        action,
+    })
+}
+
+fn parse_harvest(fields: &[&str]) -> Result<Harvest, String> {
+    let [species, strategy, rate, start, end] = fields else {
+        return Err("harvest needs: species strategy rate start end".to_string());
+    };
+    let species = parse_species(species)?;
+    let rate = parse_number(rate)?;
+    let strategy = match *strategy {
+        "effort" => HarvestStrategy::ConstantEffort(rate),
+        "quota" => HarvestStrategy::ConstantQuota(rate),
+        other => return Err(format!("unknown harvest strategy '{}'", other)),
+    };
+    Ok(Harvest {
+        species,
+        strategy,
+        start: parse_number(start)?,
+        end: parse_number(end)?,
+    })
+}
+
+fn parse_forcing(fields: &[&str]) -> Result<Forcing, String> {
+    let (rate, amplitude, period, phase) = match fields {
+        [rate, amplitude, period] => (rate, amplitude, period, "0"),
+        [rate, amplitude, period, phase] => (rate, amplitude, period, *phase),
+        _ => return Err("forcing needs: rate amplitude period [phase]".to_string()),
+    };
+    let rate = Rate::ALL
+        .into_iter()
+        .find(|r| r.name() == *rate)
+        .ok_or_else(|| format!("unknown rate '{}'", rate))?;
+    Ok(Forcing {
+        rate,
+        amplitude: parse_number(amplitude)?,
+        period: parse_number(period)?,
+        phase: parse_number(phase)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_scenario() {
+        let text = "
+            # Fisheries scenario
+            alpha = 0.02
+            t_end = 4000
+            delay = 25
+            harvest = prey effort 0.002 1000 3000
+            forcing = alpha 0.3 365
+        ";
+        let scenario = Scenario::parse(text).unwrap();
+
+        assert_eq!(scenario.params.alpha, 0.02);
+        assert_eq!(scenario.params.t_end, 4000.0);
+        assert_eq!(
+            scenario.params.gamma, 0.01,
+            "Unlisted values keep defaults."
+        );
+        assert_eq!(
+            scenario.terms.harvests[0].strategy,
+            HarvestStrategy::ConstantEffort(0.002)
+        );
+        assert_eq!(scenario.terms.forcings[0].period, 365.0);
+        assert_eq!(scenario.delay, Some(25.0));
+    }
+
+    #[test]
+    fn test_parse_interventions() {
+        let text = "
+            intervention = 1000 add prey 500
+            intervention = 200 remove predator 0.3 every 200
+            intervention = 4000 set alpha 0.02
+        ";
+        let scenario = Scenario::parse(text).unwrap();
+        let interventions = &scenario.schedule.interventions;
+
+        assert_eq!(interventions.len(), 3);
+        assert_eq!(interventions[1].every, Some(200.0));
+        assert_eq!(
+            interventions[2].action,
+            InterventionAction::SetParameter {
+                name: "alpha".to_string(),
+                value: 0.02
+            }
+        );
+        let err = Scenario::parse("intervention = 10 set t_end 50").unwrap_err();
+        assert!(err.to_string().contains("line 1"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_scenario_reports_line() {
+        let error = Scenario::parse("alpha = 0.1\nharvest = fish effort 1 0 1").unwrap_err();
+        assert!(error.to_string().contains("line 2"));
+    }
+
+    #[test]
+    fn test_parse_plot_options() {
+        let text = "
+            plot_size = 1200x500 # wide
+            plot_prey_color = #2ca02c
+            plot_title = Fishery
+            plot_log_y = true
+        ";
+        let scenario = Scenario::parse(text).unwrap();
+
+        assert_eq!((scenario.plot.width, scenario.plot.height), (1200, 500));
+        assert_eq!(scenario.plot.prey_color.0, 0x2c);
+        assert_eq!(scenario.plot.title, "Fishery");
+        assert!(scenario.plot.log_y);
+        assert!(Scenario::parse("plot_y_range = 5:1").is_err());
+    }
+
+    #[test]
+    fn test_delay_with_interventions_is_rejected() {
+        let error = Scenario::parse("delay = 25\nintervention = 1000 add prey 500").unwrap_err();
+        assert!(error.to_string().contains("maturation delay"), "{}", error);
+    }
+
+    #[test]
+    fn test_written_scenario_parses_back() {
+        for text in [
+            "alpha = 0.012\nbeta = 1.5e-5\n\
+             harvest = predator quota 0.25 100 900\n\
+             forcing = gamma 0.3 365 1.5\n\
+             intervention = 200 remove prey 0.5 every 1000\n\
+             intervention = 50 set alpha 0.02\n",
+            "delay = 40\nharvest = prey effort 0.002 0 100\n",
+        ] {
+            let scenario = Scenario::parse(text).unwrap();
+            assert_eq!(Scenario::parse(&scenario.to_text()).unwrap(), scenario);
+        }
+    }
+}
@@ -0,0 +1,296 @@
+use crate::error::SimulationError;
+use crate::interventions::Schedule;
+use crate::models::{ExternalTerms, LotkaVolterraParameters, Species};
+use crate::parallel::par_map;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rand_distr::StandardNormal;
+
+/// Times, prey and predators of one realisation.
+pub type SdePath = (Vec<f64>, Vec<f64>, Vec<f64>);
+
+/// Time structure of the environmental fluctuations.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NoiseKind {
+    /// Uncorrelated (white) noise.
+    White,
+    /// Coloured noise from a unit-variance Ornstein-Uhlenbeck process.
+    OrnsteinUhlenbeck { correlation_time: f64 },
+}
+
+/// Integration scheme for the stochastic differential equations.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SdeScheme {
+    EulerMaruyama,
+    Milstein,
+}
+
+/// Environmental noise on the prey birth rate `alpha` and the predator death rate
+/// `gamma`, which become `alpha (1 + sigma_alpha xi_1(t))` and
+/// `gamma (1 + sigma_gamma xi_2(t))`. For white noise the intensities scale the Wiener
+/// increments; for coloured noise they are the relative standard deviations of the rates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EnvironmentalNoise {
+    pub alpha_intensity: f64,
+    pub gamma_intensity: f64,
+    pub kind: NoiseKind,
+    pub scheme: SdeScheme,
+    pub dt: f64,
+}
+
+impl Default for EnvironmentalNoise {
+    fn default() -> Self {
+        Self {
+            alpha_intensity: 0.2,
+            gamma_intensity: 0.2,
+            kind: NoiseKind::White,
+            scheme: SdeScheme::Milstein,
+            dt: 0.1,
+        }
+    }
+}
+
+impl EnvironmentalNoise {
+    /// Validates the intensities, time step and correlation time.
+    pub fn validate(&self) -> Result<(), SimulationError> {
+        let bad_time = matches!(
+            self.kind,
+            NoiseKind::OrnsteinUhlenbeck { correlation_time } if correlation_time <= 0.0
+        );
+        if self.alpha_intensity < 0.0 || self.gamma_intensity < 0.0 || self.dt <= 0.0 || bad_time {
+            return Err(SimulationError::InvalidParameter(
+                "Noise intensities must be non-negative, and the time step and correlation time positive.".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Integrates one realisation of the environmentally forced model from `t_start` to
+/// `t_end` and returns the times and the prey and predator paths.
+///
+/// With white noise the model is the Itô system
+///
+/// ```text
+/// dx = (alpha x - beta x y) dt + sigma_alpha alpha x dW_1
+/// dy = (delta x y - gamma y) dt - sigma_gamma gamma y dW_2
+/// ```
+///
+/// and Milstein adds the `g g' (dW^2 - dt) / 2` correction to Euler-Maruyama. Coloured
+/// noise adds the Ornstein-Uhlenbeck states to the system; since their noise is
+/// additive both schemes coincide. Populations are kept non-negative.
+///
+/// Forcing acts on the rates and harvesting is subtracted from the drift. The scheduled
+/// interventions are applied before the first step at or after their time; the schedule
+/// should have been validated, as actions that fail are skipped.
+pub fn simulate_sde(
+    params: &LotkaVolterraParameters,
+    terms: &ExternalTerms,
+    schedule: &Schedule,
+    noise: &EnvironmentalNoise,
+    rng: &mut impl Rng,
+) -> SdePath {
+    let dt = noise.dt;
+    let sqrt_dt = dt.sqrt();
+    let steps = ((params.t_end - params.t_start) / dt).round().max(0.0) as usize;
+    let (sa, sg) = (noise.alpha_intensity, noise.gamma_intensity);
+    let mut p = *params;
+    // Due events are popped from the back
+    let mut events: Vec<_> = schedule
+        .events(params.t_start, params.t_end)
+        .into_iter()
+        .rev()
+        .collect();
+
+    let mut times = Vec::with_capacity(steps + 1);
+    let mut prey = Vec::with_capacity(steps + 1);
+    let mut predators = Vec::with_capacity(steps + 1);
+    let (mut x, mut y) = (params.initial_prey, params.initial_predator);
+    // Ornstein-Uhlenbeck states, started from their stationary distribution
+    let mut eta: [f64; 2] = [rng.sample(StandardNormal), rng.sample(StandardNormal)];
+
+    times.push(params.t_start);
+    prey.push(x);
+    predators.push(y);
+
+    for k in 1..=steps {
+        let t = params.t_start + (k - 1) as f64 * dt;
+        while let Some((_, action)) = events.pop_if(|event| event.0 <= t) {
+            let mut state = [x, y];
+            action.apply(&mut p, &mut state).ok();
+            [x, y] = state;
+        }
+
+        let dw: [f64; 2] = [
+            sqrt_dt * rng.sample::<f64, _>(StandardNormal),
+            sqrt_dt * rng.sample::<f64, _>(StandardNormal),
+        ];
+        let [alpha, beta, delta, gamma] = terms.forced_rates(&p, t);
+        let harvest = [
+            terms.harvest_rate(Species::Prey, t, x),
+            terms.harvest_rate(Species::Predator, t, y),
+        ];
+        let drift = |alpha: f64, gamma: f64| {
+            (
+                alpha * x - beta * x * y - harvest[0],
+                delta * x * y - gamma * y - harvest[1],
+            )
+        };
+
+        let (next_x, next_y) = match noise.kind {
+            NoiseKind::White => {
+                let (fx, fy) = drift(alpha, gamma);
+                let (gx, gy) = (sa * alpha, -sg * gamma);
+                let mut next = (x + fx * dt + gx * x * dw[0], y + fy * dt + gy * y * dw[1]);
+                if noise.scheme == SdeScheme::Milstein {
+                    next.0 += 0.5 * gx * gx * x * (dw[0] * dw[0] - dt);
+                    next.1 += 0.5 * gy * gy * y * (dw[1] * dw[1] - dt);
+                }
+                next
+            }
+            NoiseKind::OrnsteinUhlenbeck { correlation_time } => {
+                let (fx, fy) = drift(alpha * (1.0 + sa * eta[0]), gamma * (1.0 + sg * eta[1]));
+                let diffusion = (2.0 / correlation_time).sqrt();
+                for (e, w) in eta.iter_mut().zip(dw) {
+                    *e += -*e / correlation_time * dt + diffusion * w;
+                }
+                (x + fx * dt, y + fy * dt)
+            }
+        };
+        x = next_x.max(0.0);
+        y = next_y.max(0.0);
+
+        times.push(params.t_start + k as f64 * dt);
+        prey.push(x);
+        predators.push(y);
+    }
+
+    (times, prey, predators)
+}
+
+/// Simulates `runs` independent realisations in parallel. Run `i` uses the seed
+/// `seed + i`, so ensembles are reproducible when a seed is given.
+pub fn sde_ensemble(
+    params: &LotkaVolterraParameters,
+    terms: &ExternalTerms,
+    schedule: &Schedule,
+    noise: &EnvironmentalNoise,
+    runs: usize,
+    seed: Option<u64>,
+) -> Result<Vec<SdePath>, SimulationError> {
+    noise.validate()?;
+    terms.validate()?;
+    schedule.validate()?;
+    let base = seed.unwrap_or_else(|| rand::rng().random());
+    let seeds: Vec<u64> = (0..runs as u64).map(|i| base.wrapping_add(i)).collect();
+    Ok(par_map(&seeds, |&seed| {
+        simulate_sde(
+            params,
+            terms,
+            schedule,
+            noise,
+            &mut StdRng::seed_from_u64(seed),
+        )
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interventions::{Intervention, InterventionAction};
+    use crate::models::{Harvest, HarvestStrategy};
+    use crate::solver::{solve_lotka_volterra, solve_with_interventions};
+
+    fn test_params() -> LotkaVolterraParameters {
+        LotkaVolterraParameters {
+            t_end: 500.0,
+            ..LotkaVolterraParameters::default()
+        }
+    }
+
+    #[test]
+    fn test_zero_noise_matches_deterministic() {
+        let params = test_params();
+        let noise = EnvironmentalNoise {
+            alpha_intensity: 0.0,
+            gamma_intensity: 0.0,
+            dt: 0.01,
+            ..EnvironmentalNoise::default()
+        };
+        let (_, prey, predators) = simulate_sde(
+            &params,
+            &ExternalTerms::default(),
+            &Schedule::default(),
+            &noise,
+            &mut StdRng::seed_from_u64(1),
+        );
+        let y0 = [params.initial_prey, params.initial_predator];
+        let (_, ode_prey, ode_predators) =
+            solve_lotka_volterra(params, y0, 0.0, 500.0, 0.01).unwrap();
+
+        let relative = |a: f64, b: f64| (a - b).abs() / b;
+        assert!(relative(*prey.last().unwrap(), *ode_prey.last().unwrap()) < 0.01);
+        assert!(relative(*predators.last().unwrap(), *ode_predators.last().unwrap()) < 0.01);
+    }
+
+    #[test]
+    fn test_zero_noise_follows_harvesting_and_interventions() {
+        let params = test_params();
+        let noise = EnvironmentalNoise {
+            alpha_intensity: 0.0,
+            gamma_intensity: 0.0,
+            dt: 0.01,
+            ..EnvironmentalNoise::default()
+        };
+        let terms = ExternalTerms {
+            harvests: vec![Harvest {
+                species: Species::Prey,
+                strategy: HarvestStrategy::ConstantEffort(0.002),
+                start: 0.0,
+                end: 500.0,
+            }],
+            ..ExternalTerms::default()
+        };
+        let schedule = Schedule {
+            interventions: vec![Intervention {
+                time: 100.0,
+                every: None,
+                action: InterventionAction::Remove {
+                    species: Species::Predator,
+                    fraction: 0.5,
+                },
+            }],
+        };
+        let (_, prey, predators) = simulate_sde(
+            &params,
+            &terms,
+            &schedule,
+            &noise,
+            &mut StdRng::seed_from_u64(1),
+        );
+        let y0 = [params.initial_prey, params.initial_predator];
+        let (_, ode_prey, ode_predators) =
+            solve_with_interventions(params, &terms, &schedule, y0, 0.0, 500.0, 0.01).unwrap();
+
+        let relative = |a: f64, b: f64| (a - b).abs() / b;
+        assert!(relative(*prey.last().unwrap(), *ode_prey.last().unwrap()) < 0.01);
+        assert!(relative(*predators.last().unwrap(), *ode_predators.last().unwrap()) < 0.01);
+    }
+
+    #[test]
+    fn test_ensemble_is_seedable() {
+        let noise = EnvironmentalNoise {
+            kind: NoiseKind::OrnsteinUhlenbeck {
+                correlation_time: 20.0,
+            },
+            dt: 1.0,
+            ..EnvironmentalNoise::default()
+        };
+        let (terms, schedule) = (ExternalTerms::default(), Schedule::default());
+        let run = || sde_ensemble(&test_params(), &terms, &schedule, &noise, 3, Some(5)).unwrap();
+        let (first, second) = (run(), run());
+
+        assert_eq!(first, second);
+        assert_ne!(first[0].1, first[1].1);
+    }
+}
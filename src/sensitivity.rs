@@ -0,0 +1,631 @@
+use crate::analysis::{estimate_period, peak, went_extinct};
+use crate::dde::solve_run;
+use crate::error::SimulationError;
+use crate::models::LotkaVolterraParameters;
+use crate::parallel::par_map;
+use crate::report::text_table;
+use crate::scenario::Scenario;
+use crate::stochastic::{LotkaVolterraParams, StochasticRun};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::fmt::Write as _;
+use std::str::FromStr;
+
+/// A parameter varied by the sensitivity analysis, together with its range.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParameterRange {
+    pub name: String,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl ParameterRange {
+    /// Maps a unit-interval coordinate onto the range.
+    fn scale(&self, u: f64) -> f64 {
+        self.min + u * (self.max - self.min)
+    }
+
+    /// Ranges of +/- `fraction` around each rate of the given parameter set.
+    pub fn around_rates(params: &LotkaVolterraParameters, fraction: f64) -> Vec<Self> {
+        ["alpha", "beta", "delta", "gamma"]
+            .iter()
+            .map(|&name| {
+                let value = params.get(name).unwrap_or_default();
+                Self {
+                    name: name.to_string(),
+                    min: value * (1.0 - fraction),
+                    max: value * (1.0 + fraction),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Parses ranges written as `name=min:max`, e.g. `alpha=0.005:0.015`.
+impl FromStr for ParameterRange {
+    type Err = SimulationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            SimulationError::InvalidParameter(format!(
+                "Range '{}' must have the form name=min:max",
+                s
+            ))
+        };
+        let (name, bounds) = s.split_once('=').ok_or_else(invalid)?;
+        let (min, max) = bounds.split_once(':').ok_or_else(invalid)?;
+        let name = name.trim().replace('-', "_");
+        let min: f64 = min.trim().parse().map_err(|_| invalid())?;
+        let max: f64 = max.trim().parse().map_err(|_| invalid())?;
+
+        // Rejects unknown parameter names early
+        LotkaVolterraParameters::default().get(&name)?;
+        if min >= max || min.is_nan() || max.is_nan() {
+            return Err(SimulationError::InvalidParameter(format!(
+                "Range for '{}' must satisfy min < max",
+                name
+            )));
+        }
+        Ok(Self { name, min, max })
+    }
+}
+
+/// Scalar quantity of interest computed from one simulation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScalarOutput {
+    /// Largest prey population of the deterministic solution.
+    PeakPrey,
+    /// Mean spacing between prey peaks of the deterministic solution. Runs without a
+    /// full cycle have no period and are left out of the analysis.
+    Period,
+    /// Fraction of stochastic replicates in which either population dies out.
+    ExtinctionProbability { replicates: usize, dt: f64 },
+}
+
+impl ScalarOutput {
+    /// Short name used in table headers and plot captions.
+    pub fn label(&self) -> &'static str {
+        match self {
+            ScalarOutput::PeakPrey => "Peak prey",
+            ScalarOutput::Period => "Period",
+            ScalarOutput::ExtinctionProbability { .. } => "Extinction probability",
+        }
+    }
+
+    /// Evaluates the output for one run of the scenario, with its harvesting, forcing,
+    /// interventions and delay; `step` is the solver output step and `seed` seeds the
+    /// stochastic replicates. Returns `None` when the output is undefined for the run,
+    /// such as the period of a run without a full cycle. The stochastic model behind the
+    /// extinction probability only takes interventions, and scenarios with harvesting,
+    /// forcing or a delay are rejected.
+    pub fn evaluate(
+        &self,
+        scenario: &Scenario,
+        step: f64,
+        seed: u64,
+    ) -> Result<Option<f64>, SimulationError> {
+        let params = &scenario.params;
+        match *self {
+            ScalarOutput::PeakPrey | ScalarOutput::Period => {
+                let (times, prey, _) = solve_run(
+                    params,
+                    &scenario.terms,
+                    &scenario.schedule,
+                    scenario.delay,
+                    step,
+                )?;
+                Ok(match self {
+                    ScalarOutput::PeakPrey => Some(peak(&prey)),
+                    _ => estimate_period(&times, &prey),
+                })
+            }
+            ScalarOutput::ExtinctionProbability { replicates, dt } => {
+                let terms = &scenario.terms;
+                if !terms.harvests.is_empty()
+                    || !terms.forcings.is_empty()
+                    || scenario.delay.is_some()
+                {
+                    return Err(SimulationError::InvalidParameter(
+                        "The extinction probability uses the stochastic model, which has no \
+                         harvesting, forcing or delay."
+                            .to_string(),
+                    ));
+                }
+                let stochastic = LotkaVolterraParams::from_deterministic(params, dt);
+                let mut rng = StdRng::seed_from_u64(seed);
+                let extinct = (0..replicates)
+                    .filter(|_| {
+                        let mut run = StochasticRun::with_schedule(
+                            stochastic.clone(),
+                            &mut rng,
+                            &scenario.schedule,
+                            params.t_start,
+                        );
+                        let mut prey = vec![run.state()[0]];
+                        let mut predators = vec![run.state()[1]];
+                        for [x, y] in run.advance(stochastic.n) {
+                            prey.push(x);
+                            predators.push(y);
+                        }
+                        went_extinct(&prey, &predators)
+                    })
+                    .count();
+                Ok(Some(extinct as f64 / replicates.max(1) as f64))
+            }
+        }
+    }
+}
+
+/// Table of sensitivity indices, one row per varied parameter. `skipped` counts the
+/// runs whose output was undefined and that were left out.
+#[derive(Debug, Clone)]
+pub struct IndexTable {
+    pub method: &'static str,
+    pub output: &'static str,
+    pub columns: Vec<&'static str>,
+    pub rows: Vec<(String, Vec<f64>)>,
+    pub skipped: usize,
+}
+
+impl IndexTable {
+    /// Prints the table to the terminal.
+    pub fn print(&self) {
+        let mut header = vec!["Parameter"];
+        header.extend(&self.columns);
+        let rows: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .map(|(name, values)| {
+                let mut row = vec![name.clone()];
+                row.extend(values.iter().map(|v| format!("{:.4}", v)));
+                row
+            })
+            .collect();
+        println!("{} indices for {}", self.method, self.output);
+        print!("{}", text_table(&header, &rows));
+        if self.skipped > 0 {
+            println!(
+                "{} runs without a defined {} were left out.",
+                self.skipped,
+                self.output.to_lowercase()
+            );
+        }
+    }
+
+    /// Renders the table as CSV.
+    pub fn to_csv(&self) -> String {
+        let mut csv = format!("parameter,{}\n", self.columns.join(","));
+        for (name, values) in &self.rows {
+            let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+            let _ = writeln!(csv, "{},{}", name, values.join(","));
+        }
+        csv
+    }
+}
+
+fn make_rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_rng(&mut rand::rng()),
+    }
+}
+
+fn validate_ranges(ranges: &[ParameterRange]) -> Result<(), SimulationError> {
+    if ranges.is_empty() {
+        return Err(SimulationError::InvalidParameter(
+            "At least one parameter range is required.".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Builds the scenario for a point of the unit hypercube.
+fn scenario_at(
+    base: &Scenario,
+    ranges: &[ParameterRange],
+    point: &[f64],
+) -> Result<Scenario, SimulationError> {
+    let mut scenario = base.clone();
+    for (range, &u) in ranges.iter().zip(point) {
+        scenario.params.set(&range.name, range.scale(u))?;
+    }
+    Ok(scenario)
+}
+
+/// Evaluates the output at every point in parallel. Each point is given its own seed
+/// drawn from `rng`, so the results do not depend on the thread that evaluates it.
+fn evaluate_points(
+    base: &Scenario,
+    ranges: &[ParameterRange],
+    output: ScalarOutput,
+    step: f64,
+    points: &[Vec<f64>],
+    rng: &mut StdRng,
+) -> Result<Vec<Option<f64>>, SimulationError> {
+    base.terms.validate()?;
+    base.schedule.validate()?;
+    let sets = points
+        .iter()
+        .map(|p| Ok((scenario_at(base, ranges, p)?, rng.random::<u64>())))
+        .collect::<Result<Vec<_>, SimulationError>>()?;
+    par_map(&sets, |(scenario, seed)| {
+        output.evaluate(scenario, step, *seed)
+    })
+    .into_iter()
+    .collect()
+}
+
+/// Evaluates the output at `points` evenly spaced values of one parameter of the
+/// scenario, in parallel, and returns `[value, output]` pairs. Values where the output is
+/// undefined are left out.
+pub fn parameter_sweep(
+    base: &Scenario,
+    range: &ParameterRange,
+    points: usize,
+    output: ScalarOutput,
+    step: f64,
+) -> Result<Vec<[f64; 2]>, SimulationError> {
+    let units: Vec<Vec<f64>> = (0..points)
+        .map(|i| vec![i as f64 / (points.max(2) - 1) as f64])
+        .collect();
+    let outputs = evaluate_points(
+        base,
+        std::slice::from_ref(range),
+        output,
+        step,
+        &units,
+        &mut make_rng(None),
+    )?;
+    Ok(units
+        .iter()
+        .zip(outputs)
+        .filter_map(|(u, value)| Some([range.scale(u[0]), value?]))
+        .collect())
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len().max(1) as f64
+}
+
+fn variance(values: &[f64]) -> f64 {
+    let m = mean(values);
+    values.iter().map(|v| (v - m).powi(2)).sum::<f64>() / values.len().max(1) as f64
+}
+
+/// Computes Sobol first-order and total indices with Saltelli sampling, using
+/// `samples * (k + 2)` model evaluations for `k` parameters of the scenario.
+pub fn sobol_indices(
+    base: &Scenario,
+    ranges: &[ParameterRange],
+    output: ScalarOutput,
+    samples: usize,
+    step: f64,
+    seed: Option<u64>,
+) -> Result<IndexTable, SimulationError> {
+    validate_ranges(ranges)?;
+    let k = ranges.len();
+    let mut rng = make_rng(seed);
+    let mut draw = || -> Vec<Vec<f64>> {
+        (0..samples)
+            .map(|_| (0..k).map(|_| rng.random::<f64>()).collect())
+            .collect()
+    };
+    let a = draw();
+    let b = draw();
+
+    // A with column i taken from B, for each parameter i
+    let mut points = a.clone();
+    points.extend(b.iter().cloned());
+    for i in 0..k {
+        points.extend(a.iter().zip(&b).map(|(row_a, row_b)| {
+            let mut row = row_a.clone();
+            row[i] = row_b[i];
+            row
+        }));
+    }
+
+    let values = evaluate_points(base, ranges, output, step, &points, &mut rng)?;
+    let skipped = values.iter().filter(|v| v.is_none()).count();
+
+    // A sample is only usable if the output is defined at all of its k + 2 points
+    let complete: Vec<usize> = (0..samples)
+        .filter(|&j| (0..k + 2).all(|m| values[m * samples + j].is_some()))
+        .collect();
+    if complete.is_empty() {
+        return Err(SimulationError::InvalidParameter(format!(
+            "The {} is undefined for every sample.",
+            output.label().to_lowercase()
+        )));
+    }
+    let column = |m: usize| -> Vec<f64> {
+        complete
+            .iter()
+            .map(|&j| values[m * samples + j].unwrap_or_default())
+            .collect()
+    };
+    let (f_a, f_b) = (column(0), column(1));
+    let samples = complete.len();
+    let both: Vec<f64> = f_a.iter().chain(&f_b).copied().collect();
+    let var = variance(&both);
+    // Centering f(B) leaves the estimator unbiased but greatly reduces its noise
+    let f_mean = mean(&both);
+
+    let rows = ranges
+        .iter()
+        .enumerate()
+        .map(|(i, range)| {
+            let f_abi = column(i + 2);
+            let (first, total) = if var > 0.0 {
+                let first = (0..samples)
+                    .map(|j| (f_b[j] - f_mean) * (f_abi[j] - f_a[j]))
+                    .sum::<f64>()
+                    / samples as f64
+                    / var;
+                let total = (0..samples)
+                    .map(|j| (f_a[j] - f_abi[j]).powi(2))
+                    .sum::<f64>()
+                    / (2.0 * samples as f64)
+                    / var;
+                (first, total)
+            } else {
+                (0.0, 0.0)
+            };
+            (range.name.clone(), vec![first, total])
+        })
+        .collect();
+
+    Ok(IndexTable {
+        method: "Sobol",
+        output: output.label(),
+        columns: vec!["S1", "ST"],
+        rows,
+        skipped,
+    })
+}
+
+/// Computes Morris elementary effects from `trajectories` one-at-a-time trajectories
+/// on a grid of `levels` levels of the parameters of the scenario. Effects are expressed
+/// per unit of the scaled range.
+pub fn morris_effects(
+    base: &Scenario,
+    ranges: &[ParameterRange],
+    output: ScalarOutput,
+    trajectories: usize,
+    levels: usize,
+    step: f64,
+    seed: Option<u64>,
+) -> Result<IndexTable, SimulationError> {
+    validate_ranges(ranges)?;
+    if levels < 2 {
+        return Err(SimulationError::InvalidParameter(
+            "Morris screening needs at least two levels.".to_string(),
+        ));
+    }
+    let k = ranges.len();
+    let delta = levels as f64 / (2.0 * (levels - 1) as f64);
+    let mut rng = make_rng(seed);
+
+    // Each trajectory visits k + 1 points, moving one parameter per step
+    let mut points = Vec::with_capacity(trajectories * (k + 1));
+    let mut moved = Vec::with_capacity(trajectories * k);
+    for _ in 0..trajectories {
+        let mut point: Vec<f64> = (0..k)
+            .map(|_| rng.random_range(0..levels) as f64 / (levels - 1) as f64)
+            .collect();
+        let mut order: Vec<usize> = (0..k).collect();
+        order.shuffle(&mut rng);
+        points.push(point.clone());
+        for i in order {
+            let signed = if point[i] + delta <= 1.0 {
+                delta
+            } else {
+                -delta
+            };
+            point[i] += signed;
+            points.push(point.clone());
+            moved.push((i, signed));
+        }
+    }
+
+    let values = evaluate_points(base, ranges, output, step, &points, &mut rng)?;
+    let skipped = values.iter().filter(|v| v.is_none()).count();
+    // Steps with an undefined output at either end give no effect
+    let mut effects = vec![Vec::with_capacity(trajectories); k];
+    for t in 0..trajectories {
+        for s in 0..k {
+            let (i, signed) = moved[t * k + s];
+            let before = values[t * (k + 1) + s];
+            let after = values[t * (k + 1) + s + 1];
+            if let (Some(before), Some(after)) = (before, after) {
+                effects[i].push((after - before) / signed);
+            }
+        }
+    }
+
+    let rows = ranges
+        .iter()
+        .zip(&effects)
+        .map(|(range, ee)| {
+            let abs: Vec<f64> = ee.iter().map(|e| e.abs()).collect();
+            (
+                range.name.clone(),
+                vec![mean(ee), mean(&abs), variance(ee).sqrt()],
+            )
+        })
+        .collect();
+
+    Ok(IndexTable {
+        method: "Morris",
+        output: output.label(),
+        columns: vec!["mu", "mu*", "sigma"],
+        rows,
+        skipped,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_params() -> LotkaVolterraParameters {
+        LotkaVolterraParameters {
+            alpha: 0.1,
+            beta: 0.02,
+            gamma: 0.02,
+            delta: 0.1,
+            initial_prey: 40.0,
+            initial_predator: 9.0,
+            t_start: 0.0,
+            t_end: 50.0,
+        }
+    }
+
+    fn scenario(params: LotkaVolterraParameters) -> Scenario {
+        Scenario {
+            params,
+            ..Scenario::default()
+        }
+    }
+
+    #[test]
+    fn test_parameter_sweep_spans_range() {
+        let range: ParameterRange = "initial_prey=20:60".parse().unwrap();
+        let sweep = parameter_sweep(
+            &scenario(test_params()),
+            &range,
+            5,
+            ScalarOutput::PeakPrey,
+            0.5,
+        )
+        .unwrap();
+        assert_eq!(sweep.len(), 5);
+        assert_eq!((sweep[0][0], sweep[4][0]), (20.0, 60.0));
+        assert!(
+            sweep.iter().all(|p| p[1] >= p[0]),
+            "Peaks start at the initial prey."
+        );
+    }
+
+    #[test]
+    fn test_parse_parameter_range() {
+        let range: ParameterRange = "initial-prey=10:20".parse().unwrap();
+        assert_eq!(range.name, "initial_prey");
+        assert_eq!((range.min, range.max), (10.0, 20.0));
+        assert!("alpha=2:1".parse::<ParameterRange>().is_err());
+        assert!("omega=0:1".parse::<ParameterRange>().is_err());
+    }
+
+    #[test]
+    fn test_sobol_ignores_inert_parameter() {
+        // Stretching t_end by a microsecond leaves the peak prey unchanged
+        let ranges = vec![
+            "initial_prey=20:60".parse().unwrap(),
+            "t_end=50:50.000001".parse().unwrap(),
+        ];
+        let table = sobol_indices(
+            &scenario(test_params()),
+            &ranges,
+            ScalarOutput::PeakPrey,
+            32,
+            0.5,
+            Some(7),
+        )
+        .unwrap();
+        assert_eq!(table.rows.len(), 2);
+        assert!(table.rows[0].1[1] > table.rows[1].1[1]);
+    }
+
+    #[test]
+    fn test_runs_without_period_are_skipped() {
+        // A run shorter than a cycle has no period
+        let params = LotkaVolterraParameters {
+            t_end: 1.0,
+            ..test_params()
+        };
+        assert_eq!(
+            ScalarOutput::Period
+                .evaluate(&scenario(params), 0.5, 0)
+                .unwrap(),
+            None
+        );
+
+        let ranges = vec!["t_end=1:20".parse().unwrap()];
+        let table = sobol_indices(
+            &scenario(params),
+            &ranges,
+            ScalarOutput::Period,
+            16,
+            0.5,
+            Some(5),
+        )
+        .unwrap();
+        assert!(table.skipped > 0 && table.skipped < 16 * 3);
+        assert!(table.rows[0].1.iter().all(|v| v.is_finite()));
+    }
+
+    #[test]
+    fn test_extinction_probability_uses_seed() {
+        let params = LotkaVolterraParameters {
+            initial_prey: 5.0,
+            initial_predator: 3.0,
+            t_end: 20.0,
+            ..test_params()
+        };
+        let output = ScalarOutput::ExtinctionProbability {
+            replicates: 20,
+            dt: 0.01,
+        };
+        let estimate = |seed| output.evaluate(&scenario(params), 1.0, seed).unwrap();
+        assert_eq!(estimate(11), estimate(11));
+        assert!(estimate(11).is_some_and(|p| (0.0..=1.0).contains(&p)));
+    }
+
+    #[test]
+    fn test_morris_is_seedable() {
+        let ranges = ParameterRange::around_rates(&test_params(), 0.1);
+        let run = || {
+            morris_effects(
+                &scenario(test_params()),
+                &ranges,
+                ScalarOutput::PeakPrey,
+                4,
+                4,
+                0.5,
+                Some(3),
+            )
+            .unwrap()
+            .rows
+        };
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn test_outputs_follow_the_scenario_terms() {
+        use crate::models::{Harvest, HarvestStrategy, Species};
+
+        // The prey rise at first, so the peak comes after the start
+        let base = scenario(LotkaVolterraParameters {
+            delta: 0.01,
+            initial_prey: 5.0,
+            initial_predator: 2.0,
+            ..test_params()
+        });
+        let mut harvested = base.clone();
+        harvested.terms.harvests.push(Harvest {
+            species: Species::Prey,
+            strategy: HarvestStrategy::ConstantEffort(0.05),
+            start: 0.0,
+            end: 50.0,
+        });
+        let peak = |s: &Scenario| ScalarOutput::PeakPrey.evaluate(s, 0.5, 0).unwrap().unwrap();
+        assert!(peak(&harvested) < peak(&base));
+
+        // The stochastic model behind the extinction probability has no harvesting
+        let output = ScalarOutput::ExtinctionProbability {
+            replicates: 2,
+            dt: 0.01,
+        };
+        assert!(output.evaluate(&harvested, 1.0, 0).is_err());
+    }
+}
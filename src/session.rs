@@ -0,0 +1,418 @@
+use crate::error::SimulationError;
+use crate::models::LotkaVolterraParameters;
+use crate::scenario::Scenario;
+use std::path::{Path, PathBuf};
+
+/// Name of the file with the GUI state in the session directory.
+const STATE_FILE: &str = "gui.txt";
+/// Name of the parameter file with the last parameters in the session directory.
+const SCENARIO_FILE: &str = "scenario.txt";
+
+/// Views of the application, chosen in the side panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    Deterministic,
+    Stochastic,
+    Ensemble,
+    Sweep,
+    PhasePortrait,
+    Fitting,
+    Difference,
+    Spatial,
+    Agents,
+}
+
+impl View {
+    pub const ALL: [View; 9] = [
+        View::Deterministic,
+        View::Stochastic,
+        View::Ensemble,
+        View::Sweep,
+        View::PhasePortrait,
+        View::Fitting,
+        View::Difference,
+        View::Spatial,
+        View::Agents,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            View::Deterministic => "Deterministic",
+            View::Stochastic => "Stochastic",
+            View::Ensemble => "Ensemble",
+            View::Sweep => "Parameter Sweep",
+            View::PhasePortrait => "Phase Portrait",
+            View::Fitting => "Fitting",
+            View::Difference => "Difference",
+            View::Spatial => "Spatial",
+            View::Agents => "Agents",
+        }
+    }
+}
+
+/// Range and scale of a parameter slider, editable in the GUI.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SliderRange {
+    pub min: f64,
+    pub max: f64,
+    pub logarithmic: bool,
+}
+
+impl SliderRange {
+    /// A slider from `min` to `max` on a linear scale.
+    pub const fn linear(min: f64, max: f64) -> Self {
+        Self {
+            min,
+            max,
+            logarithmic: false,
+        }
+    }
+
+    /// A slider from `min` to `max` on a logarithmic scale.
+    pub const fn log(min: f64, max: f64) -> Self {
+        Self {
+            min,
+            max,
+            logarithmic: true,
+        }
+    }
+
+    /// The range widened to include `value`, with some room above it.
+    pub fn including(self, value: f64) -> Self {
+        Self {
+            min: self.min.min(value),
+            max: if value > self.max {
+                value * 2.0
+            } else {
+                self.max
+            },
+            ..self
+        }
+    }
+
+    /// Smallest value shown on a logarithmic slider that starts at zero.
+    pub fn smallest_positive(&self) -> f64 {
+        if self.min > 0.0 {
+            self.min
+        } else {
+            self.max * 1e-3
+        }
+    }
+}
+
+/// A pinned run, saved by its parameters and solved again when the session is resumed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SavedSnapshot {
+    pub name: String,
+    pub params: LotkaVolterraParameters,
+    pub visible: bool,
+}
+
+/// GUI state kept between sessions: the last parameters, harvesting, forcing and
+/// interventions, the selected view, the window, the slider ranges and the snapshots.
+///
+/// A session is saved as two files in a directory: the parameters as a parameter file
+/// (see [`Scenario`]) and the rest as `key = value` lines
+///
+/// ```text
+/// view = <view name>
+/// window = <x> <y> <width> <height>
+/// slider = <parameter> <min> <max> <linear|log>
+/// snapshot = <shown|hidden> <parameters> <name>
+/// ```
+///
+/// where a snapshot lists its parameters in the order of [`LotkaVolterraParameters::NAMES`]
+/// and is numbered when it has no name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Session {
+    pub scenario: Scenario,
+    pub view: View,
+    pub window: Option<[f32; 4]>,
+    pub sliders: Vec<(String, SliderRange)>,
+    pub snapshots: Vec<SavedSnapshot>,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self {
+            scenario: Scenario::default(),
+            view: View::Deterministic,
+            window: None,
+            sliders: Vec::new(),
+            snapshots: Vec::new(),
+        }
+    }
+}
+
+impl Session {
+    /// Directory the session is kept in, `lotka-volterra` in the user's configuration
+    /// directory.
+    pub fn directory() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("lotka-volterra"))
+    }
+
+    /// Reads the session saved in `dir`, or `None` if none has been saved there. Lines
+    /// that cannot be read are skipped, and a parameter file that cannot be read leaves
+    /// the default scenario, so that the rest of the state is still resumed.
+    pub fn load(dir: impl AsRef<Path>) -> Result<Option<Self>, SimulationError> {
+        let path = dir.as_ref().join(STATE_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let text = std::fs::read_to_string(&path).map_err(|e| {
+            SimulationError::InvalidParameter(format!("Cannot read {}: {}", path.display(), e))
+        })?;
+        let (mut session, errors) = Self::parse(&text);
+        for e in errors {
+            println!("Ignoring {} {}", STATE_FILE, e);
+        }
+        match Scenario::load(dir.as_ref().join(SCENARIO_FILE)) {
+            Ok(scenario) => session.scenario = scenario,
+            Err(e) => println!("Ignoring the saved parameters: {}", e),
+        }
+        Ok(Some(session))
+    }
+
+    /// Writes the session to `dir`, creating it if needed. Parameters that a parameter
+    /// file would reject, such as a negative rate or an end time before the start, are
+    /// clamped to the nearest valid values.
+    pub fn save(&self, dir: impl AsRef<Path>) -> Result<(), SimulationError> {
+        let dir = dir.as_ref();
+        let cannot_write = |e: std::io::Error| {
+            SimulationError::InvalidParameter(format!("Cannot write {}: {}", dir.display(), e))
+        };
+        std::fs::create_dir_all(dir).map_err(cannot_write)?;
+        std::fs::write(dir.join(STATE_FILE), self.to_text()).map_err(cannot_write)?;
+        let scenario = Scenario {
+            params: clamped(self.scenario.params),
+            ..self.scenario.clone()
+        };
+        scenario.save(dir.join(SCENARIO_FILE))
+    }
+
+    /// Parses the GUI state, leaving the scenario at its default. Lines that cannot be
+    /// read are skipped, and their errors are returned with the state.
+    pub fn parse(text: &str) -> (Self, Vec<SimulationError>) {
+        let mut session = Session::default();
+        let mut errors = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            if let Err(e) = session.parse_line(line) {
+                errors.push(SimulationError::InvalidParameter(format!(
+                    "line {}: {}",
+                    number + 1,
+                    e
+                )));
+            }
+        }
+        (session, errors)
+    }
+
+    /// Reads one `key = value` line into the state.
+    fn parse_line(&mut self, line: &str) -> Result<(), String> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(());
+        }
+        let (key, value) = line.split_once('=').ok_or("expected 'key = value'")?;
+        let value = value.trim();
+        let fields: Vec<&str> = value.split_whitespace().collect();
+        let numbers = |fields: &[&str]| -> Result<Vec<f64>, String> {
+            fields
+                .iter()
+                .map(|f| f.parse().map_err(|_| format!("invalid number '{}'", f)))
+                .collect()
+        };
+
+        match key.trim() {
+            "view" => {
+                self.view = View::ALL
+                    .into_iter()
+                    .find(|view| view.name() == value)
+                    .ok_or_else(|| format!("unknown view '{}'", value))?;
+            }
+            "window" => {
+                let [x, y, width, height] = numbers(&fields)?[..] else {
+                    return Err("expected <x> <y> <width> <height>".to_string());
+                };
+                self.window = Some([x, y, width, height].map(|v| v as f32));
+            }
+            "slider" => {
+                let [name, min, max, scale] = fields[..] else {
+                    return Err("expected <parameter> <min> <max> <linear|log>".to_string());
+                };
+                let range = numbers(&[min, max])?;
+                let logarithmic = match scale {
+                    "linear" => false,
+                    "log" => true,
+                    _ => return Err("scale must be 'linear' or 'log'".to_string()),
+                };
+                self.sliders.push((
+                    name.to_string(),
+                    SliderRange {
+                        min: range[0],
+                        max: range[1],
+                        logarithmic,
+                    },
+                ));
+            }
+            "snapshot" => {
+                let names = LotkaVolterraParameters::NAMES;
+                if fields.len() < names.len() + 1 {
+                    return Err("expected <shown|hidden> <parameters> <name>".to_string());
+                }
+                let visible = match fields[0] {
+                    "shown" => true,
+                    "hidden" => false,
+                    _ => return Err("expected 'shown' or 'hidden'".to_string()),
+                };
+                let mut params = LotkaVolterraParameters::default();
+                for (name, value) in names.iter().zip(numbers(&fields[1..=names.len()])?) {
+                    params.set(name, value).map_err(|e| e.to_string())?;
+                }
+                self.snapshots.push(SavedSnapshot {
+                    name: snapshot_name(&fields[names.len() + 1..].join(" "), self.snapshots.len()),
+                    params,
+                    visible,
+                });
+            }
+            key => return Err(format!("unknown key '{}'", key)),
+        }
+        Ok(())
+    }
+
+    /// Writes the GUI state that [`Session::parse`] reads back. The scenario is written
+    /// separately as a parameter file.
+    pub fn to_text(&self) -> String {
+        let mut text = String::from("# Lotka-Volterra GUI state\n");
+        text += &format!("view = {}\n", self.view.name());
+        if let Some([x, y, width, height]) = self.window {
+            text += &format!("window = {} {} {} {}\n", x, y, width, height);
+        }
+        for (name, range) in &self.sliders {
+            let scale = if range.logarithmic { "log" } else { "linear" };
+            text += &format!("slider = {} {} {} {}\n", name, range.min, range.max, scale);
+        }
+        for (i, snapshot) in self.snapshots.iter().enumerate() {
+            let values: Vec<String> = LotkaVolterraParameters::NAMES
+                .iter()
+                .map(|name| snapshot.params.get(name).unwrap_or_default().to_string())
+                .collect();
+            text += &format!(
+                "snapshot = {} {} {}\n",
+                if snapshot.visible { "shown" } else { "hidden" },
+                values.join(" "),
+                snapshot_name(&snapshot.name, i)
+            );
+        }
+        text
+    }
+}
+
+/// The snapshot name on one line, or a numbered name for the `i`-th snapshot when it
+/// is blank.
+fn snapshot_name(name: &str, i: usize) -> String {
+    match name.split_whitespace().collect::<Vec<_>>().join(" ") {
+        name if name.is_empty() => format!("Snapshot {}", i + 1),
+        name => name,
+    }
+}
+
+/// The parameters with negative or NaN values raised to zero and the end time moved to the
+/// start time if it came before it.
+fn clamped(params: LotkaVolterraParameters) -> LotkaVolterraParameters {
+    let mut params = params;
+    for name in LotkaVolterraParameters::NAMES {
+        let value = params.get(name).unwrap_or_default();
+        params.set(name, value.max(0.0)).ok();
+    }
+    params.t_end = params.t_end.max(params.t_start);
+    params
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_round_trip() {
+        let session = Session {
+            scenario: Scenario {
+                params: LotkaVolterraParameters {
+                    alpha: 0.02,
+                    ..LotkaVolterraParameters::default()
+                },
+                delay: Some(1.5),
+                ..Scenario::default()
+            },
+            view: View::Sweep,
+            window: Some([10.0, 20.0, 1200.0, 800.5]),
+            sliders: vec![(
+                "beta".to_string(),
+                SliderRange {
+                    min: 1e-7,
+                    max: 1e-3,
+                    logarithmic: true,
+                },
+            )],
+            snapshots: vec![SavedSnapshot {
+                name: "Fast prey".to_string(),
+                params: LotkaVolterraParameters {
+                    alpha: 0.05,
+                    ..LotkaVolterraParameters::default()
+                },
+                visible: false,
+            }],
+        };
+        let dir = std::env::temp_dir().join(format!("lv_session_{}", std::process::id()));
+        assert_eq!(Session::load(&dir).unwrap(), None);
+        session.save(&dir).unwrap();
+        assert_eq!(Session::load(&dir).unwrap(), Some(session));
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn test_parse_session_reports_line() {
+        let (session, errors) =
+            Session::parse("view = Parameter Sweep\nwindow = 1 2 3\nslider = beta 0 1 linear\n");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].to_string().contains("line 2"), "{}", errors[0]);
+        assert_eq!(session.view, View::Sweep, "Other lines are still read.");
+        assert_eq!(session.sliders.len(), 1);
+        assert_eq!(Session::parse("view = Nowhere").1.len(), 1);
+    }
+
+    #[test]
+    fn test_session_survives_bad_parameters_and_names() {
+        let session = Session {
+            scenario: Scenario {
+                params: LotkaVolterraParameters {
+                    t_start: 50.0,
+                    t_end: 10.0,
+                    ..LotkaVolterraParameters::default()
+                },
+                ..Scenario::default()
+            },
+            view: View::Stochastic,
+            snapshots: vec![SavedSnapshot {
+                name: "  ".to_string(),
+                params: LotkaVolterraParameters::default(),
+                visible: true,
+            }],
+            ..Session::default()
+        };
+        let dir = std::env::temp_dir().join(format!("lv_session_bad_{}", std::process::id()));
+        session.save(&dir).unwrap();
+        let loaded = Session::load(&dir).unwrap().unwrap();
+        assert_eq!(loaded.view, View::Stochastic);
+        assert_eq!(loaded.snapshots[0].name, "Snapshot 1");
+        let params = loaded.scenario.params;
+        assert_eq!((params.t_start, params.t_end), (50.0, 50.0));
+
+        // An unreadable parameter file only loses the parameters
+        std::fs::write(dir.join(SCENARIO_FILE), "alpha = -1\n").unwrap();
+        let loaded = Session::load(&dir).unwrap().unwrap();
+        assert_eq!(loaded.scenario, Scenario::default());
+        assert_eq!(loaded.view, View::Stochastic);
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
@@ -1,9 +1,38 @@
-use crate::models::{LotkaVolterraParameters, LotkaVolterraSystem};
-use ode_solvers::SVector;
+use crate::error::SimulationError;
+use crate::interventions::{InterventionAction, Schedule};
+use crate::models::{
+    ContinuousModel, ExternalTerms, LotkaVolterraParameters, LotkaVolterraSystem, State,
+};
+use crate::worker::Progress;
 use ode_solvers::dopri5::Dopri5;
+use ode_solvers::{DVector, SVector};
+use std::time::{Duration, Instant};
 
 /// Solves the deterministic Lotka-Volterra system using the ode_solver crate.
 type SolverResult = Result<(Vec<f64>, Vec<f64>, Vec<f64>), Box<dyn std::error::Error>>;
+type StatsResult = Result<(Vec<f64>, Vec<f64>, Vec<f64>, SolverStats), Box<dyn std::error::Error>>;
+
+/// Relative and absolute tolerance of the adaptive Dormand-Prince solver.
+pub const TOLERANCE: f64 = 1e-6;
+
+/// Work done by the adaptive solver, summed over all integration segments.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SolverStats {
+    pub function_evaluations: u32,
+    pub accepted_steps: u32,
+    pub rejected_steps: u32,
+    pub segments: u32,
+    pub elapsed: Duration,
+}
+
+impl SolverStats {
+    fn add(&mut self, stats: ode_solvers::dop_shared::Stats) {
+        self.function_evaluations += stats.num_eval;
+        self.accepted_steps += stats.accepted_steps;
+        self.rejected_steps += stats.rejected_steps;
+        self.segments += 1;
+    }
+}
 
 pub fn solve_lotka_volterra(
     params: LotkaVolterraParameters,
@@ -12,10 +41,91 @@ pub fn solve_lotka_volterra(
     t_end: f64,
     step: f64,
 ) -> SolverResult {
-    let system = LotkaVolterraSystem::new(params);
-    let mut solver = Dopri5::new(system, t0, t_end, step, SVector::from(y0), 1e-6, 1e-6);
+    solve_lotka_volterra_with_terms(params, &ExternalTerms::default(), y0, t0, t_end, step)
+}
+
+/// Solves the system including harvesting and forcing terms.
+pub fn solve_lotka_volterra_with_terms(
+    params: LotkaVolterraParameters,
+    terms: &ExternalTerms,
+    y0: [f64; 2],
+    t0: f64,
+    t_end: f64,
+    step: f64,
+) -> SolverResult {
+    let mut monitor = Monitor::new((t0, t_end), None);
+    integrate_segment(params, terms, y0, t0, t_end, step, &mut monitor)
+}
+
+/// Solver statistics and, for solves started from another thread, the shared progress
+/// over the time span `span`.
+struct Monitor<'a> {
+    stats: SolverStats,
+    progress: Option<&'a Progress>,
+    span: (f64, f64),
+}
+
+impl<'a> Monitor<'a> {
+    fn new(span: (f64, f64), progress: Option<&'a Progress>) -> Self {
+        Self {
+            stats: SolverStats::default(),
+            progress,
+            span,
+        }
+    }
+}
+
+/// The Lotka-Volterra system reporting progress after every accepted step and stopping
+/// the integration once cancelled.
+struct MonitoredSystem<'a> {
+    system: LotkaVolterraSystem,
+    progress: Option<&'a Progress>,
+    span: (f64, f64),
+}
+
+impl ode_solvers::System<f64, State> for MonitoredSystem<'_> {
+    fn system(&self, t: f64, y: &State, dydt: &mut State) {
+        self.system.system(t, y, dydt);
+    }
 
-    solver.integrate()?;
+    fn solout(&mut self, t: f64, _y: &State, _dy: &State) -> bool {
+        let Some(progress) = self.progress else {
+            return false;
+        };
+        let (start, end) = self.span;
+        progress.set_fraction((t - start) / (end - start).max(f64::MIN_POSITIVE));
+        progress.is_cancelled()
+    }
+}
+
+fn integrate_segment(
+    params: LotkaVolterraParameters,
+    terms: &ExternalTerms,
+    y0: [f64; 2],
+    t0: f64,
+    t_end: f64,
+    step: f64,
+    monitor: &mut Monitor,
+) -> SolverResult {
+    let system = MonitoredSystem {
+        system: LotkaVolterraSystem::with_terms(params, terms.clone()),
+        progress: monitor.progress,
+        span: monitor.span,
+    };
+    let mut solver = Dopri5::new(
+        system,
+        t0,
+        t_end,
+        step,
+        SVector::from(y0),
+        TOLERANCE,
+        TOLERANCE,
+    );
+
+    monitor.stats.add(solver.integrate()?);
+    if monitor.progress.is_some_and(Progress::is_cancelled) {
+        return Err(SimulationError::UserCancelled.into());
+    }
     Ok((
         solver.x_out().to_vec(),
         solver.y_out().iter().map(|y| y[0]).collect(),
@@ -23,9 +133,170 @@ pub fn solve_lotka_volterra(
     ))
 }
 
+/// Solves the system while applying scheduled interventions. Integration stops at each
+/// intervention time, the state or parameter jump is applied and integration restarts,
+/// so the output contains both the state before and after every jump.
+pub fn solve_with_interventions(
+    params: LotkaVolterraParameters,
+    terms: &ExternalTerms,
+    schedule: &Schedule,
+    y0: [f64; 2],
+    t0: f64,
+    t_end: f64,
+    step: f64,
+) -> SolverResult {
+    let (times, prey, predators, _) =
+        solve_with_statistics(params, terms, schedule, y0, t0, t_end, step)?;
+    Ok((times, prey, predators))
+}
+
+/// Solves the system with interventions like [`solve_with_interventions`] and also
+/// returns the work done by the solver and the wall-clock time taken.
+pub fn solve_with_statistics(
+    params: LotkaVolterraParameters,
+    terms: &ExternalTerms,
+    schedule: &Schedule,
+    y0: [f64; 2],
+    t0: f64,
+    t_end: f64,
+    step: f64,
+) -> StatsResult {
+    let started = Instant::now();
+    let mut monitor = Monitor::new((t0, t_end), None);
+    let (times, prey, predators) =
+        solve_monitored(params, terms, schedule, y0, step, &mut monitor)?;
+    let stats = SolverStats {
+        elapsed: started.elapsed(),
+        ..monitor.stats
+    };
+    Ok((times, prey, predators, stats))
+}
+
+/// Solves the system with interventions from `params.t_start` to `params.t_end`,
+/// reporting the fraction of the time span covered to `progress`. Meant for solves on
+/// a worker thread: once `progress` is cancelled the solve stops with
+/// [`SimulationError::UserCancelled`].
+pub fn solve_with_progress(
+    params: LotkaVolterraParameters,
+    terms: &ExternalTerms,
+    schedule: &Schedule,
+    step: f64,
+    progress: &Progress,
+) -> SolverResult {
+    let y0 = [params.initial_prey, params.initial_predator];
+    let mut monitor = Monitor::new((params.t_start, params.t_end), Some(progress));
+    solve_monitored(params, terms, schedule, y0, step, &mut monitor)
+}
+
+/// Solves the window from `start = (t0, y0)` to `t_end` of a run from `params.t_start`,
+/// where `y0` is the state of the run at `t0` before any intervention due then. Rates
+/// switched by interventions before `t0` are switched as in the whole run. Used to solve
+/// part of a long run again at a finer output step.
+pub fn solve_window(
+    params: LotkaVolterraParameters,
+    terms: &ExternalTerms,
+    schedule: &Schedule,
+    start: (f64, [f64; 2]),
+    t_end: f64,
+    step: f64,
+    progress: &Progress,
+) -> SolverResult {
+    let (t0, y0) = start;
+    let (mut params, mut unused_state) = (params, y0);
+    for (_, action) in schedule.events(params.t_start, t0) {
+        if matches!(action, InterventionAction::SetParameter { .. }) {
+            action.apply(&mut params, &mut unused_state)?;
+        }
+    }
+    let mut monitor = Monitor::new((t0, t_end), Some(progress));
+    solve_monitored(params, terms, schedule, y0, step, &mut monitor)
+}
+
+/// Integrates over `monitor.span` segment by segment, applying the interventions between
+/// segments.
+fn solve_monitored(
+    params: LotkaVolterraParameters,
+    terms: &ExternalTerms,
+    schedule: &Schedule,
+    y0: [f64; 2],
+    step: f64,
+    monitor: &mut Monitor,
+) -> SolverResult {
+    let (t0, t_end) = monitor.span;
+    let mut params = params;
+    let mut state = y0;
+    let mut segment_start = t0;
+    let (mut times, mut prey, mut predators) = (Vec::new(), Vec::new(), Vec::new());
+
+    let events = schedule.events(t0, t_end);
+    let stops = events
+        .iter()
+        .map(|&(time, action)| (time, Some(action)))
+        .chain(std::iter::once((t_end, None)));
+
+    for (stop, action) in stops {
+        if stop - segment_start > 1e-9 {
+            // Output step adjusted so that the segment ends exactly on an output point
+            let intervals = ((stop - segment_start) / step).ceil().max(1.0);
+            let dx = (stop - segment_start) / intervals;
+            let (t, x, y) =
+                integrate_segment(params, terms, state, segment_start, stop, dx, monitor)?;
+            if let (Some(&x_last), Some(&y_last)) = (x.last(), y.last()) {
+                state = [x_last, y_last];
+            }
+            times.extend(t);
+            prey.extend(x);
+            predators.extend(y);
+            segment_start = stop;
+        }
+
+        if let Some(action) = action {
+            action.apply(&mut params, &mut state)?;
+            times.push(stop);
+            prey.push(state[0]);
+            predators.push(state[1]);
+        }
+    }
+
+    Ok((times, prey, predators))
+}
+
+/// Adapts any [`ContinuousModel`] to the ode_solvers interface.
+struct ModelSystem<'a, M: ContinuousModel>(&'a M);
+
+impl<M: ContinuousModel> ode_solvers::System<f64, DVector<f64>> for ModelSystem<'_, M> {
+    fn system(&self, t: f64, y: &DVector<f64>, dydt: &mut DVector<f64>) {
+        self.0.derivative(t, y.as_slice(), dydt.as_mut_slice());
+    }
+}
+
+/// Solves any continuous model, returning the output times and the state at each time.
+pub fn solve_model<M: ContinuousModel>(
+    model: &M,
+    t0: f64,
+    t_end: f64,
+    step: f64,
+) -> Result<(Vec<f64>, Vec<Vec<f64>>), SimulationError> {
+    let y0 = DVector::from_vec(model.initial_state());
+    let mut solver = Dopri5::new(ModelSystem(model), t0, t_end, step, y0, 1e-6, 1e-6);
+    solver
+        .integrate()
+        .map_err(|e| SimulationError::SolverError(e.to_string()))?;
+    Ok((
+        solver.x_out().to_vec(),
+        solver
+            .y_out()
+            .iter()
+            .map(|y| y.as_slice().to_vec())
+            .collect(),
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::interventions::Intervention;
+    use crate::models::{Harvest, HarvestStrategy, Species};
 
     #[test]
     fn test_deterministic_solver_runs() {
@@ -52,4 +323,119 @@ mod tests {
             "Deterministic solver should not return an error."
         );
     }
+
+    #[test]
+    fn test_solve_model_matches_solver() {
+        let params = LotkaVolterraParameters {
+            alpha: 0.1,
+            beta: 0.02,
+            gamma: 0.02,
+            delta: 0.1,
+            initial_prey: 40.0,
+            initial_predator: 9.0,
+            t_start: 0.0,
+            t_end: 20.0,
+        };
+        let (_, states) = solve_model(&LotkaVolterraSystem::new(params), 0.0, 20.0, 0.5).unwrap();
+        let (_, prey, _) = solve_lotka_volterra(params, [40.0, 9.0], 0.0, 20.0, 0.5).unwrap();
+
+        assert!((states.last().unwrap()[0] - prey.last().unwrap()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_harvesting_reduces_prey() {
+        let params = LotkaVolterraParameters {
+            alpha: 0.1,
+            beta: 0.0,
+            gamma: 0.0,
+            delta: 0.0,
+            initial_prey: 40.0,
+            initial_predator: 0.0,
+            t_start: 0.0,
+            t_end: 10.0,
+        };
+        let terms = ExternalTerms {
+            harvests: vec![Harvest {
+                species: Species::Prey,
+                strategy: HarvestStrategy::ConstantEffort(0.1),
+                start: 0.0,
+                end: 10.0,
+            }],
+            forcings: Vec::new(),
+        };
+
+        // Effort equal to the birth rate keeps the prey population constant
+        let (_, prey, _) =
+            solve_lotka_volterra_with_terms(params, &terms, [40.0, 0.0], 0.0, 10.0, 0.1).unwrap();
+        assert!((prey.last().unwrap() - 40.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_interventions_apply_jumps() {
+        let params = LotkaVolterraParameters {
+            alpha: 0.0,
+            beta: 0.0,
+            gamma: 0.0,
+            delta: 0.0,
+            initial_prey: 40.0,
+            initial_predator: 10.0,
+            t_start: 0.0,
+            t_end: 10.0,
+        };
+        let schedule = Schedule {
+            interventions: vec![Intervention {
+                time: 5.0,
+                every: None,
+                action: InterventionAction::Add {
+                    species: Species::Prey,
+                    amount: 500.0,
+                },
+            }],
+        };
+
+        let (times, prey, _) = solve_with_interventions(
+            params,
+            &ExternalTerms::default(),
+            &schedule,
+            [40.0, 10.0],
+            0.0,
+            10.0,
+            0.3,
+        )
+        .unwrap();
+        assert_eq!(*times.last().unwrap(), 10.0);
+        assert_eq!(*prey.last().unwrap(), 540.0);
+    }
+
+    #[test]
+    fn test_window_matches_whole_run() {
+        let params = LotkaVolterraParameters {
+            t_end: 1000.0,
+            ..LotkaVolterraParameters::default()
+        };
+        let schedule = Schedule {
+            interventions: vec![Intervention {
+                time: 200.0,
+                every: None,
+                action: InterventionAction::SetParameter {
+                    name: "alpha".to_string(),
+                    value: 0.02,
+                },
+            }],
+        };
+        let terms = ExternalTerms::default();
+        let progress = Progress::default();
+        let (times, prey, predators) =
+            solve_with_progress(params, &terms, &schedule, 1.0, &progress).unwrap();
+
+        // The whole run has one sample at every whole time plus one after the intervention
+        let i = times.iter().position(|&t| t == 500.0).unwrap();
+        let start = (500.0, [prey[i], predators[i]]);
+        let (window_times, window_prey, _) =
+            solve_window(params, &terms, &schedule, start, 600.0, 0.01, &progress).unwrap();
+        assert_eq!(window_times.len(), 10001);
+        let j = times.iter().position(|&t| t == 600.0).unwrap();
+        let error = (window_prey.last().unwrap() - prey[j]).abs() / prey[j];
+        assert!(error < 1e-3, "Relative error {}", error);
+    }
 }
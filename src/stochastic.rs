@@ -1,6 +1,16 @@
-use eframe::egui;
-use egui_plot::{Corner, Legend, Line, Plot, PlotPoints};
+use crate::error::SimulationError;
+use crate::interventions::{InterventionAction, Schedule};
+use crate::models::{LotkaVolterraParameters, LotkaVolterraSystem, StochasticModel, Transition};
+use crate::worker::Progress;
 use rand::Rng;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+/// Pace of the streaming simulation: one chunk of steps is sent per frame.
+pub const FRAME: Duration = Duration::from_millis(16);
 
 /// Structure to hold Lotka-Volterra parameters.
 #[derive(Clone)]
@@ -15,199 +25,564 @@ pub struct LotkaVolterraParams {
     pub initial_predator: f64, // Initial predator population
 }
 
-/// Simulates the stochastic Lotka-Volterra system and returns phase plot data.
-pub fn solve_stochastic_lotka_volterra(params: &LotkaVolterraParams) -> Vec<[f64; 2]> {
-    let mut rng = rand::rng();
-    let mut prey = params.initial_prey;
-    let mut predators = params.initial_predator;
-    let mut phase_points = Vec::new();
-
-    phase_points.push([prey, predators]);
-
-    for _ in 0..params.n {
-        let x: f64 = rng.random();
-
-        if x <= params.alpha * prey * params.dt {
-            // Prey reproduces
-            prey += 1.0;
-        } else if x <= params.alpha * prey * params.dt + params.delta * predators * params.dt {
-            // Predators die
-            predators -= 1.0;
-        } else if x
-            <= params.alpha * prey * params.dt
-                + params.delta * predators * params.dt
-                + params.beta * prey * predators * params.dt
-        {
-            // Prey die
-            prey -= 1.0;
-        } else if x
-            <= params.alpha * prey * params.dt
-                + params.delta * predators * params.dt
-                + params.beta * prey * predators * params.dt
-                + params.gamma * prey * predators * params.dt
-        {
-            // Predators reproduce
-            predators += 1.0;
+impl LotkaVolterraParams {
+    /// Builds stochastic parameters matching a deterministic parameter set. Note that the
+    /// stochastic model names the predator birth rate `gamma` and the death rate `delta`.
+    pub fn from_deterministic(params: &LotkaVolterraParameters, dt: f64) -> Self {
+        Self {
+            alpha: params.alpha,
+            beta: params.beta,
+            gamma: params.delta,
+            delta: params.gamma,
+            dt,
+            n: ((params.t_end - params.t_start) / dt).max(0.0) as usize,
+            initial_prey: params.initial_prey,
+            initial_predator: params.initial_predator,
         }
-
-        phase_points.push([prey, predators]);
     }
+}
 
+/// Simulates the stochastic Lotka-Volterra system and returns phase plot data.
+pub fn solve_stochastic_lotka_volterra(params: &LotkaVolterraParams) -> Vec<[f64; 2]> {
+    let mut run = StochasticRun::new(params.clone(), rand::rng());
+    let mut phase_points = Vec::with_capacity(params.n + 1);
+    phase_points.push(run.state());
+    phase_points.extend(run.advance(params.n));
     phase_points
 }
 
-/// Stores stochastic phase plot data
-struct StochasticLotkaVolterraApp {
+/// A stochastic simulation that is advanced a number of steps at a time.
+pub struct StochasticRun<R: Rng> {
     params: LotkaVolterraParams,
-    phase_points: Vec<[f64; 2]>,
+    rng: R,
+    state: [f64; 2],
+    step: usize,
+    t_start: f64,
+    events: Vec<(f64, InterventionAction)>, // Interventions still to apply, in order
 }
 
-impl StochasticLotkaVolterraApp {
-    fn new() -> Self {
-        let params = LotkaVolterraParams {
-            alpha: 0.01,
-            beta: 0.00001,
-            gamma: 0.00001,
-            delta: 0.01,
-            dt: 0.001,
-            n: 1000000,
-            initial_prey: 2000.0,
-            initial_predator: 2000.0,
-        };
-
-        let phase_points = solve_stochastic_lotka_volterra(&params);
+impl<R: Rng> StochasticRun<R> {
+    pub fn new(params: LotkaVolterraParams, rng: R) -> Self {
+        Self::with_schedule(params, rng, &Schedule::default(), 0.0)
+    }
 
+    /// Creates a run starting at `t_start` that applies the scheduled interventions as
+    /// it goes. Step `i` corresponds to time `t_start + i * dt`. The schedule should have
+    /// been validated, as actions that fail are skipped.
+    pub fn with_schedule(
+        params: LotkaVolterraParams,
+        rng: R,
+        schedule: &Schedule,
+        t_start: f64,
+    ) -> Self {
+        let state = [params.initial_prey, params.initial_predator];
+        let t_end = t_start + params.n as f64 * params.dt;
+        let mut events: Vec<(f64, InterventionAction)> = schedule
+            .events(t_start, t_end)
+            .into_iter()
+            .map(|(time, action)| (time, action.clone()))
+            .collect();
+        events.reverse();
         Self {
             params,
-            phase_points,
+            rng,
+            state,
+            step: 0,
+            t_start,
+            events,
         }
     }
 
-    fn update_simulation(&mut self) {
-        self.phase_points = solve_stochastic_lotka_volterra(&self.params);
+    /// Current prey and predator populations.
+    pub fn state(&self) -> [f64; 2] {
+        self.state
+    }
+
+    /// Number of steps taken so far.
+    pub fn steps_done(&self) -> usize {
+        self.step
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.step >= self.params.n
     }
+
+    /// Takes up to `steps` steps, stopping at the end of the run, and returns the
+    /// populations after each.
+    pub fn advance(&mut self, steps: usize) -> Vec<[f64; 2]> {
+        let steps = steps.min(self.params.n - self.step);
+        let mut points = Vec::with_capacity(steps);
+        for i in self.step..self.step + steps {
+            let t = self.t_start + i as f64 * self.params.dt;
+            while let Some((_, action)) = self.events.pop_if(|(time, _)| *time <= t) {
+                action
+                    .apply_stochastic(&mut self.params, &mut self.state)
+                    .ok();
+            }
+            let [prey, predators] = &mut self.state;
+            stochastic_step(&self.params, &mut self.rng, prey, predators);
+            points.push(self.state);
+        }
+        self.step += steps;
+        points
+    }
+}
+
+/// Playback controls shared between a [`StochasticStream`] and its simulation thread.
+#[derive(Debug, Default)]
+struct Playback {
+    progress: Progress,
+    paused: AtomicBool,
+    steps_per_frame: AtomicUsize,
+    pending_steps: AtomicUsize,
 }
 
-impl eframe::App for StochasticLotkaVolterraApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        egui::CentralPanel::default().show(ctx, |ui| {
-            ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
-                ui.heading("Stochastic Lotka-Volterra Phase Plot");
-            });
-            ui.add_space(10.0);
-
-            // Plot the stochastic phase plot
-            if !self.phase_points.is_empty() {
-                let stochastic_line = Line::new(PlotPoints::from_iter(
-                    self.phase_points.iter().map(|&[x, y]| [x, y]),
-                ))
-                .name("Stochastic Phase Plot");
-
-                Plot::new("stochastic_phase_plot")
-                    .view_aspect(2.0)
-                    .legend(Legend::default().position(Corner::LeftTop))
-                    .x_axis_label("Prey Population")
-                    .y_axis_label("Predator Population")
-                    .show(ui, |plot_ui| {
-                        plot_ui.line(stochastic_line);
-                    });
-            } else {
-                ui.label("No data available for stochastic phase plot.");
+/// A stochastic simulation running on a worker thread that sends its points in chunks,
+/// one per frame, so that they can be drawn as they arrive. While paused, steps are only
+/// taken on request. Dropping the stream stops the simulation.
+pub struct StochasticStream {
+    receiver: Receiver<Vec<[f64; 2]>>,
+    playback: Arc<Playback>,
+    finished: bool,
+}
+
+impl StochasticStream {
+    /// Starts simulating `params` from `t_start` with the scheduled interventions, taking
+    /// `steps_per_frame` steps per chunk. The first chunk holds the initial populations.
+    pub fn start(
+        params: LotkaVolterraParams,
+        schedule: &Schedule,
+        t_start: f64,
+        steps_per_frame: usize,
+        paused: bool,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let playback = Arc::new(Playback::default());
+        playback.paused.store(paused, Ordering::Relaxed);
+        playback
+            .steps_per_frame
+            .store(steps_per_frame, Ordering::Relaxed);
+        let shared = Arc::clone(&playback);
+        let schedule = schedule.clone();
+        thread::spawn(move || {
+            let total = params.n.max(1) as f64;
+            let mut run = StochasticRun::with_schedule(params, rand::rng(), &schedule, t_start);
+            if sender.send(vec![run.state()]).is_err() {
+                return;
+            }
+            while !run.is_finished() && !shared.progress.is_cancelled() {
+                let steps = if shared.paused.load(Ordering::Relaxed) {
+                    shared.pending_steps.swap(0, Ordering::Relaxed)
+                } else {
+                    shared.steps_per_frame.load(Ordering::Relaxed)
+                };
+                if steps > 0 {
+                    let chunk = run.advance(steps);
+                    shared
+                        .progress
+                        .set_fraction(run.steps_done() as f64 / total);
+                    if sender.send(chunk).is_err() {
+                        return;
+                    }
+                }
+                thread::sleep(FRAME);
             }
+        });
+        Self {
+            receiver,
+            playback,
+            finished: false,
+        }
+    }
+
+    /// All the points that have arrived since the last call.
+    pub fn receive(&mut self) -> Vec<[f64; 2]> {
+        let mut points = Vec::new();
+        loop {
+            match self.receiver.try_recv() {
+                Ok(chunk) => points.extend(chunk),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.finished = true;
+                    break;
+                }
+            }
+        }
+        points
+    }
+
+    /// Whether the simulation has ended and all its points have been received.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Fraction of the steps taken.
+    pub fn progress(&self) -> f32 {
+        self.playback.progress.fraction()
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.playback.paused.store(paused, Ordering::Relaxed);
+    }
 
-            // Add space before parameter section to push it to the bottom
-            ui.add_space(20.0);
-
-            // Collapsible parameter section
-            egui::CollapsingHeader::new("Adjust Parameters")
-                .default_open(true)
-                .show(ui, |ui| {
-                    ui.columns(2, |columns| {
-                        let mut updated = false;
-
-                        // Left column: Initial Conditions + Time Step + Steps
-                        columns[0].vertical(|ui| {
-                            ui.label("Initial Conditions & Time Settings:");
-                            updated |= ui
-                                .add(
-                                    egui::Slider::new(&mut self.params.initial_prey, 0.0..=2000.0)
-                                        .text("Initial Prey"),
-                                )
-                                .changed();
-                            updated |= ui
-                                .add(
-                                    egui::Slider::new(
-                                        &mut self.params.initial_predator,
-                                        0.0..=2000.0,
-                                    )
-                                    .text("Initial Predator"),
-                                )
-                                .changed();
-                            updated |= ui
-                                .add(
-                                    egui::Slider::new(&mut self.params.dt, 0.0..=0.001)
-                                        .text("dt (Time Step)"),
-                                )
-                                .changed();
-                            updated |= ui
-                                .add(
-                                    egui::Slider::new(&mut self.params.n, 0..=1000000)
-                                        .text("n (Steps)"),
-                                )
-                                .changed();
-                        });
-
-                        // Right column: Model Parameters
-                        columns[1].vertical(|ui| {
-                            ui.label("Model Parameters:");
-                            updated |= ui
-                                .add(
-                                    egui::Slider::new(&mut self.params.alpha, 0.0..=0.01)
-                                        .text("Alpha (Prey Birth Rate)"),
-                                )
-                                .changed();
-                            updated |= ui
-                                .add(
-                                    egui::Slider::new(&mut self.params.beta, 0.0..=0.0001)
-                                        .text("Beta (Predation Rate)"),
-                                )
-                                .changed();
-                            updated |= ui
-                                .add(
-                                    egui::Slider::new(&mut self.params.gamma, 0.0..=0.0001)
-                                        .text("Gamma (Predator Birth Rate)"),
-                                )
-                                .changed();
-                            updated |= ui
-                                .add(
-                                    egui::Slider::new(&mut self.params.delta, 0.0..=0.01)
-                                        .text("Delta (Predator Death Rate)"),
-                                )
-                                .changed();
-                        });
-
-                        if updated {
-                            // Re-run the simulation when sliders change
-                            self.update_simulation();
-                        }
-                    });
+    pub fn set_speed(&self, steps_per_frame: usize) {
+        self.playback
+            .steps_per_frame
+            .store(steps_per_frame, Ordering::Relaxed);
+    }
+
+    /// Asks for `steps` more steps while paused.
+    pub fn step(&self, steps: usize) {
+        self.playback
+            .pending_steps
+            .fetch_add(steps, Ordering::Relaxed);
+    }
+}
+
+impl Drop for StochasticStream {
+    fn drop(&mut self) {
+        self.playback.progress.cancel();
+    }
+}
+
+/// Thins a path to at most about `max_points` points by keeping every k-th point and the
+/// last one, so that plotting stays fast with millions of events.
+pub fn decimate(points: &[[f64; 2]], max_points: usize) -> Vec<[f64; 2]> {
+    let stride = points.len().div_ceil(max_points.max(1)).max(1);
+    let mut thinned: Vec<[f64; 2]> = points.iter().step_by(stride).copied().collect();
+    if points.len() > 1 && !(points.len() - 1).is_multiple_of(stride) {
+        thinned.extend(points.last());
+    }
+    thinned
+}
+
+/// Thins a time series to at most about `2 * buckets` points by keeping the lowest and
+/// highest point of each of `buckets` runs of consecutive points, in their original
+/// order, with the first and last points. Unlike [`decimate`], peaks and troughs survive.
+pub fn decimate_min_max(points: &[[f64; 2]], buckets: usize) -> Vec<[f64; 2]> {
+    let size = points.len().div_ceil(buckets.max(1)).max(1);
+    if size <= 2 {
+        return points.to_vec();
+    }
+    let last = points.len() - 1;
+    let mut thinned = vec![points[0]];
+    let mut kept = 0;
+    for (k, bucket) in points.chunks(size).enumerate() {
+        let by_value = |a: &usize, b: &usize| bucket[*a][1].total_cmp(&bucket[*b][1]);
+        let low = (0..bucket.len()).min_by(by_value).unwrap_or_default() + k * size;
+        let high = (0..bucket.len()).max_by(by_value).unwrap_or_default() + k * size;
+        for i in [low.min(high), low.max(high)] {
+            if i > kept && i < last {
+                thinned.push(points[i]);
+                kept = i;
+            }
+        }
+    }
+    thinned.push(points[last]);
+    thinned
+}
+
+/// Indices of the points of `bucket` with the lowest and highest of either coordinate,
+/// in order and without repeats.
+fn extremes(bucket: &[[f64; 2]]) -> Vec<usize> {
+    let mut kept: Vec<usize> = [0, 1]
+        .into_iter()
+        .flat_map(|axis| {
+            let by_axis = |a: &usize, b: &usize| bucket[*a][axis].total_cmp(&bucket[*b][axis]);
+            let indices = 0..bucket.len();
+            [indices.clone().min_by(by_axis), indices.max_by(by_axis)]
+        })
+        .flatten()
+        .collect();
+    kept.sort_unstable();
+    kept.dedup();
+    kept
+}
+
+/// A path thinned as it arrives, so that a long stream takes bounded memory. Points are
+/// gathered in buckets of consecutive points, and each bucket keeps only its points with
+/// the lowest and highest of either coordinate, in their original order, so that
+/// excursions survive. Once more than `capacity` points are kept, the bucket size doubles
+/// and the kept points are thinned again.
+#[derive(Debug, Clone)]
+pub struct ThinnedPath {
+    capacity: usize,
+    bucket_size: usize,
+    first: Option<[f64; 2]>,
+    kept: Vec<[f64; 2]>,
+    bucket: [Option<(usize, [f64; 2])>; 4], // Lowest and highest of each coordinate so far
+    bucket_len: usize,
+    last: Option<[f64; 2]>,
+    len: usize,
+}
+
+impl ThinnedPath {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(8),
+            bucket_size: 1,
+            first: None,
+            kept: Vec::new(),
+            bucket: [None; 4],
+            bucket_len: 0,
+            last: None,
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, point: [f64; 2]) {
+        if self.first.is_none() {
+            self.first = Some(point);
+        } else {
+            for (slot, candidate) in self.bucket.iter_mut().enumerate() {
+                let (axis, highest) = (slot / 2, slot % 2 == 1);
+                let replaces = candidate.is_none_or(|(_, kept)| {
+                    if highest {
+                        point[axis] > kept[axis]
+                    } else {
+                        point[axis] < kept[axis]
+                    }
                 });
-        });
+                if replaces {
+                    *candidate = Some((self.len, point));
+                }
+            }
+            self.bucket_len += 1;
+            if self.bucket_len == self.bucket_size {
+                let bucket = self.bucket_points();
+                self.kept.extend(bucket);
+                self.bucket = [None; 4];
+                self.bucket_len = 0;
+            }
+            if self.kept.len() > self.capacity {
+                // Every run of 8 kept points holds up to two buckets' worth of extremes
+                self.kept = self
+                    .kept
+                    .chunks(8)
+                    .flat_map(|chunk| extremes(chunk).into_iter().map(|i| chunk[i]))
+                    .collect();
+                self.bucket_size *= 2;
+            }
+        }
+        self.last = Some(point);
+        self.len += 1;
+    }
+
+    /// Number of points pushed since the path was created or cleared.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
     }
 
-    /// Handle window close event
-    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
-        println!("\nSimulation terminating...");
+    /// The most recent point.
+    pub fn last(&self) -> Option<[f64; 2]> {
+        self.last
+    }
+
+    /// Extremes of the bucket being filled, in order.
+    fn bucket_points(&self) -> Vec<[f64; 2]> {
+        let mut candidates: Vec<(usize, [f64; 2])> =
+            self.bucket.iter().flatten().copied().collect();
+        candidates.sort_unstable_by_key(|(i, _)| *i);
+        candidates.dedup_by_key(|(i, _)| *i);
+        candidates.into_iter().map(|(_, point)| point).collect()
+    }
+
+    /// The thinned path in order, from the first point to the last.
+    pub fn points(&self) -> Vec<[f64; 2]> {
+        let mut points: Vec<[f64; 2]> = self.first.into_iter().collect();
+        points.extend(&self.kept);
+        points.extend(self.bucket_points());
+        if self.len > 1 && points.last() != self.last.as_ref() {
+            points.extend(self.last);
+        }
+        points
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new(self.capacity);
+    }
+}
+
+impl Extend<[f64; 2]> for ThinnedPath {
+    fn extend<I: IntoIterator<Item = [f64; 2]>>(&mut self, points: I) {
+        for point in points {
+            self.push(point);
+        }
     }
 }
 
-pub fn launch_stochastic_gui() -> eframe::Result<()> {
-    let options = eframe::NativeOptions::default();
-    eframe::run_native(
-        "Lotka-Volterra Stochastic Simulation",
-        options,
-        Box::new(|_cc| Ok(Box::new(StochasticLotkaVolterraApp::new()))),
-    )
+/// Simulates the stochastic system starting at `t_start` while applying scheduled
+/// interventions. Step `i` corresponds to time `t_start + i * dt`.
+pub fn solve_stochastic_with_interventions(
+    params: &LotkaVolterraParams,
+    schedule: &Schedule,
+    t_start: f64,
+) -> Result<Vec<[f64; 2]>, SimulationError> {
+    schedule.validate()?;
+    let mut run = StochasticRun::with_schedule(params.clone(), rand::rng(), schedule, t_start);
+    let mut phase_points = Vec::with_capacity(params.n + 1);
+    phase_points.push(run.state());
+    phase_points.extend(run.advance(params.n));
+    Ok(phase_points)
+}
+
+/// Simulates the Lotka-Volterra system with the Gillespie algorithm from `t_start` to
+/// `t_end` of `params`, applying the scheduled interventions. The run restarts at each
+/// intervention, which is exact since the waiting times are memoryless, so both the
+/// state before and after it are recorded at the intervention time. The fraction of the
+/// time span covered is reported to `progress` at each intervention, and once `progress`
+/// is cancelled the simulation stops with [`SimulationError::UserCancelled`].
+pub fn simulate_gillespie_with_interventions(
+    params: &LotkaVolterraParameters,
+    schedule: &Schedule,
+    step: f64,
+    rng: &mut impl Rng,
+    progress: &Progress,
+) -> Result<(Vec<f64>, Vec<Vec<f64>>), SimulationError> {
+    schedule.validate()?;
+    let mut params = *params;
+    let mut times = Vec::new();
+    let mut states: Vec<Vec<f64>> = Vec::new();
+    let mut t0 = params.t_start;
+    let mut events = schedule.events(params.t_start, params.t_end).into_iter();
+    loop {
+        let event = events.next();
+        let stop = event.map_or(params.t_end, |(time, _)| time);
+        let (segment_times, segment_states) = simulate_gillespie_cancellable(
+            &LotkaVolterraSystem::new(params),
+            (t0, stop),
+            step,
+            rng,
+            progress,
+        );
+        if progress.is_cancelled() {
+            return Err(SimulationError::UserCancelled);
+        }
+        times.extend(segment_times);
+        states.extend(segment_states);
+        progress.set_fraction((stop - params.t_start) / (params.t_end - params.t_start));
+        let Some((time, action)) = event else {
+            break;
+        };
+        let last = states.last().map_or([0.0; 2], |s| [s[0], s[1]]);
+        let mut state = last;
+        action.apply(&mut params, &mut state)?;
+        params.initial_prey = state[0].round();
+        params.initial_predator = state[1].round();
+        t0 = time;
+    }
+    Ok((times, states))
+}
+
+/// Simulates any stochastic model exactly with the Gillespie algorithm. The state is
+/// recorded every `step` time units, and at `t_end` if it is off that grid, so the output
+/// size does not depend on the number of events.
+pub fn simulate_gillespie<M: StochasticModel>(
+    model: &M,
+    t0: f64,
+    t_end: f64,
+    step: f64,
+    rng: &mut impl Rng,
+) -> (Vec<f64>, Vec<Vec<f64>>) {
+    simulate_gillespie_cancellable(model, (t0, t_end), step, rng, &Progress::default())
+}
+
+/// Simulates the model like [`simulate_gillespie`] over `span`. Once `progress` is
+/// cancelled the simulation stops and returns the states recorded so far.
+pub fn simulate_gillespie_cancellable<M: StochasticModel>(
+    model: &M,
+    span: (f64, f64),
+    step: f64,
+    rng: &mut impl Rng,
+    progress: &Progress,
+) -> (Vec<f64>, Vec<Vec<f64>>) {
+    let (t0, t_end) = span;
+    let mut state = model.initial_state();
+    let mut transitions: Vec<Transition> = Vec::new();
+    let mut times = vec![t0];
+    let mut states = vec![state.clone()];
+    let mut t = t0;
+    let mut next_output = t0 + step;
+
+    while t < t_end {
+        if progress.is_cancelled() {
+            return (times, states);
+        }
+        transitions.clear();
+        model.transitions(&state, &mut transitions);
+        let total: f64 = transitions.iter().map(|tr| tr.rate).sum();
+        let next_t = if total > 0.0 {
+            t - (1.0 - rng.random::<f64>()).ln() / total
+        } else {
+            f64::INFINITY
+        };
+
+        // Record the current state on every output time before the next event
+        while next_output <= next_t.min(t_end) + 1e-12 {
+            times.push(next_output);
+            states.push(state.clone());
+            next_output += step;
+        }
+        if next_t >= t_end {
+            break;
+        }
+
+        let mut target = rng.random::<f64>() * total;
+        let chosen = transitions
+            .iter()
+            .find(|tr| {
+                target -= tr.rate;
+                target <= 0.0
+            })
+            .unwrap_or(&transitions[transitions.len() - 1]);
+        for &(index, change) in &chosen.changes {
+            state[index] = (state[index] + change).max(0.0);
+        }
+        t = next_t;
+    }
+    if times.last().is_some_and(|&last| last < t_end - 1e-12) {
+        times.push(t_end);
+        states.push(state);
+    }
+
+    (times, states)
+}
+
+/// Advances the populations by one time step, triggering at most one event.
+fn stochastic_step(
+    params: &LotkaVolterraParams,
+    rng: &mut impl Rng,
+    prey: &mut f64,
+    predators: &mut f64,
+) {
+    let x: f64 = rng.random();
+
+    if x <= params.alpha * *prey * params.dt {
+        // Prey reproduces
+        *prey += 1.0;
+    } else if x <= params.alpha * *prey * params.dt + params.delta * *predators * params.dt {
+        // Predators die
+        *predators -= 1.0;
+    } else if x
+        <= params.alpha * *prey * params.dt
+            + params.delta * *predators * params.dt
+            + params.beta * *prey * *predators * params.dt
+    {
+        // Prey die
+        *prey -= 1.0;
+    } else if x
+        <= params.alpha * *prey * params.dt
+            + params.delta * *predators * params.dt
+            + params.beta * *prey * *predators * params.dt
+            + params.gamma * *prey * *predators * params.dt
+    {
+        // Predators reproduce
+        *predators += 1.0;
+    }
 }
 
 #[cfg(test)]
@@ -254,4 +629,214 @@ mod tests {
             "Initial values should match the input parameters."
         );
     }
+
+    #[test]
+    fn test_run_advances_in_chunks_and_decimates() {
+        use rand::SeedableRng;
+
+        let params = LotkaVolterraParams {
+            alpha: 0.1,
+            beta: 0.02,
+            gamma: 0.02,
+            delta: 0.1,
+            dt: 0.01,
+            n: 250,
+            initial_prey: 100.0,
+            initial_predator: 50.0,
+        };
+        let mut run = StochasticRun::new(params, rand::rngs::StdRng::seed_from_u64(3));
+        assert_eq!(run.advance(100).len(), 100);
+        assert_eq!(run.advance(1000).len(), 150, "Stops at the end of the run.");
+        assert!(run.is_finished() && run.advance(10).is_empty());
+
+        let points: Vec<[f64; 2]> = (0..1_000_001).map(|i| [i as f64, 0.0]).collect();
+        let thinned = decimate(&points, 5000);
+        assert!(thinned.len() <= 5001);
+        assert_eq!(thinned[0], [0.0, 0.0]);
+        assert_eq!(thinned.last(), points.last());
+    }
+
+    #[test]
+    fn test_min_max_decimation_keeps_peaks() {
+        // A narrow spike every 1000 points that stride thinning would step over
+        let points: Vec<[f64; 2]> = (0..100_000)
+            .map(|i| [i as f64, if i % 1000 == 517 { 10.0 } else { -(i as f64) }])
+            .collect();
+        let thinned = decimate_min_max(&points, 500);
+        assert!(thinned.len() <= 1002);
+        assert_eq!(thinned.iter().filter(|p| p[1] == 10.0).count(), 100);
+        assert!(
+            thinned.windows(2).all(|w| w[0][0] < w[1][0]),
+            "Points stay in order."
+        );
+        assert_eq!((thinned[0], thinned.last()), (points[0], points.last()));
+        assert_eq!(decimate_min_max(&points[..3], 500), &points[..3]);
+    }
+
+    #[test]
+    fn test_thinned_path_stays_bounded_and_keeps_excursions() {
+        let mut path = ThinnedPath::new(1000);
+        // A one-point excursion in each coordinate that stride thinning would step over
+        path.extend((0..200_000).map(|i| match i {
+            123_457 => [500.0, 0.0],
+            54_321 => [0.0, -500.0],
+            i => [(i as f64 * 0.01).sin(), (i as f64 * 0.01).cos()],
+        }));
+        let points = path.points();
+        assert_eq!(path.len(), 200_000);
+        // The kept points, the extremes of the bucket being filled and both ends
+        assert!(
+            points.len() <= 1000 + 4 + 2,
+            "Kept {} points.",
+            points.len()
+        );
+        assert!(points.contains(&[500.0, 0.0]) && points.contains(&[0.0, -500.0]));
+        assert_eq!(points[0], [0.0, 1.0]);
+        assert_eq!(points.last().copied(), path.last());
+    }
+
+    #[test]
+    fn test_stream_sends_all_points_and_steps_while_paused() {
+        let params = LotkaVolterraParams {
+            alpha: 0.1,
+            beta: 0.02,
+            gamma: 0.02,
+            delta: 0.1,
+            dt: 0.01,
+            n: 500,
+            initial_prey: 100.0,
+            initial_predator: 50.0,
+        };
+        let mut stream = StochasticStream::start(params, &Schedule::default(), 0.0, 200, true);
+        stream.step(50);
+        let start = std::time::Instant::now();
+        let mut points = Vec::new();
+        while points.len() < 51 {
+            assert!(start.elapsed() < Duration::from_secs(5), "Step timed out.");
+            points.extend(stream.receive());
+        }
+        assert_eq!(points[0], [100.0, 50.0]);
+
+        stream.set_paused(false);
+        while !stream.is_finished() {
+            assert!(
+                start.elapsed() < Duration::from_secs(5),
+                "Stream timed out."
+            );
+            points.extend(stream.receive());
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(points.len(), 501);
+        assert_eq!(stream.progress(), 1.0);
+    }
+
+    #[test]
+    fn test_gillespie_records_output_grid() {
+        use crate::models::LotkaVolterraSystem;
+        use rand::SeedableRng;
+
+        let params = LotkaVolterraParameters {
+            alpha: 0.1,
+            beta: 0.002,
+            delta: 0.002,
+            gamma: 0.1,
+            initial_prey: 50.0,
+            initial_predator: 50.0,
+            t_start: 0.0,
+            t_end: 10.0,
+        };
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        let (times, states) =
+            simulate_gillespie(&LotkaVolterraSystem::new(params), 0.0, 10.0, 1.0, &mut rng);
+
+        assert_eq!(times.len(), 11);
+        assert_eq!(states[0], vec![50.0, 50.0]);
+        assert!(states.iter().flatten().all(|n| n.fract() == 0.0));
+    }
+
+    #[test]
+    fn test_stochastic_interventions_apply() {
+        use crate::interventions::{Intervention, InterventionAction};
+        use crate::models::Species;
+
+        let params = LotkaVolterraParams {
+            alpha: 0.0,
+            beta: 0.0,
+            gamma: 0.0,
+            delta: 0.0,
+            dt: 1.0,
+            n: 10,
+            initial_prey: 100.0,
+            initial_predator: 50.0,
+        };
+        let schedule = Schedule {
+            interventions: vec![Intervention {
+                time: 5.0,
+                every: None,
+                action: InterventionAction::Add {
+                    species: Species::Prey,
+                    amount: 500.0,
+                },
+            }],
+        };
+
+        let result = solve_stochastic_with_interventions(&params, &schedule, 0.0).unwrap();
+        assert_eq!(result[5], [100.0, 50.0]);
+        assert_eq!(result[6], [600.0, 50.0]);
+
+        // The streamed simulation applies the same schedule
+        let mut stream = StochasticStream::start(params, &schedule, 0.0, 100, false);
+        let start = std::time::Instant::now();
+        let mut points = Vec::new();
+        while !stream.is_finished() {
+            assert!(
+                start.elapsed() < Duration::from_secs(5),
+                "Stream timed out."
+            );
+            points.extend(stream.receive());
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(points, result);
+
+        // And so does the Gillespie simulation, recording both sides of the event
+        let params = LotkaVolterraParameters {
+            alpha: 0.0,
+            beta: 0.0,
+            delta: 0.0,
+            gamma: 0.0,
+            initial_prey: 100.0,
+            initial_predator: 50.0,
+            t_start: 0.0,
+            t_end: 10.0,
+        };
+        let progress = Progress::default();
+        let (times, states) = simulate_gillespie_with_interventions(
+            &params,
+            &schedule,
+            1.0,
+            &mut rand::rng(),
+            &progress,
+        )
+        .unwrap();
+        let at_five: Vec<&Vec<f64>> = times
+            .iter()
+            .zip(&states)
+            .filter(|(t, _)| **t == 5.0)
+            .map(|(_, s)| s)
+            .collect();
+        assert_eq!(at_five, [&vec![100.0, 50.0], &vec![600.0, 50.0]]);
+        assert_eq!(times.last(), Some(&10.0));
+        assert_eq!(states.last(), Some(&vec![600.0, 50.0]));
+        assert_eq!(progress.fraction(), 1.0);
+
+        progress.cancel();
+        let cancelled = simulate_gillespie_with_interventions(
+            &params,
+            &schedule,
+            1.0,
+            &mut rand::rng(),
+            &progress,
+        );
+        assert!(matches!(cancelled, Err(SimulationError::UserCancelled)));
+    }
 }
@@ -0,0 +1,161 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Progress and cancellation flag shared between a job and the thread that started it.
+#[derive(Debug, Default)]
+pub struct Progress {
+    fraction: AtomicU32,
+    cancelled: AtomicBool,
+}
+
+impl Progress {
+    /// Fraction of the work done, between 0 and 1.
+    pub fn fraction(&self) -> f32 {
+        f32::from_bits(self.fraction.load(Ordering::Relaxed))
+    }
+
+    /// Records the fraction of the work done.
+    pub fn set_fraction(&self, fraction: f64) {
+        let fraction = fraction.clamp(0.0, 1.0) as f32;
+        self.fraction.store(fraction.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Asks the job to stop at its next check.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// A computation running on a worker thread. Its result is collected without blocking
+/// with [`Job::try_result`]; dropping the job cancels it.
+pub struct Job<T> {
+    receiver: Receiver<T>,
+    progress: Arc<Progress>,
+}
+
+impl<T: Send + 'static> Job<T> {
+    /// Starts `work` on a new thread. The work should check [`Progress::is_cancelled`]
+    /// regularly and may report its progress.
+    pub fn spawn(work: impl FnOnce(&Progress) -> T + Send + 'static) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let progress = Arc::new(Progress::default());
+        let shared = Arc::clone(&progress);
+        thread::spawn(move || {
+            let result = work(&shared);
+            // The receiver is gone if the job was dropped, in which case nobody needs it
+            sender.send(result).ok();
+        });
+        Self { receiver, progress }
+    }
+
+    /// Fraction of the work done.
+    pub fn progress(&self) -> f32 {
+        self.progress.fraction()
+    }
+
+    /// The result, once the work has finished.
+    pub fn try_result(&self) -> Option<T> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+impl<T> Drop for Job<T> {
+    fn drop(&mut self) {
+        self.progress.cancel();
+    }
+}
+
+/// Delays an action until its trigger has been quiet for a while, so that dragging a
+/// slider starts one solve rather than one per frame.
+#[derive(Debug, Clone, Copy)]
+pub struct Debouncer {
+    delay: Duration,
+    last_trigger: Option<Instant>,
+}
+
+impl Debouncer {
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            last_trigger: None,
+        }
+    }
+
+    /// Records a change, restarting the quiet period.
+    pub fn trigger(&mut self) {
+        self.last_trigger = Some(Instant::now());
+    }
+
+    /// Whether a change is waiting for its quiet period to end.
+    pub fn is_pending(&self) -> bool {
+        self.last_trigger.is_some()
+    }
+
+    /// Returns true once, when the quiet period after the last change has passed.
+    pub fn ready(&mut self) -> bool {
+        match self.last_trigger {
+            Some(time) if time.elapsed() >= self.delay => {
+                self.last_trigger = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Time left until the pending change is ready.
+    pub fn remaining(&self) -> Option<Duration> {
+        self.last_trigger
+            .map(|time| self.delay.saturating_sub(time.elapsed()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_returns_result_and_cancels_on_drop() {
+        let job = Job::spawn(|progress| {
+            progress.set_fraction(1.0);
+            21 * 2
+        });
+        let start = Instant::now();
+        let result = loop {
+            if let Some(result) = job.try_result() {
+                break result;
+            }
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::yield_now();
+        };
+        assert_eq!(result, 42);
+        assert_eq!(job.progress(), 1.0);
+
+        let (sender, receiver) = mpsc::channel();
+        let job = Job::spawn(move |progress| {
+            while !progress.is_cancelled() {
+                thread::sleep(Duration::from_millis(1));
+            }
+            sender.send(()).ok();
+        });
+        drop(job);
+        assert!(receiver.recv_timeout(Duration::from_secs(5)).is_ok());
+    }
+
+    #[test]
+    fn test_debouncer_waits_for_quiet_period() {
+        let mut debouncer = Debouncer::new(Duration::from_millis(20));
+        assert!(!debouncer.ready());
+        debouncer.trigger();
+        assert!(!debouncer.ready() && debouncer.is_pending());
+        thread::sleep(Duration::from_millis(30));
+        assert!(debouncer.ready());
+        assert!(!debouncer.ready(), "Ready only once per change.");
+    }
+}